@@ -0,0 +1,740 @@
+use crate::firebase::{DailyModificationGroup, FirebaseClient, LogEntry};
+use crate::inventory::fetch_grouped_levels;
+use crate::utils::{AppConfig, AppError};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// Operations and net change within one hour of a day (shop timezone)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct HourBucket {
+    pub hour: u32,
+    pub count: u32,
+    pub net_change: i32,
+}
+
+/// A product referenced by an analytics result
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ProductRef {
+    pub product_id: String,
+    pub name: String,
+    pub shared_days: u32,
+}
+
+/// Purchasing aid for one variant: reorder when stock at the location drops to `reorder_point`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ReorderPoint {
+    pub inventory_item_id: String,
+    pub variant: String,
+    pub avg_daily_sales: f64,
+    pub std_dev: f64,
+    pub safety_stock: f64,
+    pub reorder_point: f64,
+}
+
+/// z-score for a 95% service level
+pub const SERVICE_LEVEL_Z: f64 = 1.65;
+/// Days between placing a supplier order and receiving it
+pub const DEFAULT_LEAD_TIME_DAYS: f64 = 7.0;
+
+/// A log entry with values that should be impossible, and why it was flagged
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnomalousLog {
+    pub log: LogEntry,
+    pub reasons: Vec<String>,
+}
+
+/// Largest plausible absolute `rettifica` for a single logged operation
+pub const MAX_SANE_RETTIFICA: i32 = 1000;
+
+/// Net change of one product over a reporting period
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ProductNetChange {
+    pub product_id: String,
+    pub name: String,
+    pub net_change: i32,
+    pub operations: u32,
+}
+
+/// Totals of a location's logs over a period, for reports and charts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InventoryStatistics {
+    pub location: String,
+    pub start_date: String,
+    pub end_date: String,
+    pub per_product: Vec<ProductNetChange>, // Largest decrease first
+    pub per_request_type: HashMap<String, i32>, // Rettifica / Annullamento / Trasferimento
+    pub units_decreased: i32,
+    pub daily: Vec<DailyModificationGroup>, // Most recent day first
+}
+
+// ============================================================================
+// LOG-DERIVED METRICS
+// ============================================================================
+
+/// Units sold in a set of logs: decreases ("Rettifica") net of their undos ("Annullamento").
+/// Transfers move stock between stores and count corrections ("Riconciliazione") only fix
+/// the books, so neither is a sale.
+pub fn units_sold_from_logs(logs: &[LogEntry]) -> i32 {
+    let net: i32 = logs
+        .iter()
+        .filter(|log| log.request_type == "Rettifica" || log.request_type == "Annullamento")
+        .map(|log| log.data.rettifica)
+        .sum();
+
+    (-net).max(0)
+}
+
+/// Turnover = units sold / average inventory.
+/// The opening quantity is reconstructed by reversing the period's net change from the
+/// current quantity, and the average is taken between opening and closing levels.
+pub fn calculate_turnover(units_sold: i32, current_quantity: i32, period_net_change: i32) -> f64 {
+    if units_sold <= 0 {
+        return 0.0;
+    }
+
+    let opening_quantity = current_quantity - period_net_change;
+    let average_inventory = (opening_quantity + current_quantity) as f64 / 2.0;
+
+    if average_inventory <= 0.0 {
+        return 0.0;
+    }
+
+    units_sold as f64 / average_inventory
+}
+
+/// Sell-through rate as a percentage: units sold / (units sold + units remaining) × 100.
+/// Negative stock counts as nothing remaining; no sales and no stock gives 0.
+pub fn calculate_sell_through(units_sold: i32, units_remaining: i32) -> f64 {
+    let units_sold = units_sold.max(0);
+    let total = units_sold + units_remaining.max(0);
+    if total == 0 {
+        return 0.0;
+    }
+
+    units_sold as f64 / total as f64 * 100.0
+}
+
+/// Aggregate `logs` per product and per request type, with the total of units taken out
+/// of stock (every negative `rettifica`, whatever its type) and a per-day breakdown
+pub fn compute_inventory_statistics(
+    logs: &[LogEntry],
+    location: &str,
+    start_date: &str,
+    end_date: &str,
+) -> InventoryStatistics {
+    let mut per_product: HashMap<String, ProductNetChange> = HashMap::new();
+    let mut per_request_type: HashMap<String, i32> = HashMap::new();
+
+    for log in logs {
+        let product = per_product
+            .entry(log.data.id.clone())
+            .or_insert_with(|| ProductNetChange {
+                product_id: log.data.id.clone(),
+                name: log.data.nome.clone(),
+                net_change: 0,
+                operations: 0,
+            });
+        product.net_change += log.data.rettifica;
+        product.operations += 1;
+
+        *per_request_type
+            .entry(log.request_type.clone())
+            .or_insert(0) += log.data.rettifica;
+    }
+
+    let mut per_product: Vec<ProductNetChange> = per_product.into_values().collect();
+    per_product.sort_by(|a, b| {
+        a.net_change
+            .cmp(&b.net_change)
+            .then_with(|| a.name.cmp(&b.name))
+    });
+
+    let units_decreased = logs
+        .iter()
+        .filter(|log| log.data.rettifica < 0)
+        .map(|log| -log.data.rettifica)
+        .sum();
+
+    let log_refs: Vec<&LogEntry> = logs.iter().collect();
+    let daily = crate::inventory::group_modifications_by_date(&log_refs, &[]);
+
+    InventoryStatistics {
+        location: location.to_string(),
+        start_date: start_date.to_string(),
+        end_date: end_date.to_string(),
+        per_product,
+        per_request_type,
+        units_decreased,
+        daily,
+    }
+}
+
+/// Store/day pairs on which each product was sold (a "Rettifica" decrease).
+/// Days are taken from the timestamp's date part.
+fn sale_days_by_product(logs: &[LogEntry]) -> HashMap<String, HashSet<(String, String)>> {
+    let mut days: HashMap<String, HashSet<(String, String)>> = HashMap::new();
+
+    for log in logs {
+        if log.request_type != "Rettifica" || log.data.rettifica >= 0 {
+            continue;
+        }
+        let day = log.timestamp.split('T').next().unwrap_or("").to_string();
+        days.entry(log.data.id.clone())
+            .or_default()
+            .insert((log.data.negozio.clone(), day));
+    }
+
+    days
+}
+
+/// Products most often sold on the same day at the same store as `product_id`,
+/// ranked by the number of shared store-days (ties broken by name).
+/// This is day-level co-occurrence, not basket-level: logs carry no receipt id.
+pub fn frequently_sold_together(
+    logs: &[LogEntry],
+    product_id: &str,
+    limit: usize,
+) -> Vec<ProductRef> {
+    let sale_days = sale_days_by_product(logs);
+    let Some(target_days) = sale_days.get(product_id) else {
+        return Vec::new();
+    };
+
+    let names: HashMap<&str, &str> = logs
+        .iter()
+        .map(|log| (log.data.id.as_str(), log.data.nome.as_str()))
+        .collect();
+
+    let mut related: Vec<ProductRef> = sale_days
+        .iter()
+        .filter(|(id, _)| id.as_str() != product_id)
+        .filter_map(|(id, days)| {
+            let shared_days = days.intersection(target_days).count() as u32;
+            (shared_days > 0).then(|| ProductRef {
+                product_id: id.clone(),
+                name: names.get(id.as_str()).unwrap_or(&"").to_string(),
+                shared_days,
+            })
+        })
+        .collect();
+
+    related.sort_by(|a, b| {
+        b.shared_days
+            .cmp(&a.shared_days)
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    related.truncate(limit);
+    related
+}
+
+/// Mean and population standard deviation of a series (zeros for an empty one)
+pub fn mean_and_std_dev(series: &[f64]) -> (f64, f64) {
+    if series.is_empty() {
+        return (0.0, 0.0);
+    }
+
+    let n = series.len() as f64;
+    let mean = series.iter().sum::<f64>() / n;
+    let variance = series.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / n;
+    (mean, variance.sqrt())
+}
+
+/// Reorder point from a daily-sales series: expected demand over the lead time plus a
+/// safety stock of `z·σ·√lead_time`
+pub fn reorder_point_from_series(
+    daily_sales: &[f64],
+    lead_time_days: f64,
+    z: f64,
+) -> (f64, f64, f64, f64) {
+    let (mean, std_dev) = mean_and_std_dev(daily_sales);
+    let safety_stock = z * std_dev * lead_time_days.sqrt();
+    let reorder_point = mean * lead_time_days + safety_stock;
+    (mean, std_dev, safety_stock, reorder_point)
+}
+
+/// Reorder points per variant from the logs of the last `days` days ending on `end_date`
+/// (YYYY-MM-DD). Days without sales count as zero; variants that never sold are skipped.
+pub fn compute_reorder_points_from_logs(
+    logs: &[LogEntry],
+    days: i32,
+    end_date: chrono::NaiveDate,
+    lead_time_days: f64,
+) -> Vec<ReorderPoint> {
+    let mut sales: HashMap<&str, (String, HashMap<String, i32>)> = HashMap::new();
+    for log in logs {
+        if log.request_type != "Rettifica" && log.request_type != "Annullamento" {
+            continue;
+        }
+        let day = log.timestamp.split('T').next().unwrap_or("").to_string();
+        let entry = sales
+            .entry(log.data.inventory_item_id.as_str())
+            .or_insert_with(|| {
+                (
+                    format!("{} - {}", log.data.nome, log.data.variant),
+                    HashMap::new(),
+                )
+            });
+        // Decreases are negative; keep units sold positive
+        *entry.1.entry(day).or_default() -= log.data.rettifica;
+    }
+
+    let period: Vec<String> = (0..days.max(0) as i64)
+        .rev()
+        .filter_map(|offset| end_date.checked_sub_signed(chrono::Duration::days(offset)))
+        .map(|day| day.format("%Y-%m-%d").to_string())
+        .collect();
+
+    let mut points: Vec<ReorderPoint> = sales
+        .into_iter()
+        .filter_map(|(inventory_item_id, (variant, by_day))| {
+            let series: Vec<f64> = period
+                .iter()
+                .map(|day| by_day.get(day).copied().unwrap_or(0).max(0) as f64)
+                .collect();
+            if series.iter().all(|units| *units == 0.0) {
+                return None;
+            }
+
+            let (avg_daily_sales, std_dev, safety_stock, reorder_point) =
+                reorder_point_from_series(&series, lead_time_days, SERVICE_LEVEL_Z);
+            Some(ReorderPoint {
+                inventory_item_id: inventory_item_id.to_string(),
+                variant,
+                avg_daily_sales,
+                std_dev,
+                safety_stock,
+                reorder_point,
+            })
+        })
+        .collect();
+
+    points.sort_by(|a, b| b.reorder_point.total_cmp(&a.reorder_point));
+    points
+}
+
+/// Flag logs with out-of-range adjustments, missing product names, unparseable
+/// timestamps or inventory items that don't belong to the logged product (an item id
+/// must be numeric and always appear with the same product id)
+pub fn find_anomalies(logs: &[LogEntry]) -> Vec<AnomalousLog> {
+    // Product most logs attribute each inventory item to
+    let mut products_by_item: HashMap<&str, HashMap<&str, usize>> = HashMap::new();
+    for log in logs {
+        *products_by_item
+            .entry(log.data.inventory_item_id.as_str())
+            .or_default()
+            .entry(log.data.id.as_str())
+            .or_default() += 1;
+    }
+    let owner_of = |item: &str| {
+        products_by_item.get(item).and_then(|counts| {
+            counts
+                .iter()
+                .max_by(|a, b| a.1.cmp(b.1).then_with(|| b.0.cmp(a.0)))
+                .map(|(product_id, _)| *product_id)
+        })
+    };
+
+    logs.iter()
+        .filter_map(|log| {
+            let mut reasons = Vec::new();
+
+            if log.data.rettifica.abs() > MAX_SANE_RETTIFICA {
+                reasons.push(format!(
+                    "Rettifica {} outside ±{}",
+                    log.data.rettifica, MAX_SANE_RETTIFICA
+                ));
+            }
+            if log.data.nome.trim().is_empty() {
+                reasons.push("Empty product name".to_string());
+            }
+            if chrono::DateTime::parse_from_rfc3339(&log.timestamp).is_err() {
+                reasons.push(format!("Unparseable timestamp '{}'", log.timestamp));
+            }
+            if log.data.inventory_item_id.parse::<u64>().is_err() {
+                reasons.push(format!(
+                    "Invalid inventory item id '{}'",
+                    log.data.inventory_item_id
+                ));
+            } else if owner_of(&log.data.inventory_item_id) != Some(log.data.id.as_str()) {
+                reasons.push(format!(
+                    "Inventory item {} is logged under another product",
+                    log.data.inventory_item_id
+                ));
+            }
+
+            (!reasons.is_empty()).then(|| AnomalousLog {
+                log: log.clone(),
+                reasons,
+            })
+        })
+        .collect()
+}
+
+/// Bucket logs into 24 hourly bins by the hour of their timestamp in `tz`.
+/// Logs with an unparseable timestamp are skipped.
+pub fn bucket_logs_by_hour(logs: &[LogEntry], tz: chrono_tz::Tz) -> Vec<HourBucket> {
+    use chrono::Timelike;
+
+    let mut buckets: Vec<HourBucket> = (0..24)
+        .map(|hour| HourBucket {
+            hour,
+            count: 0,
+            net_change: 0,
+        })
+        .collect();
+
+    for log in logs {
+        let Ok(parsed) = chrono::DateTime::parse_from_rfc3339(&log.timestamp) else {
+            continue;
+        };
+        let bucket = &mut buckets[parsed.with_timezone(&tz).hour() as usize];
+        bucket.count += 1;
+        bucket.net_change += log.data.rettifica;
+    }
+
+    buckets
+}
+
+/// UTC RFC3339 bounds of a `YYYY-MM-DD` day in `tz` (handles DST-length days)
+pub fn day_bounds_utc(date: &str, tz: chrono_tz::Tz) -> Result<(String, String), String> {
+    let day = chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .map_err(|e| format!("Invalid date '{}': {}", date, e))?;
+    let next_day = day
+        .succ_opt()
+        .ok_or_else(|| format!("Invalid date '{}'", date))?;
+
+    let start_of = |d: chrono::NaiveDate| {
+        d.and_hms_opt(0, 0, 0)
+            .and_then(|midnight| midnight.and_local_timezone(tz).earliest())
+            .map(|local| local.with_timezone(&chrono::Utc).to_rfc3339())
+            .ok_or_else(|| format!("Invalid local midnight for {}", d))
+    };
+
+    Ok((start_of(day)?, start_of(next_day)?))
+}
+
+// ============================================================================
+// SHARED DATA LOADING
+// ============================================================================
+
+/// Fetch a product's logs for the last `days` days and its current quantity at `location`
+async fn load_product_period(
+    client: &tauri::State<'_, reqwest::Client>,
+    config: &tauri::State<'_, AppConfig>,
+    product_id: &str,
+    location: &str,
+    days: i32,
+) -> Result<(Vec<LogEntry>, i32), String> {
+    let end_date = chrono::Utc::now();
+    let start_date = end_date - chrono::Duration::days(days as i64);
+
+    let firebase_client = FirebaseClient::new(client, config.inner().clone());
+    let logs = firebase_client
+        .get_logs_by_product_id(
+            product_id.to_string(),
+            location.to_string(),
+            start_date.to_rfc3339(),
+            end_date.to_rfc3339(),
+        )
+        .await?;
+
+    let product =
+        crate::products::get_product_by_id(client.clone(), config.clone(), product_id.to_string())
+            .await?;
+    let inventory_item_ids: Vec<String> = product
+        .variants
+        .iter()
+        .map(|v| v.inventory_item_id.clone())
+        .collect();
+
+    let inventory_levels =
+        fetch_grouped_levels(client, config, &inventory_item_ids, location).await?;
+
+    let current_quantity: i32 = inventory_levels
+        .aliases
+        .values()
+        .filter_map(|levels| levels.get("primary"))
+        .sum();
+
+    Ok((logs, current_quantity))
+}
+
+/// Fetch a location's logs for `date` (YYYY-MM-DD) in the shop timezone, which is
+/// returned alongside them
+pub(crate) async fn load_day_logs(
+    client: &tauri::State<'_, reqwest::Client>,
+    config: &tauri::State<'_, AppConfig>,
+    shop_info: &tauri::State<'_, crate::status::ShopInfoCache>,
+    location: &str,
+    date: &str,
+) -> Result<(Vec<LogEntry>, chrono_tz::Tz), String> {
+    let timezone = shop_info
+        .get()
+        .map(|info| info.timezone)
+        .unwrap_or_else(|| "Europe/Rome".to_string());
+    let tz: chrono_tz::Tz = timezone
+        .parse()
+        .map_err(|e| format!("Invalid shop timezone '{}': {}", timezone, e))?;
+
+    let (start_date, end_date) = day_bounds_utc(date, tz)?;
+
+    let firebase_client = FirebaseClient::new(client, config.inner().clone());
+    let logs = firebase_client
+        .get_logs_date_range(None, location.to_string(), start_date, end_date.clone())
+        .await?;
+
+    // The date-range query's upper bound is inclusive, so drop anything from the next day
+    let logs = logs
+        .into_iter()
+        .filter(|log| {
+            match (
+                chrono::DateTime::parse_from_rfc3339(&log.timestamp),
+                chrono::DateTime::parse_from_rfc3339(&end_date),
+            ) {
+                (Ok(ts), Ok(end)) => ts < end,
+                _ => true,
+            }
+        })
+        .collect();
+
+    Ok((logs, tz))
+}
+
+// ============================================================================
+// TAURI COMMANDS FOR ANALYTICS
+// ============================================================================
+
+/// Inventory turnover ratio for a product at a location over the last `days` days
+#[tauri::command]
+pub async fn get_turnover(
+    product_id: String,
+    location: String,
+    days: i32,
+    client: tauri::State<'_, reqwest::Client>,
+    config: tauri::State<'_, AppConfig>,
+) -> Result<f64, AppError> {
+    if days < 1 {
+        return Err(AppError::Other("days must be at least 1".to_string()));
+    }
+
+    println!(
+        "📈 Computing turnover for product {} at {} over {} days",
+        product_id, location, days
+    );
+
+    let (logs, current_quantity) =
+        load_product_period(&client, &config, &product_id, &location, days).await?;
+
+    let units_sold = units_sold_from_logs(&logs);
+    let period_net_change: i32 = logs.iter().map(|log| log.data.rettifica).sum();
+    let turnover = calculate_turnover(units_sold, current_quantity, period_net_change);
+
+    println!(
+        "✅ Turnover: {:.2} ({} sold, {} on hand)",
+        turnover, units_sold, current_quantity
+    );
+    Ok(turnover)
+}
+
+/// Sell-through rate (%) for a product at a location over the last `days` days
+#[tauri::command]
+pub async fn get_sell_through(
+    product_id: String,
+    location: String,
+    days: i32,
+    client: tauri::State<'_, reqwest::Client>,
+    config: tauri::State<'_, AppConfig>,
+) -> Result<f64, AppError> {
+    if days < 1 {
+        return Err(AppError::Other("days must be at least 1".to_string()));
+    }
+
+    println!(
+        "📈 Computing sell-through for product {} at {} over {} days",
+        product_id, location, days
+    );
+
+    let (logs, current_quantity) =
+        load_product_period(&client, &config, &product_id, &location, days).await?;
+
+    let units_sold = units_sold_from_logs(&logs);
+    let sell_through = calculate_sell_through(units_sold, current_quantity);
+
+    println!(
+        "✅ Sell-through: {:.1}% ({} sold, {} remaining)",
+        sell_through, units_sold, current_quantity
+    );
+    Ok(sell_through)
+}
+
+/// Products frequently sold on the same days and store as `product_id` over the last `days` days
+#[tauri::command]
+pub async fn get_frequently_sold_together(
+    product_id: String,
+    days: i32,
+    limit: usize,
+    client: tauri::State<'_, reqwest::Client>,
+    config: tauri::State<'_, AppConfig>,
+) -> Result<Vec<ProductRef>, AppError> {
+    if days < 1 {
+        return Err(AppError::Other("days must be at least 1".to_string()));
+    }
+
+    println!(
+        "🛒 Finding products sold together with {} over {} days",
+        product_id, days
+    );
+
+    let end_date = chrono::Utc::now();
+    let start_date = end_date - chrono::Duration::days(days as i64);
+
+    let firebase_client = FirebaseClient::new(&client, config.inner().clone());
+    let mut logs = Vec::new();
+    for (negozio, _) in config.named_locations() {
+        logs.extend(
+            firebase_client
+                .get_logs_date_range(
+                    None,
+                    negozio,
+                    start_date.to_rfc3339(),
+                    end_date.to_rfc3339(),
+                )
+                .await
+                .map_err(AppError::Firebase)?,
+        );
+    }
+
+    let related = frequently_sold_together(&logs, &product_id, limit);
+    println!("✅ Found {} related products", related.len());
+    Ok(related)
+}
+
+/// Operations per hour of `date` (YYYY-MM-DD) at a location, in the shop timezone
+#[tauri::command]
+pub async fn get_hourly_activity(
+    location: String,
+    date: String,
+    client: tauri::State<'_, reqwest::Client>,
+    config: tauri::State<'_, AppConfig>,
+    shop_info: tauri::State<'_, crate::status::ShopInfoCache>,
+) -> Result<Vec<HourBucket>, AppError> {
+    let (logs, tz) = load_day_logs(&client, &config, &shop_info, &location, &date).await?;
+    println!("🕐 Hourly activity for {} on {} ({})", location, date, tz);
+
+    let buckets = bucket_logs_by_hour(&logs, tz);
+    println!("✅ Bucketed {} logs into hourly bins", logs.len());
+    Ok(buckets)
+}
+
+/// Per-variant reorder points at a location from the sales of the last `days` days
+#[tauri::command]
+pub async fn compute_reorder_points(
+    location: String,
+    days: i32,
+    client: tauri::State<'_, reqwest::Client>,
+    config: tauri::State<'_, AppConfig>,
+) -> Result<Vec<ReorderPoint>, AppError> {
+    if days < 1 {
+        return Err(AppError::Other("days must be at least 1".to_string()));
+    }
+
+    println!(
+        "📦 Computing reorder points for {} over {} days",
+        location, days
+    );
+
+    let end_date = chrono::Utc::now();
+    let start_date = end_date - chrono::Duration::days(days as i64 - 1);
+    let start_of_period = start_date
+        .date_naive()
+        .and_hms_opt(0, 0, 0)
+        .unwrap_or_default();
+
+    let firebase_client = FirebaseClient::new(&client, config.inner().clone());
+    let logs = firebase_client
+        .get_logs_date_range(
+            None,
+            location,
+            start_of_period.and_utc().to_rfc3339(),
+            end_date.to_rfc3339(),
+        )
+        .await
+        .map_err(AppError::Firebase)?;
+
+    let points = compute_reorder_points_from_logs(
+        &logs,
+        days,
+        end_date.date_naive(),
+        DEFAULT_LEAD_TIME_DAYS,
+    );
+    println!("✅ Computed reorder points for {} variants", points.len());
+    Ok(points)
+}
+
+/// Data hygiene: logs at a location over the last `days` days with impossible values
+#[tauri::command]
+pub async fn find_anomalous_logs(
+    location: String,
+    days: i32,
+    client: tauri::State<'_, reqwest::Client>,
+    config: tauri::State<'_, AppConfig>,
+) -> Result<Vec<AnomalousLog>, AppError> {
+    if days < 1 {
+        return Err(AppError::Other("days must be at least 1".to_string()));
+    }
+
+    println!("🧹 Scanning {} logs of the last {} days", location, days);
+
+    let end_date = chrono::Utc::now();
+    let start_date = end_date - chrono::Duration::days(days as i64);
+
+    let firebase_client = FirebaseClient::new(&client, config.inner().clone());
+    let logs = firebase_client
+        .get_logs_date_range(
+            None,
+            location,
+            start_date.to_rfc3339(),
+            end_date.to_rfc3339(),
+        )
+        .await
+        .map_err(AppError::Firebase)?;
+
+    let anomalies = find_anomalies(&logs);
+    println!(
+        "✅ Found {} anomalous logs out of {}",
+        anomalies.len(),
+        logs.len()
+    );
+    Ok(anomalies)
+}
+
+/// Net changes at a location between `start_date` and `end_date`, per product, per
+/// request type and per day
+#[tauri::command]
+pub async fn get_inventory_statistics(
+    location: String,
+    start_date: String,
+    end_date: String,
+    client: tauri::State<'_, reqwest::Client>,
+    config: tauri::State<'_, AppConfig>,
+) -> Result<InventoryStatistics, AppError> {
+    println!(
+        "📊 Computing statistics for {} from {} to {}",
+        location, start_date, end_date
+    );
+
+    let firebase_client = FirebaseClient::new(&client, config.inner().clone());
+    let logs = firebase_client
+        .get_logs_date_range(None, location.clone(), start_date.clone(), end_date.clone())
+        .await
+        .map_err(AppError::Firebase)?;
+
+    let statistics = compute_inventory_statistics(&logs, &location, &start_date, &end_date);
+    println!(
+        "✅ {} products changed, {} units decreased over {} days",
+        statistics.per_product.len(),
+        statistics.units_decreased,
+        statistics.daily.len()
+    );
+    Ok(statistics)
+}
@@ -1,7 +1,7 @@
 #!/usr/bin/env cargo
 
 use inventario_cappellettoshop_lib::stock::*;
-use inventario_cappellettoshop_lib::utils::AppConfig;
+use inventario_cappellettoshop_lib::utils::{build_http_client, AppConfig};
 use std::env;
 
 #[tokio::main]
@@ -56,7 +56,7 @@ async fn scan_and_update_products_standalone(
 ) -> Result<StockUpdateResult, String> {
     use inventario_cappellettoshop_lib::stock::StockUpdateResult;
 
-    let client = reqwest::Client::new();
+    let client = build_http_client(config.request_timeout_secs);
 
     println!("📍 Shop: {}", config.shop_domain);
     println!("🔧 API Version: {}", config.api_version);
@@ -66,14 +66,16 @@ async fn scan_and_update_products_standalone(
         println!("⚡ LIVE MODE - Products will be set to draft status");
     }
 
-    // Step 1: Fetch all products with concurrent requests
+    // Step 1: Fetch all active products, one page at a time
     println!("\n📄 Fetching all products...");
-    let all_products = fetch_all_products_concurrent(&client, config).await?;
+    let all_products = fetch_all_active_products(&client, config).await?;
     println!("✅ Fetched {} total products", all_products.len());
+    let (all_products, duplicates_removed) = dedupe_shopify_products(all_products);
 
     // Step 2: Find products with no stock
     println!("\n🔍 Analyzing inventory...");
-    let products_with_no_stock = find_products_with_no_stock(all_products);
+    let products_with_no_stock =
+        find_products_with_no_stock(all_products, &default_excluded_products());
     println!(
         "🎯 Found {} active products with no stock",
         products_with_no_stock.len()
@@ -83,11 +85,14 @@ async fn scan_and_update_products_standalone(
     let mut update_results = Vec::new();
     if !dry_run && !products_with_no_stock.is_empty() {
         println!("\n📝 Updating products to draft status...");
-        update_results = update_products_to_draft(&client, config, &products_with_no_stock).await?;
+        // No window to report progress to from the command line
+        update_results =
+            update_products_to_draft(&client, config, &products_with_no_stock, None).await?;
     }
 
     // Step 4: Generate summary
-    let summary = generate_summary(&products_with_no_stock, &update_results);
+    let mut summary = generate_summary(&products_with_no_stock, &update_results);
+    summary.duplicates_removed = duplicates_removed;
 
     // Step 5: Print results
     print_results(&products_with_no_stock, &update_results, &summary, dry_run);
@@ -2,15 +2,17 @@ use crate::firebase::{
     create_inventory_log_data, DailyModificationGroup, DateRange, FirebaseClient, LogEntry,
     ModificationDetail, ProductModificationHistory, VariantModificationHistory,
 };
-use crate::location::LocationInfo;
-use crate::utils::{AppConfig, InventoryUpdate, StatusResponse};
+use crate::location::{resolve_location_config, LocationConfig, LocationIndex, LocationInfo};
+use crate::utils::{send_with_retry, AppConfig, AppError, InventoryUpdate, StatusResponse};
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use std::collections::HashMap;
 use tauri::State;
+use tracing::{error, info, warn};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct LocationConfigResponse {
+    pub locations: Vec<LocationInfo>,
     pub primary_location: LocationInfo,
     pub secondary_location: LocationInfo,
 }
@@ -18,81 +20,63 @@ pub struct LocationConfigResponse {
 #[tauri::command]
 pub async fn get_location_config(
     config: State<'_, AppConfig>,
-) -> Result<LocationConfigResponse, String> {
+) -> Result<LocationConfigResponse, AppError> {
+    // Seen from the primary store, so the configured order is kept
+    let resolved = resolve_location_config(&config.default_location_name(), &config)?;
     Ok(LocationConfigResponse {
-        primary_location: LocationInfo {
-            name: "Treviso".to_string(),
-            id: config.primary_location.clone(),
-        },
-        secondary_location: LocationInfo {
-            name: "Mogliano".to_string(),
-            id: config.secondary_location.clone(),
-        },
+        locations: resolved.locations,
+        primary_location: resolved.primary_location,
+        secondary_location: resolved.secondary_location,
     })
 }
 
-#[tauri::command]
-pub async fn get_inventory_levels_for_locations(
-    config: State<'_, AppConfig>,
-    inventory_item_ids: Vec<String>,
-    primary_location_name: String,
-) -> Result<HashMap<String, HashMap<String, i32>>, String> {
-    let client = reqwest::Client::new();
-    let ids = inventory_item_ids.join(",");
-    let url = config.get_api_url(&format!(
-        "inventory_levels.json?inventory_item_ids={}&limit=250",
-        ids
-    ));
-
-    println!(
-        "🏪 Getting inventory for primary location: {}",
-        primary_location_name
-    );
-
-    let response = client
-        .get(&url)
-        .headers(config.get_headers())
-        .send()
-        .await
-        .map_err(|e| format!("Request failed: {}", e))?;
-
-    let data: Value = response
-        .json()
-        .await
-        .map_err(|e| format!("Failed to parse JSON: {}", e))?;
+/// Levels grouped by `group_levels_by_location`. The "primary"/"secondary" labels are
+/// kept apart from the per-store quantities, so summing a store map never counts a
+/// store twice.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct GroupedLevels {
+    /// inventory_item_id → store name → available
+    pub by_location: HashMap<String, HashMap<String, i32>>,
+    /// inventory_item_id → "primary"/"secondary" → available
+    pub aliases: HashMap<String, HashMap<String, i32>>,
+}
 
-    let inventory_levels = data["inventory_levels"]
-        .as_array()
-        .ok_or("No inventory levels found")?;
+impl GroupedLevels {
+    /// Quantity of `inventory_item_id` at the store labelled `label` ("primary" or
+    /// "secondary"), 0 if it has no level there
+    pub fn alias(&self, inventory_item_id: &str, label: &str) -> i32 {
+        self.aliases
+            .get(inventory_item_id)
+            .and_then(|levels| levels.get(label))
+            .copied()
+            .unwrap_or(0)
+    }
 
-    // Determine which location IDs to use based on primary location preference
-    let (primary_location_id, secondary_location_id) = if primary_location_name == "Treviso" {
-        (
-            config.primary_location.clone(),
-            config.secondary_location.clone(),
-        )
-    } else {
-        (
-            config.secondary_location.clone(),
-            config.primary_location.clone(),
-        )
-    };
+    /// Available quantity of `inventory_item_id` summed over every store
+    pub fn total(&self, inventory_item_id: &str) -> i32 {
+        self.by_location
+            .get(inventory_item_id)
+            .map(|levels| levels.values().sum())
+            .unwrap_or(0)
+    }
 
-    println!(
-        "📍 Using Primary Location ID: {} ({})",
-        primary_location_id, primary_location_name
-    );
-    println!(
-        "📍 Using Secondary Location ID: {} ({})",
-        secondary_location_id,
-        if primary_location_name == "Treviso" {
-            "Mogliano"
-        } else {
-            "Treviso"
+    /// Store names and labels in one map per item, the shape the frontend reads
+    pub fn merged(self) -> HashMap<String, HashMap<String, i32>> {
+        let mut merged = self.by_location;
+        for (inventory_item_id, aliases) in self.aliases {
+            merged.entry(inventory_item_id).or_default().extend(aliases);
         }
-    );
+        merged
+    }
+}
 
-    let mut result = HashMap::new();
+/// Group `inventory_levels` by store for every location in `locations`, labelling the
+/// `primary_location`/`secondary_location` quantities "primary"/"secondary" as well
+pub fn group_levels_by_location(
+    inventory_levels: &[Value],
+    locations: &LocationConfig,
+) -> Result<GroupedLevels, AppError> {
+    let mut result = GroupedLevels::default();
 
     for level in inventory_levels {
         let inventory_item_id = level["inventory_item_id"]
@@ -107,54 +91,199 @@ pub async fn get_inventory_levels_for_locations(
 
         let available = level["available"].as_i64().unwrap_or(0) as i32;
 
-        // Only include inventory for our two locations
-        if location_id == primary_location_id || location_id == secondary_location_id {
-            let location_label = if location_id == primary_location_id {
-                "primary"
-            } else {
-                "secondary"
-            };
-
-            println!(
-                "📦 Item {} at location {} ({}): {} available",
-                inventory_item_id, location_id, location_label, available
-            );
+        // Only include inventory for configured locations
+        let Some(location) = locations.locations.iter().find(|l| l.id == location_id) else {
+            continue;
+        };
 
-            result
-                .entry(inventory_item_id)
-                .or_insert_with(HashMap::new)
-                .insert(location_label.to_string(), available);
-        }
+        result
+            .by_location
+            .entry(inventory_item_id.clone())
+            .or_default()
+            .insert(location.name.clone(), available);
+        let label = if location_id == locations.primary_location.id {
+            "primary"
+        } else if location_id == locations.secondary_location.id {
+            "secondary"
+        } else {
+            continue;
+        };
+        result
+            .aliases
+            .entry(inventory_item_id)
+            .or_default()
+            .insert(label.to_string(), available);
     }
 
-    println!("📊 Final inventory result: {:?}", result);
+    Ok(result)
+}
+
+/// Levels of `inventory_item_ids` at every configured store, with "primary" being
+/// `primary_location_name`
+pub async fn fetch_grouped_levels(
+    client: &reqwest::Client,
+    config: &AppConfig,
+    inventory_item_ids: &[String],
+    primary_location_name: &str,
+) -> Result<GroupedLevels, AppError> {
+    let inventory_levels =
+        fetch_inventory_level_entries(client, config, inventory_item_ids).await?;
+
+    let locations = resolve_location_config(primary_location_name, config)?;
+    info!(
+        "📍 Using Primary Location ID: {} ({})",
+        locations.primary_location.id, locations.primary_location.name
+    );
+    info!(
+        "📍 Using Secondary Location ID: {} ({})",
+        locations.secondary_location.id, locations.secondary_location.name
+    );
+
+    group_levels_by_location(&inventory_levels, &locations)
+}
+
+/// Levels keyed by store name, plus "primary"/"secondary" for screens that only know
+/// two stores
+#[tauri::command]
+pub async fn get_inventory_levels_for_locations(
+    client: State<'_, reqwest::Client>,
+    config: State<'_, AppConfig>,
+    inventory_item_ids: Vec<String>,
+    primary_location_name: String,
+) -> Result<HashMap<String, HashMap<String, i32>>, AppError> {
+    info!(
+        "🏪 Getting inventory for primary location: {}",
+        primary_location_name
+    );
+
+    let result = fetch_grouped_levels(
+        &client,
+        &config,
+        &inventory_item_ids,
+        &primary_location_name,
+    )
+    .await?
+    .merged();
+
+    info!("📊 Final inventory result: {:?}", result);
     Ok(result)
 }
 
 #[tauri::command]
 pub async fn get_inventory_levels(
+    client: State<'_, reqwest::Client>,
     config: State<'_, AppConfig>,
     inventory_item_ids: Vec<String>,
-) -> Result<HashMap<String, HashMap<String, i32>>, String> {
-    let client = reqwest::Client::new();
-    let ids = inventory_item_ids.join(",");
+) -> Result<HashMap<String, HashMap<String, i32>>, AppError> {
+    let inventory_levels =
+        fetch_inventory_level_entries(&client, &config, &inventory_item_ids).await?;
+
+    Ok(parse_inventory_levels(
+        &json!({ "inventory_levels": inventory_levels }),
+    )?)
+}
+
+/// Raw `inventory_levels` entries for `inventory_item_ids`, requested in chunks of at most
+/// `INVENTORY_LEVELS_MAX_IDS` ids and following the `Link` header past the first 250 levels
+async fn fetch_inventory_level_entries(
+    client: &reqwest::Client,
+    config: &AppConfig,
+    inventory_item_ids: &[String],
+) -> Result<Vec<Value>, AppError> {
+    let mut entries = Vec::new();
+
+    for chunk in config.inventory_level_chunks(inventory_item_ids) {
+        let mut page_info: Option<String> = None;
+        loop {
+            let url = match &page_info {
+                Some(cursor) => config.get_api_url(&format!(
+                    "inventory_levels.json?limit=250&page_info={}",
+                    cursor
+                )),
+                None => config.get_api_url(&format!(
+                    "inventory_levels.json?inventory_item_ids={}&limit=250",
+                    chunk.join(",")
+                )),
+            };
+
+            let response =
+                send_with_retry(client.get(&url).headers(config.get_headers()), config).await?;
+            if !response.status().is_success() {
+                return Err(AppError::from_response(response).await);
+            }
+            let next_page_info = crate::stock::extract_next_page_info(&response);
+
+            let mut data: Value = response
+                .json()
+                .await
+                .map_err(|e| AppError::Parse(format!("Failed to parse JSON: {}", e)))?;
+            match data["inventory_levels"].take() {
+                Value::Array(levels) => entries.extend(levels),
+                _ => return Err(AppError::Parse("No inventory levels found".to_string())),
+            }
+
+            match next_page_info {
+                Some(next) => page_info = Some(next),
+                None => break,
+            }
+        }
+    }
+
+    Ok(entries)
+}
+
+/// Live available quantity of one inventory item at one location, for the scan-and-decrement
+/// hot path where building the full levels map isn't needed
+#[tauri::command]
+pub async fn get_variant_quantity(
+    client: State<'_, reqwest::Client>,
+    config: State<'_, AppConfig>,
+    inventory_item_id: String,
+    location_id: String,
+) -> Result<i32, AppError> {
     let url = config.get_api_url(&format!(
-        "inventory_levels.json?inventory_item_ids={}&limit=250",
-        ids
+        "inventory_levels.json?inventory_item_ids={}&location_ids={}",
+        inventory_item_id.trim(),
+        location_id.trim()
     ));
 
-    let response = client
-        .get(&url)
-        .headers(config.get_headers())
-        .send()
-        .await
-        .map_err(|e| format!("Request failed: {}", e))?;
+    let response = send_with_retry(client.get(&url).headers(config.get_headers()), &config).await?;
+
+    if !response.status().is_success() {
+        return Err(AppError::from_response(response).await);
+    }
 
     let data: Value = response
         .json()
         .await
-        .map_err(|e| format!("Failed to parse JSON: {}", e))?;
+        .map_err(|e| AppError::Parse(format!("Failed to parse JSON: {}", e)))?;
+
+    single_level_quantity(&data, inventory_item_id.trim(), location_id.trim())
+}
+
+/// The `available` value for `inventory_item_id` at `location_id` in an
+/// `inventory_levels.json` response; `NotFound` when the item isn't stocked there
+pub fn single_level_quantity(
+    data: &Value,
+    inventory_item_id: &str,
+    location_id: &str,
+) -> Result<i32, AppError> {
+    parse_inventory_levels(data)?
+        .get(inventory_item_id)
+        .and_then(|levels| levels.get(location_id))
+        .copied()
+        .ok_or_else(|| {
+            AppError::NotFound(format!(
+                "Inventory item {} is not stocked at location {}",
+                inventory_item_id, location_id
+            ))
+        })
+}
 
+/// Map an `inventory_levels.json` response to inventory_item_id → location_id → available
+pub fn parse_inventory_levels(
+    data: &Value,
+) -> Result<HashMap<String, HashMap<String, i32>>, String> {
     let inventory_levels = data["inventory_levels"]
         .as_array()
         .ok_or("No inventory levels found")?;
@@ -182,13 +311,90 @@ pub async fn get_inventory_levels(
     Ok(result)
 }
 
+/// A variant's product-level inventory_quantity next to the sum of its location levels
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct VariantInventoryAudit {
+    pub variant_title: String,
+    pub inventory_item_id: String,
+    pub inventory_quantity: i32,
+    pub location_total: i32,
+    /// inventory_quantity - location_total; non-zero means tracking is off
+    pub difference: i32,
+}
+
+/// Compare each variant's `inventory_quantity` with its available quantities summed over
+/// every location in `levels` (inventory_item_id → location_id → available)
+pub fn build_inventory_audit(
+    product: &crate::utils::Product,
+    levels: &HashMap<String, HashMap<String, i32>>,
+) -> Vec<VariantInventoryAudit> {
+    product
+        .variants
+        .iter()
+        .map(|variant| {
+            let location_total = levels
+                .get(&variant.inventory_item_id)
+                .map(|by_location| by_location.values().sum())
+                .unwrap_or(0);
+            VariantInventoryAudit {
+                variant_title: variant.title.clone(),
+                inventory_item_id: variant.inventory_item_id.clone(),
+                inventory_quantity: variant.inventory_quantity,
+                location_total,
+                difference: variant.inventory_quantity - location_total,
+            }
+        })
+        .collect()
+}
+
+/// Spot variants whose Shopify total disagrees with the sum of their location levels
+/// (deleted locations, unmanaged inventory) before a customer runs into it
+#[tauri::command]
+pub async fn audit_product_inventory(
+    client: State<'_, reqwest::Client>,
+    config: State<'_, AppConfig>,
+    product_id: String,
+) -> Result<Vec<VariantInventoryAudit>, AppError> {
+    let product =
+        crate::products::get_product_by_id(client.clone(), config.clone(), product_id).await?;
+
+    let inventory_item_ids: Vec<String> = product
+        .variants
+        .iter()
+        .map(|v| v.inventory_item_id.clone())
+        .filter(|id| !id.is_empty())
+        .collect();
+
+    let mut levels = HashMap::new();
+    for chunk in config.inventory_level_chunks(&inventory_item_ids) {
+        levels.extend(get_inventory_levels(client.clone(), config.clone(), chunk.to_vec()).await?);
+    }
+
+    let audit = build_inventory_audit(&product, &levels);
+    let mismatched = audit.iter().filter(|a| a.difference != 0).count();
+    if mismatched > 0 {
+        warn!(
+            "⚠️ {} of {} variants of product {} disagree with their location levels",
+            mismatched,
+            audit.len(),
+            product.id
+        );
+    } else {
+        info!(
+            "✅ All {} variants of product {} match their location levels",
+            audit.len(),
+            product.id
+        );
+    }
+    Ok(audit)
+}
+
 #[tauri::command]
 pub async fn adjust_inventory(
+    client: State<'_, reqwest::Client>,
     config: State<'_, AppConfig>,
     updates: Vec<InventoryUpdate>,
-) -> Result<StatusResponse, String> {
-    let client = reqwest::Client::new();
-
+) -> Result<StatusResponse, AppError> {
     for update in updates {
         let url = config.get_api_url("inventory_levels/adjust.json");
 
@@ -198,17 +404,17 @@ pub async fn adjust_inventory(
             "available_adjustment": update.adjustment
         });
 
-        let response = client
-            .post(&url)
-            .headers(config.get_headers())
-            .json(&payload)
-            .send()
-            .await
-            .map_err(|e| format!("Request failed: {}", e))?;
+        let response = send_with_retry(
+            client
+                .post(&url)
+                .headers(config.get_headers())
+                .json(&payload),
+            &config,
+        )
+        .await?;
 
         if !response.status().is_success() {
-            let error_text = response.text().await.unwrap_or_default();
-            return Err(format!("Failed to adjust inventory: {}", error_text));
+            return Err(AppError::from_response(response).await);
         }
     }
 
@@ -220,12 +426,12 @@ pub async fn adjust_inventory(
 
 #[tauri::command]
 pub async fn set_inventory_level(
+    client: State<'_, reqwest::Client>,
     config: State<'_, AppConfig>,
     inventory_item_id: String,
     location_id: String,
     quantity: i32,
-) -> Result<StatusResponse, String> {
-    let client = reqwest::Client::new();
+) -> Result<StatusResponse, AppError> {
     let url = config.get_api_url("inventory_levels/set.json");
 
     let payload = json!({
@@ -234,17 +440,17 @@ pub async fn set_inventory_level(
         "available": quantity
     });
 
-    let response = client
-        .post(&url)
-        .headers(config.get_headers())
-        .json(&payload)
-        .send()
-        .await
-        .map_err(|e| format!("Request failed: {}", e))?;
+    let response = send_with_retry(
+        client
+            .post(&url)
+            .headers(config.get_headers())
+            .json(&payload),
+        &config,
+    )
+    .await?;
 
     if !response.status().is_success() {
-        let error_text = response.text().await.unwrap_or_default();
-        return Err(format!("Failed to set inventory level: {}", error_text));
+        return Err(AppError::from_response(response).await);
     }
 
     Ok(StatusResponse {
@@ -255,23 +461,18 @@ pub async fn set_inventory_level(
 
 #[tauri::command]
 pub async fn get_low_stock_products(
+    client: State<'_, reqwest::Client>,
     config: State<'_, AppConfig>,
     threshold: i32,
-) -> Result<Vec<Value>, String> {
-    let client = reqwest::Client::new();
+) -> Result<Vec<Value>, AppError> {
     let url = config.get_api_url("products.json?limit=250");
 
-    let response = client
-        .get(&url)
-        .headers(config.get_headers())
-        .send()
-        .await
-        .map_err(|e| format!("Request failed: {}", e))?;
+    let response = send_with_retry(client.get(&url).headers(config.get_headers()), &config).await?;
 
     let data: Value = response
         .json()
         .await
-        .map_err(|e| format!("Failed to parse JSON: {}", e))?;
+        .map_err(|e| AppError::Parse(format!("Failed to parse JSON: {}", e)))?;
 
     let products = data["products"].as_array().ok_or("No products found")?;
 
@@ -300,6 +501,129 @@ pub async fn get_low_stock_products(
     Ok(low_stock_products)
 }
 
+/// A variant below the low-stock threshold at one location
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct LocationLowStock {
+    pub product_id: String,
+    pub product_title: String,
+    pub variant_id: String,
+    pub variant_title: String,
+    pub sku: Option<String>,
+    pub inventory_item_id: String,
+    pub location_quantity: i32,
+    pub total_quantity: i32, // Summed over both locations; above the location's means a transfer can help
+}
+
+/// Variants of `products` whose quantity at the primary location in `levels` is at or
+/// below `threshold`
+pub fn find_low_stock_at_location(
+    products: &[crate::utils::Product],
+    levels: &GroupedLevels,
+    threshold: i32,
+) -> Vec<LocationLowStock> {
+    let mut low_stock = Vec::new();
+
+    for product in products {
+        for variant in &product.variants {
+            if variant.inventory_item_id.is_empty() {
+                continue;
+            }
+
+            let location_quantity = levels.alias(&variant.inventory_item_id, "primary");
+            if location_quantity > threshold {
+                continue;
+            }
+            let total_quantity = levels.total(&variant.inventory_item_id);
+
+            low_stock.push(LocationLowStock {
+                product_id: product.id.clone(),
+                product_title: product.title.clone(),
+                variant_id: variant.variant_id.clone(),
+                variant_title: variant.title.clone(),
+                sku: variant.sku.clone(),
+                inventory_item_id: variant.inventory_item_id.clone(),
+                location_quantity,
+                total_quantity,
+            });
+        }
+    }
+
+    low_stock
+}
+
+/// Like `get_low_stock_products`, but checks the quantity at `location_name` rather than
+/// the variant's total, so a variant empty here but stocked at the other store is flagged
+#[tauri::command]
+pub async fn get_low_stock_products_by_location(
+    client: State<'_, reqwest::Client>,
+    config: State<'_, AppConfig>,
+    locations: State<'_, LocationIndex>,
+    threshold: i32,
+    location_name: String,
+) -> Result<Vec<LocationLowStock>, AppError> {
+    locations.resolve(&location_name)?;
+
+    let products = crate::products::get_products(client.clone(), config.clone(), None).await?;
+
+    let inventory_item_ids: Vec<String> = products
+        .iter()
+        .flat_map(|product| product.variants.iter())
+        .map(|variant| variant.inventory_item_id.clone())
+        .filter(|id| !id.is_empty())
+        .collect();
+
+    let levels =
+        fetch_grouped_levels(&client, &config, &inventory_item_ids, &location_name).await?;
+
+    let low_stock = find_low_stock_at_location(&products, &levels, threshold);
+    info!(
+        "📉 {} variants at or below {} in {}",
+        low_stock.len(),
+        threshold,
+        location_name
+    );
+    Ok(low_stock)
+}
+
+/// Appended to inventory messages when the Firebase log could not be written
+const LOG_PENDING_SYNC_MESSAGE: &str = "registro salvato localmente, in attesa di sincronizzazione";
+
+/// Write one inventory log through the audit log, returning whether it reached Firestore
+async fn log_written_or_pending(
+    app: &tauri::AppHandle,
+    firebase_client: &FirebaseClient,
+    log_entry: LogEntry,
+) -> bool {
+    match crate::firebase::write_logs_audited(app, firebase_client, vec![log_entry])
+        .await
+        .remove(0)
+    {
+        Ok(_) => {
+            crate::status::mark_last_run(app, crate::status::RunKind::LogWrite);
+            true
+        }
+        Err(e) => {
+            warn!("⚠️ Warning: Firebase log failed, kept pending sync: {}", e);
+            false
+        }
+    }
+}
+
+/// Result message of a decrease of `quantity` units, depending on whether its log reached
+/// Firestore or is pending sync
+pub fn decrease_message(quantity: i32, logged: bool) -> String {
+    let removed = if quantity == 1 {
+        "Inventario diminuito".to_string()
+    } else {
+        format!("Inventario diminuito di {} unità", quantity)
+    };
+    if logged {
+        format!("{} e registrato con successo", removed)
+    } else {
+        format!("{} - {}", removed, LOG_PENDING_SYNC_MESSAGE)
+    }
+}
+
 #[tauri::command]
 pub async fn decrease_inventory_with_logging(
     inventory_item_id: String,
@@ -310,46 +634,79 @@ pub async fn decrease_inventory_with_logging(
     price: String,
     negozio: String,
     images: Vec<String>,
+    quantity: Option<i32>,
+    app: tauri::AppHandle,
+    client: tauri::State<'_, reqwest::Client>,
     config: tauri::State<'_, AppConfig>,
-) -> Result<EnhancedStatusResponse, String> {
-    println!("📦 Starting enhanced inventory decrease with logging:");
-    println!("   🏪 Store: {}", negozio);
-    println!("   📦 Product: {} ({})", product_name, variant_title);
-    println!("   📍 Location ID: {}", location_id);
-    println!("   🔢 Inventory Item ID: {}", inventory_item_id);
+    undo_stack: tauri::State<'_, UndoStack>,
+) -> Result<EnhancedStatusResponse, AppError> {
+    info!("📦 Starting enhanced inventory decrease with logging:");
+    info!("   🏪 Store: {}", negozio);
+    info!("   📦 Product: {} ({})", product_name, variant_title);
+    info!("   📍 Location ID: {}", location_id);
+    info!("   🔢 Inventory Item ID: {}", inventory_item_id);
+
+    // A single unit unless the caller sold more than one
+    let quantity = validate_quantity(quantity.unwrap_or(1))?;
+    info!("   🔢 Quantity: {}", quantity);
 
     // Adjust inventory first
     let update = InventoryUpdate {
         variant_id: inventory_item_id.clone(),
         location_id: location_id.clone(),
-        adjustment: -1,
+        adjustment: -quantity,
     };
 
-    println!("📉 Adjusting Shopify inventory...");
-    adjust_inventory(config.clone(), vec![update]).await?;
-    println!("✅ Shopify inventory adjusted successfully");
+    info!("📉 Adjusting Shopify inventory...");
+    adjust_inventory(client.clone(), config.clone(), vec![update]).await?;
+    info!("✅ Shopify inventory adjusted successfully");
 
     // Check if product now has zero inventory across all locations
     let mut status_changed = None;
     let mut current_product_status = None;
 
-    let has_zero_inventory = has_zero_inventory_across_all_locations(&config, &product_id).await?;
+    // Shopify has already changed, so a failed check must not skip the undo entry and log
+    let has_zero_inventory =
+        match has_zero_inventory_across_all_locations(&client, &config, &product_id).await {
+            Ok(has_zero_inventory) => has_zero_inventory,
+            Err(e) => {
+                warn!(
+                    "⚠️ Warning: Could not check product inventory status: {}",
+                    e
+                );
+                false
+            }
+        };
 
     if has_zero_inventory {
-        println!("🎯 Product has zero inventory across all locations - setting to draft");
-        match update_product_status(&config, &product_id, "draft").await {
+        info!("🎯 Product has zero inventory across all locations - setting to draft");
+        match update_product_status(&client, &config, &product_id, "draft").await {
             Ok(_) => {
                 status_changed = Some("to_draft".to_string());
                 current_product_status = Some("draft".to_string());
-                println!("✅ Product status updated to draft");
+                info!("✅ Product status updated to draft");
             }
             Err(e) => {
-                println!("⚠️ Failed to update product status to draft: {}", e);
+                warn!("⚠️ Failed to update product status to draft: {}", e);
                 // Continue with the operation even if status update fails
             }
         }
     }
 
+    undo_stack.push(UndoableAction::Decrease {
+        item: UndoItem {
+            inventory_item_id: inventory_item_id.clone(),
+            product_id: product_id.clone(),
+            variant_title: variant_title.clone(),
+            product_name: product_name.clone(),
+            price: price.clone(),
+            images: images.clone(),
+        },
+        location_id: location_id.clone(),
+        negozio: negozio.clone(),
+        quantity,
+    });
+
     // Create log entry
     let log_data = create_inventory_log_data(
         product_id,
@@ -358,22 +715,23 @@ pub async fn decrease_inventory_with_logging(
         inventory_item_id,
         product_name,
         price,
-        -1,
+        -quantity,
         images,
     );
 
     // Save to Firebase
-    println!("📝 Creating Firebase log entry...");
-    let firebase_client = FirebaseClient::new(config.inner().clone());
+    info!("📝 Creating Firebase log entry...");
+    let firebase_client = FirebaseClient::new(&client, config.inner().clone());
     let log_entry = LogEntry {
         request_type: "Rettifica".to_string(),
         data: log_data,
         timestamp: chrono::Utc::now().to_rfc3339(),
     };
 
-    firebase_client.create_log(log_entry).await?;
+    // Shopify has already changed, so a Firestore failure only leaves the log pending sync
+    let logged = log_written_or_pending(&app, &firebase_client, log_entry).await;
 
-    let base_message = "Inventario diminuito e registrato con successo".to_string();
+    let base_message = decrease_message(quantity, logged);
     let enhanced_message = match &status_changed {
         Some(_) => format!(
             "{} - Prodotto impostato come bozza (inventario esaurito)",
@@ -382,7 +740,7 @@ pub async fn decrease_inventory_with_logging(
         None => base_message,
     };
 
-    println!("✅ Enhanced inventory decrease completed with logging");
+    info!("✅ Enhanced inventory decrease completed with logging");
     Ok(EnhancedStatusResponse {
         status: "success".to_string(),
         message: enhanced_message,
@@ -401,42 +759,54 @@ pub async fn undo_decrease_inventory_with_logging(
     price: String,
     negozio: String,
     images: Vec<String>,
+    quantity: Option<i32>,
+    app: tauri::AppHandle,
+    client: tauri::State<'_, reqwest::Client>,
     config: tauri::State<'_, AppConfig>,
-) -> Result<EnhancedStatusResponse, String> {
-    println!("🔄 Starting enhanced inventory undo (increase) with logging:");
-    println!("   🏪 Store: {}", negozio);
-    println!("   📦 Product: {} ({})", product_name, variant_title);
-    println!("   📍 Location ID: {}", location_id);
-    println!("   🔢 Inventory Item ID: {}", inventory_item_id);
+    undo_stack: tauri::State<'_, UndoStack>,
+) -> Result<EnhancedStatusResponse, AppError> {
+    info!("🔄 Starting enhanced inventory undo (increase) with logging:");
+    info!("   🏪 Store: {}", negozio);
+    info!("   📦 Product: {} ({})", product_name, variant_title);
+    info!("   📍 Location ID: {}", location_id);
+    info!("   🔢 Inventory Item ID: {}", inventory_item_id);
+
+    // Decreases are single units unless the caller says otherwise
+    let quantity = validate_quantity(quantity.unwrap_or(1))?;
+    info!("   🔢 Quantity: {}", quantity);
 
     // Check if product currently has zero inventory (to know if we should activate it)
-    let had_zero_inventory = has_zero_inventory_across_all_locations(&config, &product_id).await?;
+    let had_zero_inventory =
+        has_zero_inventory_across_all_locations(&client, &config, &product_id).await?;
 
-    // Adjust inventory first (increase by 1)
+    // Adjust inventory first (increase by the undone quantity)
     let update = InventoryUpdate {
         variant_id: inventory_item_id.clone(),
         location_id: location_id.clone(),
-        adjustment: 1,
+        adjustment: quantity,
     };
 
-    println!("📈 Adjusting Shopify inventory (undo)...");
-    adjust_inventory(config.clone(), vec![update]).await?;
-    println!("✅ Shopify inventory adjusted successfully");
+    info!("📈 Adjusting Shopify inventory (undo)...");
+    adjust_inventory(client.clone(), config.clone(), vec![update]).await?;
+    info!("✅ Shopify inventory adjusted successfully");
+
+    // This decrease is now undone, so it must not be reversed again by undo_last_n
+    undo_stack.remove_decrease(&inventory_item_id, &location_id, quantity);
 
     // If product had zero inventory and now has some, set it back to active
     let mut status_changed = None;
     let mut current_product_status = None;
 
     if had_zero_inventory {
-        println!("🎯 Product previously had zero inventory - setting back to active");
-        match update_product_status(&config, &product_id, "active").await {
+        info!("🎯 Product previously had zero inventory - setting back to active");
+        match update_product_status(&client, &config, &product_id, "active").await {
             Ok(_) => {
                 status_changed = Some("to_active".to_string());
                 current_product_status = Some("active".to_string());
-                println!("✅ Product status updated to active");
+                info!("✅ Product status updated to active");
             }
             Err(e) => {
-                println!("⚠️ Failed to update product status to active: {}", e);
+                warn!("⚠️ Failed to update product status to active: {}", e);
                 // Continue with the operation even if status update fails
             }
         }
@@ -450,22 +820,27 @@ pub async fn undo_decrease_inventory_with_logging(
         inventory_item_id,
         product_name,
         price,
-        1,
+        quantity,
         images,
     );
 
     // Save to Firebase
-    println!("📝 Creating Firebase log entry (undo)...");
-    let firebase_client = FirebaseClient::new(config.inner().clone());
+    info!("📝 Creating Firebase log entry (undo)...");
+    let firebase_client = FirebaseClient::new(&client, config.inner().clone());
     let log_entry = LogEntry {
         request_type: "Annullamento".to_string(),
         data: log_data,
         timestamp: chrono::Utc::now().to_rfc3339(),
     };
 
-    firebase_client.create_log(log_entry).await?;
+    // Shopify has already changed, so a Firestore failure only leaves the log pending sync
+    let logged = log_written_or_pending(&app, &firebase_client, log_entry).await;
 
-    let base_message = "Inventario ripristinato e registrato con successo".to_string();
+    let base_message = if logged {
+        "Inventario ripristinato e registrato con successo".to_string()
+    } else {
+        format!("Inventario ripristinato - {}", LOG_PENDING_SYNC_MESSAGE)
+    };
     let enhanced_message = match &status_changed {
         Some(_) => format!(
             "{} - Prodotto riattivato (inventario disponibile)",
@@ -474,7 +849,7 @@ pub async fn undo_decrease_inventory_with_logging(
         None => base_message,
     };
 
-    println!("✅ Enhanced inventory undo completed with logging");
+    info!("✅ Enhanced inventory undo completed with logging");
     Ok(EnhancedStatusResponse {
         status: "success".to_string(),
         message: enhanced_message,
@@ -483,7 +858,18 @@ pub async fn undo_decrease_inventory_with_logging(
     })
 }
 
-/// Transfer inventory between two locations and log to Firebase  
+/// Reject a zero or negative quantity for inventory movements
+pub fn validate_quantity(quantity: i32) -> Result<i32, String> {
+    if quantity < 1 {
+        return Err(format!(
+            "Invalid quantity: {} (must be at least 1)",
+            quantity
+        ));
+    }
+    Ok(quantity)
+}
+
+/// Transfer `quantity` units between two locations and log to Firebase
 #[tauri::command]
 pub async fn transfer_inventory_between_locations(
     inventory_item_id: String,
@@ -496,71 +882,92 @@ pub async fn transfer_inventory_between_locations(
     from_location: String,
     to_location: String,
     images: Vec<String>,
+    quantity: i32,
+    app: tauri::AppHandle,
+    client: tauri::State<'_, reqwest::Client>,
     config: tauri::State<'_, AppConfig>,
-) -> Result<EnhancedStatusResponse, String> {
-    println!(
-        "🔄 Starting inventory transfer for product: {} ({})",
-        product_name, variant_title
+    undo_stack: tauri::State<'_, UndoStack>,
+) -> Result<EnhancedStatusResponse, AppError> {
+    let quantity = validate_quantity(quantity)?;
+
+    info!(
+        "🔄 Starting inventory transfer for product: {} ({}) x{}",
+        product_name, variant_title, quantity
     );
-    println!("📦 Inventory item ID: {}", inventory_item_id);
-    println!(
+    info!("📦 Inventory item ID: {}", inventory_item_id);
+    info!(
         "📍 From location: {} (ID: {})",
         from_location, from_location_id
     );
-    println!("📍 To location: {} (ID: {})", to_location, to_location_id);
+    info!("📍 To location: {} (ID: {})", to_location, to_location_id);
 
     // Note: Inventory validation is handled by the frontend (same logic as variant selection)
     // The frontend ensures only variants with stock > 0 in the primary location can be transferred
 
     // Step 4: Execute the transfer (decrease from source, increase at destination)
-    println!("📉 Decreasing inventory at source location...");
+    info!("📉 Decreasing inventory at source location...");
     let decrease_update = InventoryUpdate {
         variant_id: inventory_item_id.clone(),
         location_id: from_location_id.clone(),
-        adjustment: -1,
+        adjustment: -quantity,
     };
 
-    let from_result = adjust_inventory(config.clone(), vec![decrease_update]).await;
+    let from_result = adjust_inventory(client.clone(), config.clone(), vec![decrease_update]).await;
 
     if let Err(e) = from_result {
-        return Err(format!(
-            "Errore nella rimozione da {}: {}",
-            from_location, e
-        ));
+        return Err(format!("Errore nella rimozione da {}: {}", from_location, e).into());
     }
 
-    println!("📈 Increasing inventory at destination location...");
+    info!("📈 Increasing inventory at destination location...");
     let increase_update = InventoryUpdate {
         variant_id: inventory_item_id.clone(),
         location_id: to_location_id.clone(),
-        adjustment: 1,
+        adjustment: quantity,
     };
 
-    let to_result = adjust_inventory(config.clone(), vec![increase_update]).await;
+    let to_result = adjust_inventory(client.clone(), config.clone(), vec![increase_update]).await;
 
     if let Err(e) = to_result {
         // Rollback: restore the source location inventory
-        println!("❌ Error at destination, rolling back source location...");
+        error!("❌ Error at destination, rolling back source location...");
         let rollback_update = InventoryUpdate {
             variant_id: inventory_item_id.clone(),
             location_id: from_location_id.clone(),
-            adjustment: 1,
+            adjustment: quantity,
         };
-        let rollback_result = adjust_inventory(config.clone(), vec![rollback_update]).await;
+        let rollback_result =
+            adjust_inventory(client.clone(), config.clone(), vec![rollback_update]).await;
 
         if let Err(rollback_err) = rollback_result {
             return Err(format!(
                 "ERRORE CRITICO: Fallimento trasferimento e rollback fallito. Originale: {}, Rollback: {}",
                 e, rollback_err
-            ));
+            ).into());
         }
-        return Err(format!("Errore nell'aggiunta a {}: {}", to_location, e));
+        return Err(format!("Errore nell'aggiunta a {}: {}", to_location, e).into());
     }
 
-    println!("✅ Inventory transfer successful");
+    info!("✅ Inventory transfer successful");
+
+    undo_stack.push(UndoableAction::Transfer {
+        item: UndoItem {
+            inventory_item_id: inventory_item_id.clone(),
+            product_id: product_id.clone(),
+            variant_title: variant_title.clone(),
+            product_name: product_name.clone(),
+            price: price.clone(),
+            images: images.clone(),
+        },
+        from_location_id: from_location_id.clone(),
+        to_location_id: to_location_id.clone(),
+        from_location: from_location.clone(),
+        to_location: to_location.clone(),
+        quantity,
+        taken_back: false,
+    });
 
     // Step 5: Create Firebase log entries (one for each location)
-    let firebase_client = FirebaseClient::new(config.inner().clone());
+    let firebase_client = FirebaseClient::new(&client, config.inner().clone());
 
     // Log entry for source location (negative adjustment)
     let source_log_data = crate::firebase::LogData {
@@ -570,24 +977,10 @@ pub async fn transfer_inventory_between_locations(
         inventory_item_id: inventory_item_id.clone(),
         nome: product_name.clone(),
         prezzo: price.clone(),
-        rettifica: -1, // Negative 1 for removal from source
+        rettifica: -quantity, // Negative for removal from source
         images: images.clone(),
     };
 
-    println!("📝 Creating Firebase log for source location (removal)");
-    let source_log_entry = crate::firebase::LogEntry {
-        request_type: "Trasferimento".to_string(),
-        data: source_log_data,
-        timestamp: chrono::Utc::now().to_rfc3339(),
-    };
-
-    let source_log_result = firebase_client.create_log(source_log_entry).await;
-
-    if let Err(e) = source_log_result {
-        println!("⚠️ Warning: Failed to log source transfer: {}", e);
-        // Don't fail the entire operation for logging issues, but warn
-    }
-
     // Log entry for destination location (positive adjustment)
     let dest_log_data = crate::firebase::LogData {
         id: product_id.clone(),
@@ -596,87 +989,221 @@ pub async fn transfer_inventory_between_locations(
         inventory_item_id: inventory_item_id.clone(),
         nome: product_name.clone(),
         prezzo: price.clone(),
-        rettifica: 1, // Positive 1 for addition to destination
+        rettifica: quantity, // Positive for addition to destination
         images: images.clone(),
     };
 
-    println!("📝 Creating Firebase log for destination location (addition)");
-    let dest_log_entry = crate::firebase::LogEntry {
-        request_type: "Trasferimento".to_string(),
-        data: dest_log_data,
-        timestamp: chrono::Utc::now().to_rfc3339(),
-    };
+    info!("📝 Creating Firebase logs for source (removal) and destination (addition)");
+    let timestamp = chrono::Utc::now().to_rfc3339();
+    let log_entries = vec![
+        crate::firebase::LogEntry {
+            request_type: "Trasferimento".to_string(),
+            data: source_log_data,
+            timestamp: timestamp.clone(),
+        },
+        crate::firebase::LogEntry {
+            request_type: "Trasferimento".to_string(),
+            data: dest_log_data,
+            timestamp,
+        },
+    ];
 
-    let dest_log_result = firebase_client.create_log(dest_log_entry).await;
+    let log_results =
+        crate::firebase::write_logs_audited(&app, &firebase_client, log_entries).await;
 
-    if let Err(e) = dest_log_result {
-        println!("⚠️ Warning: Failed to log destination transfer: {}", e);
-        // Don't fail the entire operation for logging issues, but warn
+    let logs_written = log_results.iter().any(|result| result.is_ok());
+    for (side, result) in ["source", "destination"].iter().zip(log_results) {
+        if let Err(e) = result {
+            // Don't fail the entire operation for logging issues; the log stays pending sync
+            warn!("⚠️ Warning: Failed to log {} transfer: {}", side, e);
+        }
+    }
+    if logs_written {
+        crate::status::mark_last_run(&app, crate::status::RunKind::LogWrite);
     }
 
-    println!("✅ Firebase logs created successfully for transfer");
+    info!("✅ Firebase logs created successfully for transfer");
 
     // Step 6: Check if product status needs to change due to inventory levels
-    let status_changed = match has_zero_inventory_across_all_locations(&config, &product_id).await {
-        Ok(true) => {
-            println!("🔄 Product has zero inventory across all locations, setting to draft");
-            update_product_status(&config, &product_id, "draft").await?;
-            Some("to_draft".to_string())
-        }
-        Ok(false) => {
-            println!("✅ Product still has inventory in some locations");
-            None
-        }
-        Err(e) => {
-            println!(
-                "⚠️ Warning: Could not check product inventory status: {}",
-                e
-            );
-            None
-        }
-    };
+    let status_changed =
+        match has_zero_inventory_across_all_locations(&client, &config, &product_id).await {
+            Ok(true) => {
+                info!("🔄 Product has zero inventory across all locations, setting to draft");
+                update_product_status(&client, &config, &product_id, "draft").await?;
+                Some("to_draft".to_string())
+            }
+            Ok(false) => {
+                info!("✅ Product still has inventory in some locations");
+                None
+            }
+            Err(e) => {
+                warn!(
+                    "⚠️ Warning: Could not check product inventory status: {}",
+                    e
+                );
+                None
+            }
+        };
 
     // Return enhanced response with status change information
     Ok(EnhancedStatusResponse {
         status: "success".to_string(),
         message: format!(
-            "Trasferimento completato: {} ({}) spostato da {} a {}",
-            product_name, variant_title, from_location, to_location
+            "Trasferimento completato: {} x {} ({}) spostato da {} a {}",
+            quantity, product_name, variant_title, from_location, to_location
         ),
         status_changed,
         product_status: None, // We don't fetch current status for transfers
     })
 }
 
+/// Resolve the source and destination store names of a transfer to their configured
+/// locations, rejecting unknown names and a transfer from a store to itself
+pub fn resolve_transfer_locations(
+    config: &AppConfig,
+    from_name: &str,
+    to_name: &str,
+) -> Result<(LocationInfo, LocationInfo), AppError> {
+    let find = |name: &str| {
+        config
+            .named_locations()
+            .into_iter()
+            .find(|(location, _)| location.eq_ignore_ascii_case(name.trim()))
+            .map(|(name, id)| LocationInfo { name, id })
+            .ok_or_else(|| AppError::NotFound(format!("Location '{}' not found", name)))
+    };
+
+    let from = find(from_name)?;
+    let to = find(to_name)?;
+    if from.id == to.id {
+        return Err(AppError::Other(format!(
+            "Cannot transfer from {} to itself",
+            from.name
+        )));
+    }
+    Ok((from, to))
+}
+
+/// Transfer `quantity` units between two stores given by name. Location ids and the
+/// variant details used for the logs are looked up here, so callers only need the
+/// inventory item and product ids.
 #[tauri::command]
-pub async fn get_product_modification_history(
+#[allow(clippy::too_many_arguments)]
+pub async fn transfer_by_location_name(
+    inventory_item_id: String,
     product_id: String,
-    location: String,
-    days_back: i32,
+    from_name: String,
+    to_name: String,
+    quantity: i32,
+    app: tauri::AppHandle,
+    client: tauri::State<'_, reqwest::Client>,
     config: tauri::State<'_, AppConfig>,
-) -> Result<ProductModificationHistory, String> {
-    println!("📊 Starting modification history analysis:");
-    println!("   📦 Product ID: {}", product_id);
-    println!("   🏪 Location: {}", location);
-    println!("   📅 Days back: {}", days_back);
-
-    // Calculate date range
-    let end_date = chrono::Utc::now();
-    let start_date = end_date - chrono::Duration::days(days_back as i64);
+    undo_stack: tauri::State<'_, UndoStack>,
+) -> Result<EnhancedStatusResponse, AppError> {
+    let (from, to) = resolve_transfer_locations(&config, &from_name, &to_name)?;
 
-    let start_date_str = start_date.to_rfc3339();
-    let end_date_str = end_date.to_rfc3339();
-
-    let date_range = DateRange {
+    let product =
+        crate::products::get_product_by_id(client.clone(), config.clone(), product_id.clone())
+            .await?;
+    let variant = product
+        .variants
+        .iter()
+        .find(|v| v.inventory_item_id == inventory_item_id)
+        .ok_or_else(|| {
+            AppError::NotFound(format!(
+                "Inventory item {} not found in product {}",
+                inventory_item_id, product_id
+            ))
+        })?;
+
+    transfer_inventory_between_locations(
+        inventory_item_id.clone(),
+        from.id,
+        to.id,
+        product_id,
+        variant.title.clone(),
+        product.title.clone(),
+        variant.price.clone(),
+        from.name,
+        to.name,
+        product.images.clone(),
+        quantity,
+        app,
+        client,
+        config,
+        undo_stack,
+    )
+    .await
+}
+
+/// App logs and Shopify-side movements of a product at `location` since the snapshot of
+/// the day `days_back` days ago. Fails with NotFound when that snapshot doesn't exist or
+/// lacks one of the variants, since there would be no baseline for the discrepancy.
+#[tauri::command]
+pub async fn get_product_modification_history(
+    product_id: String,
+    location: String,
+    days_back: i32,
+    client: tauri::State<'_, reqwest::Client>,
+    config: tauri::State<'_, AppConfig>,
+    locations: tauri::State<'_, LocationIndex>,
+    app: tauri::AppHandle,
+) -> Result<ProductModificationHistory, AppError> {
+    info!("📊 Starting modification history analysis:");
+    info!("   📦 Product ID: {}", product_id);
+    info!("   🏪 Location: {}", location);
+    info!("   📅 Days back: {}", days_back);
+
+    let days_back = validate_days_back(days_back, config.history_max_days)?;
+    let dir = crate::status::app_data_dir(&app)?;
+
+    // The period starts when the snapshot of its first day was taken, so the snapshot
+    // quantities are the baseline for everything logged afterwards
+    let end_date = chrono::Utc::now();
+    let first_day = (end_date - chrono::Duration::days(days_back as i64)).date_naive();
+    let start_snapshot = read_snapshot(
+        &dir,
+        &config,
+        &location,
+        &first_day.format("%Y-%m-%d").to_string(),
+    )
+    .map_err(|e| {
+        AppError::NotFound(format!(
+            "{}: capture a snapshot to compare the history against",
+            e
+        ))
+    })?;
+    let start_date = snapshot_time(&start_snapshot)?;
+
+    // Later daily snapshots split the Shopify-side movements by day
+    let later_snapshots: Vec<InventorySnapshot> = first_day
+        .iter_days()
+        .skip(1)
+        .take_while(|day| *day <= end_date.date_naive())
+        .filter_map(|day| {
+            read_snapshot(
+                &dir,
+                &config,
+                &location,
+                &day.format("%Y-%m-%d").to_string(),
+            )
+            .ok()
+        })
+        .collect();
+
+    let start_date_str = start_date.to_rfc3339();
+    let end_date_str = end_date.to_rfc3339();
+
+    let date_range = DateRange {
         start_date: start_date_str.clone(),
         end_date: end_date_str.clone(),
         days_back,
     };
 
-    println!("   🕐 Date range: {} to {}", start_date_str, end_date_str);
+    info!("   🕐 Date range: {} to {}", start_date_str, end_date_str);
 
     // Step 1: Get Firebase logs for this product
-    let firebase_client = FirebaseClient::new(config.inner().clone());
+    let firebase_client = FirebaseClient::new(&client, config.inner().clone());
     let firebase_logs = firebase_client
         .get_logs_by_product_id(
             product_id.clone(),
@@ -684,16 +1211,19 @@ pub async fn get_product_modification_history(
             start_date_str.clone(),
             end_date_str.clone(),
         )
-        .await?;
+        .await
+        .map_err(AppError::Firebase)?;
 
-    println!(
+    info!(
         "📝 Found {} Firebase logs for this product",
         firebase_logs.len()
     );
 
     // Step 2: Get current product data from Shopify
-    let product = crate::products::get_product_by_id(config.clone(), product_id.clone()).await?;
-    println!("🛍️ Retrieved product: {}", product.title);
+    let product =
+        crate::products::get_product_by_id(client.clone(), config.clone(), product_id.clone())
+            .await?;
+    info!("🛍️ Retrieved product: {}", product.title);
 
     // Step 3: Get current inventory levels
     let inventory_item_ids: Vec<String> = product
@@ -702,29 +1232,23 @@ pub async fn get_product_modification_history(
         .map(|v| v.inventory_item_id.clone())
         .collect();
 
-    let location_config = get_location_config(config.clone()).await?;
-    let location_id = if location == "Treviso" {
-        location_config.primary_location.id
-    } else {
-        location_config.secondary_location.id
-    };
+    let location_id = locations.resolve(&location)?;
 
-    println!("📍 Using location ID: {} for {}", location_id, location);
+    info!("📍 Using location ID: {} for {}", location_id, location);
 
     let inventory_levels =
-        get_inventory_levels_for_locations(config.clone(), inventory_item_ids, location.clone())
-            .await?;
+        fetch_grouped_levels(&client, &config, &inventory_item_ids, &location).await?;
 
-    println!(
+    info!(
         "📊 Retrieved inventory levels for {} variants",
-        inventory_levels.len()
+        inventory_levels.by_location.len()
     );
 
     // Step 4: Analyze each variant
     let mut variants = Vec::new();
 
     for variant in &product.variants {
-        println!("🔍 Analyzing variant: {}", variant.title);
+        info!("🔍 Analyzing variant: {}", variant.title);
 
         // Get Firebase logs for this specific variant
         let variant_logs: Vec<&LogEntry> = firebase_logs
@@ -732,7 +1256,7 @@ pub async fn get_product_modification_history(
             .filter(|log| log.data.variant == variant.title)
             .collect();
 
-        println!(
+        info!(
             "   📝 Found {} Firebase logs for this variant",
             variant_logs.len()
         );
@@ -740,26 +1264,54 @@ pub async fn get_product_modification_history(
         // Calculate total app net changes (sum of all rettifica values)
         let app_net_change: i32 = variant_logs.iter().map(|log| log.data.rettifica).sum();
 
-        // Group modifications by date
-        let daily_groups = group_modifications_by_date(&variant_logs);
-
         // Get current inventory quantity
-        let current_quantity = inventory_levels
-            .get(&variant.inventory_item_id)
-            .and_then(|inv| inv.get("primary"))
-            .copied()
-            .unwrap_or(0);
+        let current_quantity = inventory_levels.alias(&variant.inventory_item_id, "primary");
+
+        let start_quantity = snapshot_quantity(&start_snapshot, &variant.inventory_item_id)
+            .ok_or_else(|| {
+                AppError::NotFound(format!(
+                    "Variant {} is not in the snapshot of {} on {}: capture a new snapshot",
+                    variant.title, start_snapshot.location, start_snapshot.date
+                ))
+            })?;
+
+        // Quantities at each snapshot in the period, then the live level
+        let mut checkpoints = vec![(start_date, start_quantity)];
+        for snapshot in &later_snapshots {
+            if let (Ok(at), Some(quantity)) = (
+                snapshot_time(snapshot),
+                snapshot_quantity(snapshot, &variant.inventory_item_id),
+            ) {
+                if at > start_date && at < end_date {
+                    checkpoints.push((at, quantity));
+                }
+            }
+        }
+        checkpoints.push((end_date, current_quantity));
+
+        // Movements Shopify saw without an app log (orders, manual edits)
+        let shopify_details = shopify_movements(&checkpoints, &variant_logs);
+        let shopify_net_change: i32 = shopify_details.iter().map(|d| d.change).sum();
 
-        println!("   📦 Current quantity: {}", current_quantity);
-        println!("   📱 App net change: {}", app_net_change);
+        // Group modifications by date
+        let daily_groups = group_modifications_by_date(&variant_logs, &shopify_details);
+
+        info!("   📦 Current quantity: {}", current_quantity);
+        info!("   📱 App net change: {}", app_net_change);
+        info!("   🛍️ Shopify net change: {}", shopify_net_change);
 
-        // All logic for Shopify change detection and discrepancy has been removed.
+        let discrepancy = compute_discrepancy(current_quantity, start_quantity, app_net_change);
+        if discrepancy != 0 {
+            warn!("   ⚠️ Discrepancy: {}", discrepancy);
+        }
 
         let variant_history = VariantModificationHistory {
             variant_title: variant.title.clone(),
             inventory_item_id: variant.inventory_item_id.clone(),
             app_net_change,
             current_quantity,
+            shopify_net_change,
+            discrepancy,
             daily_modifications: daily_groups,
         };
 
@@ -773,32 +1325,59 @@ pub async fn get_product_modification_history(
         variants,
     };
 
-    println!("✅ Modification history analysis completed");
+    info!("✅ Modification history analysis completed");
     Ok(history)
 }
 
-/// Group modifications by date and calculate net changes per day
-fn group_modifications_by_date(logs: &[&LogEntry]) -> Vec<DailyModificationGroup> {
-    use chrono::DateTime;
-    use std::collections::HashMap;
+/// Reject a non-positive `days_back` and cap it at `max_days`
+pub fn validate_days_back(days_back: i32, max_days: i32) -> Result<i32, String> {
+    if days_back < 1 {
+        return Err(format!(
+            "Invalid days_back: {} (must be at least 1)",
+            days_back
+        ));
+    }
+
+    if days_back > max_days {
+        warn!(
+            "⚠️ days_back {} exceeds maximum, capping at {}",
+            days_back, max_days
+        );
+        return Ok(max_days);
+    }
+
+    Ok(days_back)
+}
+
+/// Date (YYYY-MM-DD) of an RFC 3339 timestamp, used to group modifications by day
+fn modification_date(timestamp: &str) -> String {
+    if let Ok(parsed_time) = chrono::DateTime::parse_from_rfc3339(timestamp) {
+        parsed_time.format("%Y-%m-%d").to_string()
+    } else {
+        // Fallback: try to extract date from timestamp string
+        timestamp.split('T').next().unwrap_or("unknown").to_string()
+    }
+}
 
+/// Group app logs and Shopify movements by date and calculate net changes per day
+pub fn group_modifications_by_date(
+    logs: &[&LogEntry],
+    shopify_changes: &[ModificationDetail],
+) -> Vec<DailyModificationGroup> {
     let mut groups: HashMap<String, Vec<&LogEntry>> = HashMap::new();
+    let mut shopify_groups: HashMap<String, Vec<ModificationDetail>> = HashMap::new();
 
     // Group logs by date
     for log in logs {
-        // Parse timestamp and extract date
-        let date = if let Ok(parsed_time) = DateTime::parse_from_rfc3339(&log.timestamp) {
-            parsed_time.format("%Y-%m-%d").to_string()
-        } else {
-            // Fallback: try to extract date from timestamp string
-            log.timestamp
-                .split('T')
-                .next()
-                .unwrap_or("unknown")
-                .to_string()
-        };
-
-        groups.entry(date).or_insert_with(Vec::new).push(log);
+        groups
+            .entry(modification_date(&log.timestamp))
+            .or_default()
+            .push(log);
+    }
+    for change in shopify_changes {
+        let date = modification_date(&change.timestamp);
+        groups.entry(date.clone()).or_default();
+        shopify_groups.entry(date).or_default().push(change.clone());
     }
 
     // Convert groups to DailyModificationGroup
@@ -819,12 +1398,15 @@ fn group_modifications_by_date(logs: &[&LogEntry]) -> Vec<DailyModificationGroup
                 })
                 .collect();
 
-            // Shopify-related logic has been removed.
+            let shopify_details = shopify_groups.remove(&date).unwrap_or_default();
+            let shopify_net_change: i32 = shopify_details.iter().map(|d| d.change).sum();
 
             DailyModificationGroup {
-                date: date.clone(),
+                date,
                 app_net_change,
                 app_details,
+                shopify_net_change,
+                shopify_details,
             }
         })
         .collect();
@@ -835,152 +1417,1170 @@ fn group_modifications_by_date(logs: &[&LogEntry]) -> Vec<DailyModificationGroup
     daily_groups
 }
 
+/// Movement not explained by the app's logs (orders, manual edits in Shopify):
+/// `current_quantity` minus the start quantity plus the app changes
+pub fn compute_discrepancy(current_quantity: i32, start_quantity: i32, app_net_change: i32) -> i32 {
+    current_quantity - (start_quantity + app_net_change)
+}
+
+/// Shopify-side movements between consecutive `(time, quantity)` checkpoints: the change
+/// in quantity the app logs in that interval don't account for. Each one is dated at the
+/// end of its interval with `source: "shopify"`; intervals fully explained are left out.
+pub fn shopify_movements(
+    checkpoints: &[(chrono::DateTime<chrono::Utc>, i32)],
+    logs: &[&LogEntry],
+) -> Vec<ModificationDetail> {
+    let logged: Vec<(chrono::DateTime<chrono::Utc>, i32)> = logs
+        .iter()
+        .filter_map(|log| {
+            let at = chrono::DateTime::parse_from_rfc3339(&log.timestamp).ok()?;
+            Some((at.with_timezone(&chrono::Utc), log.data.rettifica))
+        })
+        .collect();
+
+    checkpoints
+        .windows(2)
+        .filter_map(|pair| {
+            let ((from, from_quantity), (to, to_quantity)) = (pair[0], pair[1]);
+            let app_change: i32 = logged
+                .iter()
+                .filter(|(at, _)| *at > from && *at <= to)
+                .map(|(_, change)| change)
+                .sum();
+            let change = to_quantity - from_quantity - app_change;
+            (change != 0).then(|| ModificationDetail {
+                timestamp: to.to_rfc3339(),
+                source: "shopify".to_string(),
+                change,
+                reason: Some(format!(
+                    "Variazione su Shopify senza log dell'app dal {}",
+                    from.format("%Y-%m-%d %H:%M")
+                )),
+            })
+        })
+        .collect()
+}
+
+/// Reasons accepted by Shopify's inventory mutations that make sense for manual adjustments
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AdjustReason {
+    Correction,
+    CycleCountAvailable,
+    Damaged,
+    Other,
+    Promotion,
+    QualityControl,
+    Received,
+    Restock,
+    SafetyStock,
+    Shrinkage,
+}
+
+impl AdjustReason {
+    pub const ALL: [AdjustReason; 10] = [
+        AdjustReason::Correction,
+        AdjustReason::CycleCountAvailable,
+        AdjustReason::Damaged,
+        AdjustReason::Other,
+        AdjustReason::Promotion,
+        AdjustReason::QualityControl,
+        AdjustReason::Received,
+        AdjustReason::Restock,
+        AdjustReason::SafetyStock,
+        AdjustReason::Shrinkage,
+    ];
+
+    /// The reason string Shopify expects
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AdjustReason::Correction => "correction",
+            AdjustReason::CycleCountAvailable => "cycle_count_available",
+            AdjustReason::Damaged => "damaged",
+            AdjustReason::Other => "other",
+            AdjustReason::Promotion => "promotion",
+            AdjustReason::QualityControl => "quality_control",
+            AdjustReason::Received => "received",
+            AdjustReason::Restock => "restock",
+            AdjustReason::SafetyStock => "safety_stock",
+            AdjustReason::Shrinkage => "shrinkage",
+        }
+    }
+
+    /// Label shown to staff in the UI
+    pub fn label_it(&self) -> &'static str {
+        match self {
+            AdjustReason::Correction => "Correzione",
+            AdjustReason::CycleCountAvailable => "Conteggio inventario",
+            AdjustReason::Damaged => "Danneggiato",
+            AdjustReason::Other => "Altro",
+            AdjustReason::Promotion => "Promozione",
+            AdjustReason::QualityControl => "Controllo qualità",
+            AdjustReason::Received => "Ricevuto",
+            AdjustReason::Restock => "Rifornimento",
+            AdjustReason::SafetyStock => "Scorta di sicurezza",
+            AdjustReason::Shrinkage => "Ammanco",
+        }
+    }
+}
+
+/// One entry of the adjustment reason dropdown
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ReasonOption {
+    pub value: String,
+    pub label_it: String,
+}
+
+/// Supported adjustment reasons with their Italian labels
+pub fn adjustment_reason_options() -> Vec<ReasonOption> {
+    AdjustReason::ALL
+        .iter()
+        .map(|reason| ReasonOption {
+            value: reason.as_str().to_string(),
+            label_it: reason.label_it().to_string(),
+        })
+        .collect()
+}
+
+/// Reasons the frontend can offer for GraphQL inventory adjustments
 #[tauri::command]
-pub async fn adjust_inventory_graphql(
-    config: State<'_, AppConfig>,
-    inventory_item_id: String,
-    location_id: String,
-    delta: i32,
-    reason: String,
-) -> Result<StatusResponse, String> {
-    let client = reqwest::Client::new();
-    let url = config.get_api_url("graphql.json");
+pub fn get_adjustment_reasons() -> Vec<ReasonOption> {
+    adjustment_reason_options()
+}
 
-    // Convert to Shopify Global IDs
-    let inventory_item_gid = format!("gid://shopify/InventoryItem/{}", inventory_item_id);
-    let location_gid = format!("gid://shopify/Location/{}", location_id);
+// ============================================================================
+// IDEMPOTENT ADJUSTMENTS
+// ============================================================================
+
+const APPLIED_OPERATIONS_FILE: &str = "appliedOperationsCappelletto.json";
+
+/// How many operations are remembered; older ones are forgotten first
+pub const MAX_APPLIED_OPERATIONS: usize = 1000;
+
+/// A random UUID (v4) identifying one inventory operation across retries
+pub fn new_operation_id() -> Result<String, String> {
+    use ring::rand::SecureRandom;
+
+    let mut bytes = [0u8; 16];
+    ring::rand::SystemRandom::new()
+        .fill(&mut bytes)
+        .map_err(|_| "Failed to generate an operation ID".to_string())?;
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+
+    let hex: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+    Ok(format!(
+        "{}-{}-{}-{}-{}",
+        &hex[0..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..32]
+    ))
+}
 
-    let query = r#"
-        mutation inventoryAdjustQuantities($input: InventoryAdjustQuantitiesInput!) {
-            inventoryAdjustQuantities(input: $input) {
-                userErrors {
-                    field
-                    message
-                }
-                inventoryAdjustmentGroup {
-                    id
-                    createdAt
-                    reason
-                    changes {
-                        name
-                        delta
-                    }
+/// Reject operation ids that can't be embedded in a reference document URI
+pub fn validate_operation_id(operation_id: &str) -> Result<String, String> {
+    let operation_id = operation_id.trim();
+    let valid = !operation_id.is_empty()
+        && operation_id.len() <= 64
+        && operation_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !valid {
+        return Err(format!("Invalid operation ID: '{}'", operation_id));
+    }
+    Ok(operation_id.to_string())
+}
+
+/// The `referenceDocumentUri` Shopify records with the adjustments of one operation
+pub fn reference_document_uri(operation_id: &str) -> String {
+    format!("app://inventario-cappelletto/op/{}", operation_id)
+}
+
+/// Whether an operation's adjustment is known to have reached Shopify
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum OperationStatus {
+    /// Recorded before sending; Shopify may or may not have applied it
+    Pending,
+    Applied,
+}
+
+/// One change of an operation, with the available quantity read right before sending it
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct OperationChange {
+    pub inventory_item_id: String,
+    pub location_id: String,
+    pub delta: i32,
+    pub before: i32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct OperationRecord {
+    pub operation_id: String,
+    pub status: OperationStatus,
+    #[serde(default)]
+    pub changes: Vec<OperationChange>,
+}
+
+/// Ids of the operations being sent right now. The lock also serializes every
+/// read-modify-write of the operations file.
+#[derive(Default)]
+pub struct OperationStore(std::sync::Mutex<std::collections::HashSet<String>>);
+
+impl OperationStore {
+    /// Mark `operation_id` as in flight, failing if another call is already sending it
+    pub fn claim(&self, operation_id: &str) -> Result<(), AppError> {
+        let mut in_flight = self.lock()?;
+        if !in_flight.insert(operation_id.to_string()) {
+            return Err(AppError::Conflict(format!(
+                "Operation {} is already in progress",
+                operation_id
+            )));
+        }
+        Ok(())
+    }
+
+    pub fn release(&self, operation_id: &str) {
+        if let Ok(mut in_flight) = self.0.lock() {
+            in_flight.remove(operation_id);
+        }
+    }
+
+    /// Run `f` while holding the lock that guards the operations file
+    pub fn with_file<T>(&self, f: impl FnOnce() -> Result<T, String>) -> Result<T, AppError> {
+        let _guard = self.lock()?;
+        f().map_err(AppError::from)
+    }
+
+    fn lock(
+        &self,
+    ) -> Result<std::sync::MutexGuard<'_, std::collections::HashSet<String>>, AppError> {
+        self.0
+            .lock()
+            .map_err(|_| AppError::Other("Operation store lock poisoned".to_string()))
+    }
+}
+
+/// Recorded operations, oldest first. Files written before operations had a status
+/// hold bare ids, which are read as applied.
+pub fn read_operation_records(dir: &std::path::Path) -> Result<Vec<OperationRecord>, String> {
+    let path = dir.join(APPLIED_OPERATIONS_FILE);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let content = std::fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read applied operations: {}", e))?;
+    if let Ok(ids) = serde_json::from_str::<Vec<String>>(&content) {
+        return Ok(ids
+            .into_iter()
+            .map(|operation_id| OperationRecord {
+                operation_id,
+                status: OperationStatus::Applied,
+                changes: Vec::new(),
+            })
+            .collect());
+    }
+    serde_json::from_str(&content).map_err(|e| format!("Failed to parse applied operations: {}", e))
+}
+
+fn write_operation_records(
+    dir: &std::path::Path,
+    records: &[OperationRecord],
+) -> Result<(), String> {
+    std::fs::create_dir_all(dir)
+        .map_err(|e| format!("Failed to create app data directory: {}", e))?;
+    let json_content = serde_json::to_string(records)
+        .map_err(|e| format!("Failed to serialize applied operations: {}", e))?;
+    std::fs::write(dir.join(APPLIED_OPERATIONS_FILE), json_content)
+        .map_err(|e| format!("Failed to write applied operations: {}", e))
+}
+
+/// Insert or replace `record`, keeping at most `MAX_APPLIED_OPERATIONS` operations
+pub fn save_operation_record(dir: &std::path::Path, record: OperationRecord) -> Result<(), String> {
+    let mut records = read_operation_records(dir)?;
+    records.retain(|r| r.operation_id != record.operation_id);
+    records.push(record);
+    let excess = records.len().saturating_sub(MAX_APPLIED_OPERATIONS);
+    records.drain(..excess);
+    write_operation_records(dir, &records)
+}
+
+/// Forget an operation Shopify is known to have rejected, so it can be sent again
+pub fn remove_operation_record(dir: &std::path::Path, operation_id: &str) -> Result<(), String> {
+    let mut records = read_operation_records(dir)?;
+    let before = records.len();
+    records.retain(|r| r.operation_id != operation_id);
+    if records.len() == before {
+        return Ok(());
+    }
+    write_operation_records(dir, &records)
+}
+
+/// What the live quantities say about a pending operation
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PendingCheck {
+    /// Every level moved by its delta
+    Applied,
+    /// Every level is still at the quantity seen before sending
+    NotApplied,
+    /// The levels match neither, e.g. because of other sales in the meantime
+    Unknown,
+}
+
+/// Compare a pending operation's changes with the current available quantities
+/// (inventory_item_id → location_id → available)
+pub fn check_pending_operation(
+    changes: &[OperationChange],
+    levels: &HashMap<String, HashMap<String, i32>>,
+) -> PendingCheck {
+    let current: Option<Vec<i32>> = changes
+        .iter()
+        .map(|c| {
+            levels
+                .get(&c.inventory_item_id)
+                .and_then(|l| l.get(&c.location_id))
+                .copied()
+        })
+        .collect();
+    let current = match current {
+        Some(current) if !changes.is_empty() => current,
+        _ => return PendingCheck::Unknown,
+    };
+
+    if changes
+        .iter()
+        .zip(&current)
+        .all(|(c, now)| *now == c.before + c.delta)
+    {
+        PendingCheck::Applied
+    } else if changes
+        .iter()
+        .zip(&current)
+        .all(|(c, now)| *now == c.before)
+    {
+        PendingCheck::NotApplied
+    } else {
+        PendingCheck::Unknown
+    }
+}
+
+const ADJUST_QUANTITIES_MUTATION: &str = r#"
+    mutation inventoryAdjustQuantities($input: InventoryAdjustQuantitiesInput!) {
+        inventoryAdjustQuantities(input: $input) {
+            userErrors {
+                field
+                message
+            }
+            inventoryAdjustmentGroup {
+                id
+                createdAt
+                reason
+                changes {
+                    name
+                    delta
                 }
             }
         }
-    "#;
+    }
+"#;
+
+/// Current available quantities of the levels touched by `updates`
+async fn fetch_update_levels(
+    client: &reqwest::Client,
+    config: &AppConfig,
+    updates: &[InventoryUpdate],
+) -> Result<HashMap<String, HashMap<String, i32>>, AppError> {
+    let mut item_ids: Vec<String> = updates.iter().map(|u| u.variant_id.clone()).collect();
+    item_ids.sort();
+    item_ids.dedup();
+    let entries = fetch_inventory_level_entries(client, config, &item_ids).await?;
+    Ok(parse_inventory_levels(
+        &json!({ "inventory_levels": entries }),
+    )?)
+}
 
-    let variables = json!({
-        "input": {
-            "reason": reason,
-            "name": "available",
-            "referenceDocumentUri": "app://inventario-cappelletto",
-            "changes": [{
-                "delta": delta,
-                "inventoryItemId": inventory_item_gid,
-                "locationId": location_gid
-            }]
+/// Send `updates` under `operation_id` unless it was applied before. The operation is
+/// recorded as pending before the mutation goes out; a retry of a pending operation
+/// compares the live quantities with the ones seen before the first attempt and only
+/// sends again if nothing moved. Returns None when the operation was already applied,
+/// otherwise the GraphQL response (which may still carry `userErrors`).
+async fn send_operation(
+    client: &reqwest::Client,
+    config: &AppConfig,
+    store: &OperationStore,
+    dir: &std::path::Path,
+    operation_id: &str,
+    reason: &str,
+    updates: &[InventoryUpdate],
+) -> Result<Option<Value>, AppError> {
+    let existing = store
+        .with_file(|| read_operation_records(dir))?
+        .into_iter()
+        .find(|r| r.operation_id == operation_id);
+
+    match existing {
+        Some(record) if record.status == OperationStatus::Applied => {
+            info!(
+                "⏭️ Operation {} was already applied, skipping",
+                operation_id
+            );
+            return Ok(None);
         }
-    });
+        Some(record) => {
+            let same_changes = record.changes.len() == updates.len()
+                && record.changes.iter().zip(updates).all(|(c, u)| {
+                    c.inventory_item_id == u.variant_id
+                        && c.location_id == u.location_id
+                        && c.delta == u.adjustment
+                });
+            if !same_changes {
+                return Err(AppError::Conflict(format!(
+                    "Operation {} was started with different changes",
+                    operation_id
+                )));
+            }
+
+            let levels = fetch_update_levels(client, config, updates).await?;
+            match check_pending_operation(&record.changes, &levels) {
+                PendingCheck::Applied => {
+                    info!(
+                        "⏭️ Pending operation {} reached Shopify, marking it applied",
+                        operation_id
+                    );
+                    let record = OperationRecord {
+                        status: OperationStatus::Applied,
+                        ..record
+                    };
+                    store.with_file(|| save_operation_record(dir, record))?;
+                    return Ok(None);
+                }
+                PendingCheck::NotApplied => {
+                    info!(
+                        "🔁 Pending operation {} did not reach Shopify, sending again",
+                        operation_id
+                    );
+                }
+                PendingCheck::Unknown => {
+                    return Err(AppError::Conflict(format!(
+                        "Operation {} may already have been applied: the current quantities \
+                         match neither the ones before nor after it. Check the inventory \
+                         before adjusting again.",
+                        operation_id
+                    )));
+                }
+            }
+        }
+        None => {
+            let levels = fetch_update_levels(client, config, updates).await?;
+            let changes = updates
+                .iter()
+                .map(|u| {
+                    let before = levels
+                        .get(&u.variant_id)
+                        .and_then(|l| l.get(&u.location_id))
+                        .copied()
+                        .ok_or_else(|| {
+                            AppError::NotFound(format!(
+                                "Inventory item {} is not stocked at location {}",
+                                u.variant_id, u.location_id
+                            ))
+                        })?;
+                    Ok(OperationChange {
+                        inventory_item_id: u.variant_id.clone(),
+                        location_id: u.location_id.clone(),
+                        delta: u.adjustment,
+                        before,
+                    })
+                })
+                .collect::<Result<Vec<_>, AppError>>()?;
+            let record = OperationRecord {
+                operation_id: operation_id.to_string(),
+                status: OperationStatus::Pending,
+                changes,
+            };
+            store.with_file(|| save_operation_record(dir, record))?;
+        }
+    }
 
     let payload = json!({
-        "query": query,
-        "variables": variables
+        "query": ADJUST_QUANTITIES_MUTATION,
+        "variables": {
+            "input": {
+                "reason": reason,
+                "name": "available",
+                "referenceDocumentUri": reference_document_uri(operation_id),
+                "changes": build_adjust_changes(updates)
+            }
+        }
     });
 
-    println!("🔄 Making GraphQL inventory adjustment:");
-    println!("   📦 Inventory Item: {}", inventory_item_id);
-    println!("   📍 Location: {}", location_id);
-    println!("   📊 Delta: {}", delta);
-    println!("   📝 Reason: {}", reason);
-
+    // Without a response the outcome is unknown, so the operation stays pending
     let response = client
-        .post(&url)
+        .post(config.get_api_url("graphql.json"))
         .headers(config.get_headers())
         .json(&payload)
         .send()
         .await
-        .map_err(|e| format!("GraphQL request failed: {}", e))?;
+        .map_err(|e| config.request_error(e))?;
 
     if !response.status().is_success() {
-        let error_text = response.text().await.unwrap_or_default();
-        return Err(format!(
-            "Failed to adjust inventory via GraphQL: {}",
-            error_text
-        ));
+        // A 4xx means the mutation was rejected; after a 5xx it may still have run
+        let rejected = response.status().is_client_error();
+        let error = AppError::from_response(response).await;
+        if rejected {
+            forget_operation(store, dir, operation_id);
+        }
+        return Err(error);
     }
 
     let response_json: Value = response
         .json()
         .await
-        .map_err(|e| format!("Failed to parse GraphQL response: {}", e))?;
+        .map_err(|e| AppError::Parse(format!("Failed to parse GraphQL response: {}", e)))?;
 
-    // Check for GraphQL errors
     if let Some(errors) = response_json.get("errors") {
-        return Err(format!("GraphQL errors: {}", errors));
+        forget_operation(store, dir, operation_id);
+        return Err(format!("GraphQL errors: {}", errors).into());
+    }
+
+    // Shopify applies all changes of the mutation or none of them
+    let rejected = response_json["data"]["inventoryAdjustQuantities"]["userErrors"]
+        .as_array()
+        .is_some_and(|errors| !errors.is_empty());
+    if rejected {
+        forget_operation(store, dir, operation_id);
+    } else {
+        // Shopify has applied the change; a stale pending record is resolved on retry
+        let result = store.with_file(|| {
+            let mut records = read_operation_records(dir)?;
+            match records.iter_mut().find(|r| r.operation_id == operation_id) {
+                Some(record) => record.status = OperationStatus::Applied,
+                None => return Ok(()),
+            }
+            write_operation_records(dir, &records)
+        });
+        if let Err(e) = result {
+            warn!(
+                "⚠️ Failed to record applied operation {}: {}",
+                operation_id, e
+            );
+        }
+    }
+
+    Ok(Some(response_json))
+}
+
+fn forget_operation(store: &OperationStore, dir: &std::path::Path, operation_id: &str) {
+    if let Err(e) = store.with_file(|| remove_operation_record(dir, operation_id)) {
+        warn!(
+            "⚠️ Failed to forget rejected operation {}: {}",
+            operation_id, e
+        );
     }
+}
+
+/// Outcome of an idempotent adjustment. `already_applied` means the operation id had been
+/// applied before, so nothing was sent to Shopify this time.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct AdjustmentResponse {
+    pub status: String,
+    pub message: String,
+    pub operation_id: String,
+    pub already_applied: bool,
+}
+
+/// Adjust one inventory level via GraphQL. Pass the same `operation_id` when retrying an
+/// adjustment whose outcome is unknown; a new one is generated when omitted.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn adjust_inventory_graphql(
+    app: tauri::AppHandle,
+    client: State<'_, reqwest::Client>,
+    config: State<'_, AppConfig>,
+    operations: State<'_, OperationStore>,
+    inventory_item_id: String,
+    location_id: String,
+    delta: i32,
+    reason: String,
+    operation_id: Option<String>,
+) -> Result<AdjustmentResponse, AppError> {
+    let operation_id = match operation_id {
+        Some(id) => validate_operation_id(&id)?,
+        None => new_operation_id()?,
+    };
+    let dir = crate::status::app_data_dir(&app)?;
+    let updates = [InventoryUpdate {
+        variant_id: inventory_item_id.clone(),
+        location_id: location_id.clone(),
+        adjustment: delta,
+    }];
+
+    info!("🔄 Making GraphQL inventory adjustment:");
+    info!("   📦 Inventory Item: {}", inventory_item_id);
+    info!("   📍 Location: {}", location_id);
+    info!("   📊 Delta: {}", delta);
+    info!("   📝 Reason: {}", reason);
+    info!("   🆔 Operation: {}", operation_id);
+
+    operations.claim(&operation_id)?;
+    let sent = send_operation(
+        &client,
+        &config,
+        &operations,
+        &dir,
+        &operation_id,
+        &reason,
+        &updates,
+    )
+    .await;
+    operations.release(&operation_id);
+
+    let response_json = match sent? {
+        Some(response_json) => response_json,
+        None => {
+            return Ok(AdjustmentResponse {
+                status: "success".to_string(),
+                message: format!("Operation {} already applied", operation_id),
+                operation_id,
+                already_applied: true,
+            })
+        }
+    };
 
     // Check for user errors
     if let Some(user_errors) =
         response_json["data"]["inventoryAdjustQuantities"]["userErrors"].as_array()
     {
         if !user_errors.is_empty() {
-            return Err(format!("Inventory adjustment errors: {:?}", user_errors));
+            return Err(format!("Inventory adjustment errors: {:?}", user_errors).into());
         }
     }
 
-    println!("✅ GraphQL inventory adjustment completed successfully");
+    info!("✅ GraphQL inventory adjustment completed successfully");
 
-    Ok(StatusResponse {
+    Ok(AdjustmentResponse {
         status: "success".to_string(),
         message: format!("Inventory adjusted by {} via GraphQL", delta),
+        operation_id,
+        already_applied: false,
     })
 }
 
-/// Check if a product has zero inventory across all locations
-async fn has_zero_inventory_across_all_locations(
-    config: &tauri::State<'_, AppConfig>,
-    product_id: &str,
-) -> Result<bool, String> {
-    println!("🔍 Checking total inventory for product {}", product_id);
+/// Result of setting an inventory level via GraphQL, so the UI can confirm the write
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SetQuantityResult {
+    pub inventory_item_id: String,
+    pub location_id: String,
+    pub quantity: i32,
+    /// On-hand quantity after the change, as reported by Shopify
+    pub on_hand: Option<i32>,
+}
 
-    // Get product details to find all variants
-    let product =
-        crate::products::get_product_by_id(config.clone(), product_id.to_string()).await?;
+/// Check an `inventorySetQuantities` response for errors and return the on-hand
+/// quantity after the change (None if Shopify didn't report one)
+pub fn parse_set_quantities_response(response: &Value) -> Result<Option<i32>, String> {
+    if let Some(errors) = response.get("errors") {
+        return Err(format!("GraphQL errors: {}", errors));
+    }
 
-    // Get all inventory item IDs
-    let inventory_item_ids: Vec<String> = product
-        .variants
-        .iter()
-        .map(|v| v.inventory_item_id.clone())
-        .collect();
+    let result = &response["data"]["inventorySetQuantities"];
+    if let Some(user_errors) = result["userErrors"].as_array() {
+        if !user_errors.is_empty() {
+            return Err(format!("Inventory set errors: {:?}", user_errors));
+        }
+    }
 
-    // Get inventory levels across all locations
-    let inventory_levels = get_inventory_levels(config.clone(), inventory_item_ids).await?;
+    let on_hand = result["inventoryAdjustmentGroup"]["changes"]
+        .as_array()
+        .and_then(|changes| {
+            changes
+                .iter()
+                .find(|change| change["name"].as_str() == Some("on_hand"))
+        })
+        .and_then(|change| change["quantityAfterChange"].as_i64())
+        .map(|quantity| quantity as i32);
 
-    // Check if all variants have zero inventory across all locations
-    let has_inventory = inventory_levels
-        .values()
-        .any(|location_map| location_map.values().any(|&quantity| quantity > 0));
+    Ok(on_hand)
+}
 
-    let is_zero = !has_inventory;
-    println!("📊 Product {} has zero inventory: {}", product_id, is_zero);
+/// Set the available quantity at a location using the `inventorySetQuantities` mutation
+#[tauri::command]
+pub async fn set_inventory_level_graphql(
+    client: State<'_, reqwest::Client>,
+    config: State<'_, AppConfig>,
+    inventory_item_id: String,
+    location_id: String,
+    quantity: i32,
+    reason: String,
+) -> Result<SetQuantityResult, AppError> {
+    let operation_id = new_operation_id()?;
+    let variables = set_quantities_variables(
+        &inventory_item_id,
+        &location_id,
+        quantity,
+        &reason,
+        &operation_id,
+        None,
+    );
 
-    Ok(is_zero)
+    info!("🔄 Setting inventory level via GraphQL:");
+    info!("   📦 Inventory Item: {}", inventory_item_id);
+    info!("   📍 Location: {}", location_id);
+    info!("   📊 Quantity: {}", quantity);
+    info!("   📝 Reason: {}", reason);
+    info!("   🆔 Operation: {}", operation_id);
+
+    let response_json = send_set_quantities(&client, &config, variables).await?;
+
+    let on_hand = parse_set_quantities_response(&response_json)?;
+
+    info!(
+        "✅ Inventory level set via GraphQL (on hand: {:?})",
+        on_hand
+    );
+    Ok(SetQuantityResult {
+        inventory_item_id,
+        location_id,
+        quantity,
+        on_hand,
+    })
+}
+
+/// Variables for `inventorySetQuantities` setting the available quantity. With
+/// `compare_quantity` Shopify only applies the change if the live quantity still matches.
+/// `operation_id` tags the change in Shopify's history like the adjustments do.
+pub fn set_quantities_variables(
+    inventory_item_id: &str,
+    location_id: &str,
+    quantity: i32,
+    reason: &str,
+    operation_id: &str,
+    compare_quantity: Option<i32>,
+) -> Value {
+    let mut item = json!({
+        "inventoryItemId": to_shopify_gid("InventoryItem", inventory_item_id),
+        "locationId": to_shopify_gid("Location", location_id),
+        "quantity": quantity
+    });
+    if let Some(compare_quantity) = compare_quantity {
+        item["compareQuantity"] = json!(compare_quantity);
+    }
+
+    json!({
+        "input": {
+            "reason": reason,
+            "name": "available",
+            "ignoreCompareQuantity": compare_quantity.is_none(),
+            "referenceDocumentUri": reference_document_uri(operation_id),
+            "quantities": [item]
+        }
+    })
+}
+
+/// Run the `inventorySetQuantities` mutation and return the raw response
+async fn send_set_quantities(
+    client: &reqwest::Client,
+    config: &AppConfig,
+    variables: Value,
+) -> Result<Value, AppError> {
+    let url = config.get_api_url("graphql.json");
+
+    let query = r#"
+        mutation inventorySetQuantities($input: InventorySetQuantitiesInput!) {
+            inventorySetQuantities(input: $input) {
+                userErrors {
+                    field
+                    message
+                    code
+                }
+                inventoryAdjustmentGroup {
+                    reason
+                    changes {
+                        name
+                        delta
+                        quantityAfterChange
+                    }
+                }
+            }
+        }
+    "#;
+
+    let response = client
+        .post(&url)
+        .headers(config.get_headers())
+        .json(&json!({ "query": query, "variables": variables }))
+        .send()
+        .await
+        .map_err(|e| config.request_error(e))?;
+
+    if !response.status().is_success() {
+        return Err(AppError::from_response(response).await);
+    }
+
+    response
+        .json()
+        .await
+        .map_err(|e| AppError::Parse(format!("Failed to parse GraphQL response: {}", e)))
+}
+
+/// Fail with `Conflict` when the live quantity isn't the one the user saw
+pub fn check_expected_quantity(current: i32, expected: Option<i32>) -> Result<(), AppError> {
+    match expected {
+        Some(expected) if expected != current => Err(AppError::Conflict(format!(
+            "Quantity changed to {} (expected {}), please check again",
+            current, expected
+        ))),
+        _ => Ok(()),
+    }
+}
+
+/// Whether an `inventorySetQuantities` response was rejected because `compareQuantity`
+/// no longer matched
+pub fn is_compare_quantity_stale(response: &Value) -> bool {
+    response["data"]["inventorySetQuantities"]["userErrors"]
+        .as_array()
+        .is_some_and(|errors| {
+            errors
+                .iter()
+                .any(|error| error["code"].as_str() == Some("COMPARE_QUANTITY_STALE"))
+        })
+}
+
+/// Set the available quantity like `set_inventory_level_graphql`, but when
+/// `expected_quantity` is given only if the live level still matches it, so two people
+/// editing the same variant can't silently overwrite each other. A mismatch is a
+/// `Conflict` error for the UI to ask for a re-check.
+#[tauri::command]
+pub async fn set_inventory_level_safe(
+    client: State<'_, reqwest::Client>,
+    config: State<'_, AppConfig>,
+    inventory_item_id: String,
+    location_id: String,
+    quantity: i32,
+    expected_quantity: Option<i32>,
+    reason: String,
+) -> Result<SetQuantityResult, AppError> {
+    if let Some(expected) = expected_quantity {
+        let levels = get_inventory_levels(
+            client.clone(),
+            config.clone(),
+            vec![inventory_item_id.clone()],
+        )
+        .await?;
+        let current = levels
+            .get(&inventory_item_id)
+            .and_then(|by_location| by_location.get(&location_id))
+            .copied()
+            .unwrap_or(0);
+        info!(
+            "🔒 Live quantity {} (expected {}) for item {} at {}",
+            current, expected, inventory_item_id, location_id
+        );
+        check_expected_quantity(current, expected_quantity)?;
+    }
+
+    // Shopify checks compareQuantity again, covering a change between our read and write
+    let operation_id = new_operation_id()?;
+    let variables = set_quantities_variables(
+        &inventory_item_id,
+        &location_id,
+        quantity,
+        &reason,
+        &operation_id,
+        expected_quantity,
+    );
+    let response_json = send_set_quantities(&client, &config, variables).await?;
+
+    if is_compare_quantity_stale(&response_json) {
+        return Err(AppError::Conflict(
+            "Quantity changed while saving, please check again".to_string(),
+        ));
+    }
+    let on_hand = parse_set_quantities_response(&response_json)?;
+
+    info!(
+        "✅ Inventory level set to {} for item {} at {}",
+        quantity, inventory_item_id, location_id
+    );
+    Ok(SetQuantityResult {
+        inventory_item_id,
+        location_id,
+        quantity,
+        on_hand,
+    })
+}
+
+/// Per-change outcome of a batched GraphQL inventory adjustment
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct BatchAdjustResult {
+    pub inventory_item_id: String,
+    pub location_id: String,
+    pub delta: i32,
+    pub success: bool,
+    pub error: Option<String>,
+}
+
+/// Convert a numeric Shopify id to its `gid://shopify/<kind>/<id>` form (gids pass through)
+pub fn to_shopify_gid(kind: &str, id: &str) -> String {
+    if id.starts_with("gid://") {
+        id.to_string()
+    } else {
+        format!("gid://shopify/{}/{}", kind, id)
+    }
+}
+
+/// Build the `changes` array of an `inventoryAdjustQuantities` mutation
+pub fn build_adjust_changes(updates: &[InventoryUpdate]) -> Value {
+    Value::Array(
+        updates
+            .iter()
+            .map(|update| {
+                json!({
+                    "delta": update.adjustment,
+                    "inventoryItemId": to_shopify_gid("InventoryItem", &update.variant_id),
+                    "locationId": to_shopify_gid("Location", &update.location_id),
+                })
+            })
+            .collect(),
+    )
+}
+
+/// Map `userErrors` back onto the submitted changes.
+///
+/// Errors carry a field path like `["input", "changes", "2", "delta"]`; those are attached
+/// to that change. Shopify applies the mutation all-or-nothing, so when any error is
+/// reported every other change is marked as not applied too.
+pub fn parse_batch_adjust_response(
+    updates: &[InventoryUpdate],
+    user_errors: &[Value],
+) -> Vec<BatchAdjustResult> {
+    let mut errors_by_index: HashMap<usize, Vec<String>> = HashMap::new();
+    let mut general_errors = Vec::new();
+
+    for user_error in user_errors {
+        let message = user_error["message"]
+            .as_str()
+            .unwrap_or("Unknown error")
+            .to_string();
+        let index = user_error["field"].as_array().and_then(|field| {
+            let pos = field.iter().position(|f| f.as_str() == Some("changes"))?;
+            field.get(pos + 1)?.as_str()?.parse::<usize>().ok()
+        });
+        match index {
+            Some(i) if i < updates.len() => errors_by_index.entry(i).or_default().push(message),
+            _ => general_errors.push(message),
+        }
+    }
+
+    updates
+        .iter()
+        .enumerate()
+        .map(|(i, update)| {
+            let error = match errors_by_index.get(&i) {
+                Some(messages) => Some(messages.join("; ")),
+                None if !general_errors.is_empty() => Some(general_errors.join("; ")),
+                None if !user_errors.is_empty() => {
+                    Some("Not applied: other changes in the batch were rejected".to_string())
+                }
+                None => None,
+            };
+            BatchAdjustResult {
+                inventory_item_id: update.variant_id.clone(),
+                location_id: update.location_id.clone(),
+                delta: update.adjustment,
+                success: error.is_none(),
+                error,
+            }
+        })
+        .collect()
+}
+
+/// Apply many inventory adjustments in a single `inventoryAdjustQuantities` mutation. Like
+/// `adjust_inventory_graphql`, pass the same `operation_id` to retry a batch whose outcome
+/// is unknown; a new one is generated when omitted.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn adjust_inventory_batch_graphql(
+    app: tauri::AppHandle,
+    client: State<'_, reqwest::Client>,
+    config: State<'_, AppConfig>,
+    operations: State<'_, OperationStore>,
+    updates: Vec<InventoryUpdate>,
+    reason: String,
+    operation_id: Option<String>,
+) -> Result<Vec<BatchAdjustResult>, AppError> {
+    if updates.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let operation_id = match operation_id {
+        Some(id) => validate_operation_id(&id)?,
+        None => new_operation_id()?,
+    };
+    let dir = crate::status::app_data_dir(&app)?;
+
+    info!(
+        "🔄 Making batched GraphQL inventory adjustment: {} changes ({}, operation {})",
+        updates.len(),
+        reason,
+        operation_id
+    );
+
+    operations.claim(&operation_id)?;
+    let sent = send_operation(
+        &client,
+        &config,
+        &operations,
+        &dir,
+        &operation_id,
+        &reason,
+        &updates,
+    )
+    .await;
+    operations.release(&operation_id);
+
+    let user_errors = match sent? {
+        Some(response_json) => response_json["data"]["inventoryAdjustQuantities"]["userErrors"]
+            .as_array()
+            .cloned()
+            .unwrap_or_default(),
+        // Already applied earlier: every change went through
+        None => Vec::new(),
+    };
+    let results = parse_batch_adjust_response(&updates, &user_errors);
+
+    info!(
+        "✅ Batched adjustment: {}/{} changes applied",
+        results.iter().filter(|r| r.success).count(),
+        results.len()
+    );
+    Ok(results)
+}
+
+/// Outcome of a status change that is only applied while the product still has no stock
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ConditionalStatusResult {
+    pub product_id: String,
+    pub applied: bool,
+    pub status: String,
+    pub message: String,
+}
+
+/// Re-check the zero-inventory condition right before applying a status change, so a
+/// product restocked after it was selected is left alone
+pub async fn apply_status_if_zero<C, CFut, A, AFut>(
+    product_id: &str,
+    target_status: &str,
+    check_zero: C,
+    apply: A,
+) -> Result<ConditionalStatusResult, AppError>
+where
+    C: FnOnce() -> CFut,
+    CFut: std::future::Future<Output = Result<bool, AppError>>,
+    A: FnOnce() -> AFut,
+    AFut: std::future::Future<Output = Result<(), AppError>>,
+{
+    if !check_zero().await? {
+        info!(
+            "⏭️ Inventory changed for product {}, not setting status to {}",
+            product_id, target_status
+        );
+        return Ok(ConditionalStatusResult {
+            product_id: product_id.to_string(),
+            applied: false,
+            status: target_status.to_string(),
+            message:
+                "Inventario cambiato: il prodotto ha di nuovo disponibilità, stato non modificato"
+                    .to_string(),
+        });
+    }
+
+    apply().await?;
+
+    Ok(ConditionalStatusResult {
+        product_id: product_id.to_string(),
+        applied: true,
+        status: target_status.to_string(),
+        message: format!("Stato del prodotto impostato a {}", target_status),
+    })
+}
+
+/// Set a product's status only if it still has zero inventory across all locations
+#[tauri::command]
+pub async fn set_status_if_zero(
+    product_id: String,
+    target_status: String,
+    client: State<'_, reqwest::Client>,
+    config: State<'_, AppConfig>,
+) -> Result<ConditionalStatusResult, AppError> {
+    apply_status_if_zero(
+        &product_id,
+        &target_status,
+        || async {
+            has_zero_inventory_across_all_locations(&client, &config, &product_id)
+                .await
+                .map_err(AppError::from)
+        },
+        || async {
+            update_product_status(&client, &config, &product_id, &target_status)
+                .await
+                .map(|_| ())
+                .map_err(AppError::from)
+        },
+    )
+    .await
+}
+
+/// Check if a product has zero inventory across all locations
+async fn has_zero_inventory_across_all_locations(
+    client: &tauri::State<'_, reqwest::Client>,
+    config: &tauri::State<'_, AppConfig>,
+    product_id: &str,
+) -> Result<bool, String> {
+    info!("🔍 Checking total inventory for product {}", product_id);
+
+    // Get product details to find all variants
+    let product =
+        crate::products::get_product_by_id(client.clone(), config.clone(), product_id.to_string())
+            .await?;
+
+    // Get all inventory item IDs
+    let inventory_item_ids: Vec<String> = product
+        .variants
+        .iter()
+        .map(|v| v.inventory_item_id.clone())
+        .collect();
+
+    // Get inventory levels across all locations
+    let inventory_levels =
+        get_inventory_levels(client.clone(), config.clone(), inventory_item_ids).await?;
+
+    // Check if all variants have zero inventory across all locations
+    let has_inventory = inventory_levels
+        .values()
+        .any(|location_map| location_map.values().any(|&quantity| quantity > 0));
+
+    let is_zero = !has_inventory;
+    info!("📊 Product {} has zero inventory: {}", product_id, is_zero);
+
+    Ok(is_zero)
 }
 
 /// Update product status (active/draft)
 async fn update_product_status(
+    client: &reqwest::Client,
     config: &tauri::State<'_, AppConfig>,
     product_id: &str,
     new_status: &str,
-) -> Result<(), String> {
-    println!(
+) -> Result<String, String> {
+    info!(
         "📝 Updating product {} status to: {}",
         product_id, new_status
     );
 
-    let client = reqwest::Client::new();
     let url = config.get_api_url(&format!("products/{}.json", product_id));
 
     let request_body = json!({
@@ -990,13 +2590,14 @@ async fn update_product_status(
         }
     });
 
-    let response = client
-        .put(&url)
-        .headers(config.get_headers())
-        .json(&request_body)
-        .send()
-        .await
-        .map_err(|e| format!("Request failed: {}", e))?;
+    let response = send_with_retry(
+        client
+            .put(&url)
+            .headers(config.get_headers())
+            .json(&request_body),
+        &config,
+    )
+    .await?;
 
     let status = response.status();
     if !status.is_success() {
@@ -1010,11 +2611,52 @@ async fn update_product_status(
         ));
     }
 
-    println!(
+    let data: Value = response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse JSON: {}", e))?;
+    let updated_status = data["product"]["status"]
+        .as_str()
+        .unwrap_or(new_status)
+        .to_string();
+
+    info!(
         "✅ Successfully updated product {} status to {}",
-        product_id, new_status
+        product_id, updated_status
     );
-    Ok(())
+    Ok(updated_status)
+}
+
+/// Product statuses Shopify accepts
+pub const PRODUCT_STATUSES: [&str; 3] = ["active", "draft", "archived"];
+
+/// Normalize `status` to one of `PRODUCT_STATUSES`, rejecting anything else
+pub fn validate_product_status(status: &str) -> Result<&'static str, String> {
+    let normalized = status.trim().to_lowercase();
+    PRODUCT_STATUSES
+        .iter()
+        .find(|s| **s == normalized)
+        .copied()
+        .ok_or_else(|| {
+            format!(
+                "Invalid product status: '{}' (must be one of {})",
+                status,
+                PRODUCT_STATUSES.join(", ")
+            )
+        })
+}
+
+/// Set a product's status directly (e.g. hiding seasonal items), returning the status
+/// Shopify reports after the update
+#[tauri::command]
+pub async fn set_product_status(
+    product_id: String,
+    status: String,
+    client: State<'_, reqwest::Client>,
+    config: State<'_, AppConfig>,
+) -> Result<String, AppError> {
+    let status = validate_product_status(&status)?;
+    Ok(update_product_status(&client, &config, &product_id, status).await?)
 }
 
 /// Enhanced response that includes status change information
@@ -1025,3 +2667,1152 @@ pub struct EnhancedStatusResponse {
     pub status_changed: Option<String>, // "to_draft", "to_active", or None
     pub product_status: Option<String>, // Current product status
 }
+
+// ============================================================================
+// UNDO STACK
+// ============================================================================
+
+/// Maximum number of actions kept on the undo stack
+const UNDO_STACK_LIMIT: usize = 50;
+
+/// Product/variant details needed to reverse and log an action
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UndoItem {
+    pub inventory_item_id: String,
+    pub product_id: String,
+    pub variant_title: String,
+    pub product_name: String,
+    pub price: String,
+    pub images: Vec<String>,
+}
+
+/// An inventory action recorded so it can be reversed later
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum UndoableAction {
+    Decrease {
+        item: UndoItem,
+        location_id: String,
+        negozio: String,
+        quantity: i32,
+    },
+    Transfer {
+        item: UndoItem,
+        from_location_id: String,
+        to_location_id: String,
+        from_location: String,
+        to_location: String,
+        quantity: i32,
+        /// Set when an earlier undo already took the stock back from the destination but
+        /// failed to restore the source, so only the source is left to restore
+        #[serde(default)]
+        taken_back: bool,
+    },
+}
+
+/// Inventory adjustments and compensating logs that reverse an action; `logs[i]`
+/// records `updates[i]`, and the updates must be applied in order
+#[derive(Debug, Clone)]
+pub struct UndoReversal {
+    pub updates: Vec<InventoryUpdate>,
+    pub logs: Vec<LogEntry>,
+}
+
+/// Outcome of reversing a single action
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UndoResult {
+    pub action: String,
+    pub success: bool,
+    pub error: Option<String>,
+}
+
+impl UndoItem {
+    fn log_entry(
+        &self,
+        request_type: &str,
+        negozio: &str,
+        rettifica: i32,
+        timestamp: &str,
+    ) -> LogEntry {
+        LogEntry {
+            request_type: request_type.to_string(),
+            data: create_inventory_log_data(
+                self.product_id.clone(),
+                self.variant_title.clone(),
+                negozio.to_string(),
+                self.inventory_item_id.clone(),
+                self.product_name.clone(),
+                self.price.clone(),
+                rettifica,
+                self.images.clone(),
+            ),
+            timestamp: timestamp.to_string(),
+        }
+    }
+}
+
+impl UndoableAction {
+    /// Short human-readable description used in undo results
+    pub fn describe(&self) -> String {
+        match self {
+            UndoableAction::Decrease {
+                item,
+                negozio,
+                quantity,
+                ..
+            } => format!(
+                "Rettifica -{} {} ({}) a {}",
+                quantity, item.product_name, item.variant_title, negozio
+            ),
+            UndoableAction::Transfer {
+                item,
+                from_location,
+                to_location,
+                quantity,
+                ..
+            } => format!(
+                "Trasferimento {} {} ({}) da {} a {}",
+                quantity, item.product_name, item.variant_title, from_location, to_location
+            ),
+        }
+    }
+
+    /// Build the inverse adjustments and compensating logs for this action
+    pub fn reversal(&self, timestamp: &str) -> UndoReversal {
+        match self {
+            UndoableAction::Decrease {
+                item,
+                location_id,
+                negozio,
+                quantity,
+            } => UndoReversal {
+                updates: vec![InventoryUpdate {
+                    variant_id: item.inventory_item_id.clone(),
+                    location_id: location_id.clone(),
+                    adjustment: *quantity,
+                }],
+                logs: vec![item.log_entry("Annullamento", negozio, *quantity, timestamp)],
+            },
+            UndoableAction::Transfer {
+                item,
+                from_location_id,
+                to_location_id,
+                from_location,
+                to_location,
+                quantity,
+                taken_back,
+            } => {
+                // Take the stock back from the destination first, then restore the source
+                let mut updates = vec![
+                    InventoryUpdate {
+                        variant_id: item.inventory_item_id.clone(),
+                        location_id: to_location_id.clone(),
+                        adjustment: -quantity,
+                    },
+                    InventoryUpdate {
+                        variant_id: item.inventory_item_id.clone(),
+                        location_id: from_location_id.clone(),
+                        adjustment: *quantity,
+                    },
+                ];
+                let mut logs = vec![
+                    item.log_entry("Trasferimento", to_location, -quantity, timestamp),
+                    item.log_entry("Trasferimento", from_location, *quantity, timestamp),
+                ];
+                if *taken_back {
+                    updates.remove(0);
+                    logs.remove(0);
+                }
+                UndoReversal { updates, logs }
+            }
+        }
+    }
+
+    /// What is still left to reverse after the first `applied` updates of this action's
+    /// reversal went through and the next one failed
+    pub fn after_partial_reversal(mut self, applied: usize) -> UndoableAction {
+        if let UndoableAction::Transfer { taken_back, .. } = &mut self {
+            if applied > 0 {
+                *taken_back = true;
+            }
+        }
+        self
+    }
+}
+
+/// In-memory stack of recent inventory actions, most recent last
+#[derive(Default)]
+pub struct UndoStack(std::sync::Mutex<Vec<UndoableAction>>);
+
+impl UndoStack {
+    pub fn push(&self, action: UndoableAction) {
+        let mut actions = self.0.lock().unwrap();
+        actions.push(action);
+        if actions.len() > UNDO_STACK_LIMIT {
+            actions.remove(0);
+        }
+    }
+
+    pub fn pop(&self) -> Option<UndoableAction> {
+        self.0.lock().unwrap().pop()
+    }
+
+    pub fn actions(&self) -> Vec<UndoableAction> {
+        self.0.lock().unwrap().clone()
+    }
+
+    /// Take `quantity` units off the most recent decreases of an item at a location (used
+    /// when a decrease is undone elsewhere), dropping decreases that reach zero
+    pub fn remove_decrease(&self, inventory_item_id: &str, location_id: &str, quantity: i32) {
+        let mut actions = self.0.lock().unwrap();
+        let mut remaining = quantity;
+
+        while remaining > 0 {
+            let Some(index) = actions.iter().rposition(|action| {
+                matches!(action, UndoableAction::Decrease { item, location_id: loc, .. }
+                    if item.inventory_item_id == inventory_item_id && loc == location_id)
+            }) else {
+                break;
+            };
+
+            if let UndoableAction::Decrease {
+                quantity: decreased,
+                ..
+            } = &mut actions[index]
+            {
+                let taken = remaining.min(*decreased);
+                *decreased -= taken;
+                remaining -= taken;
+                if *decreased == 0 {
+                    actions.remove(index);
+                }
+            }
+        }
+    }
+}
+
+/// Pop and reverse up to `n` actions in LIFO order, stopping at the first failure.
+/// `apply` fails with the number of updates it applied before the error; whatever is
+/// left of the failed action is pushed back so only that part is retried.
+pub async fn undo_last_n_with<F, Fut>(stack: &UndoStack, n: usize, mut apply: F) -> Vec<UndoResult>
+where
+    F: FnMut(UndoReversal) -> Fut,
+    Fut: std::future::Future<Output = Result<(), (usize, String)>>,
+{
+    let mut results = Vec::new();
+
+    for _ in 0..n {
+        let Some(action) = stack.pop() else {
+            break;
+        };
+
+        let description = action.describe();
+        let reversal = action.reversal(&chrono::Utc::now().to_rfc3339());
+        let steps = reversal.updates.len();
+
+        match apply(reversal).await {
+            Ok(()) => {
+                info!("↩️ Undone: {}", description);
+                results.push(UndoResult {
+                    action: description,
+                    success: true,
+                    error: None,
+                });
+            }
+            Err((applied, e)) => {
+                error!("❌ Failed to undo {}: {}", description, e);
+                let error = if applied > 0 {
+                    format!(
+                        "{} ({} of {} adjustments applied; the rest stays on the undo stack)",
+                        e, applied, steps
+                    )
+                } else {
+                    e
+                };
+                stack.push(action.after_partial_reversal(applied));
+                results.push(UndoResult {
+                    action: description,
+                    success: false,
+                    error: Some(error),
+                });
+                break;
+            }
+        }
+    }
+
+    results
+}
+
+/// Undo the most recent `n` inventory actions (decreases and transfers)
+#[tauri::command]
+pub async fn undo_last_n(
+    n: usize,
+    app: tauri::AppHandle,
+    client: State<'_, reqwest::Client>,
+    config: State<'_, AppConfig>,
+    undo_stack: State<'_, UndoStack>,
+) -> Result<Vec<UndoResult>, AppError> {
+    info!("🔄 Undoing last {} inventory actions", n);
+
+    let results = undo_last_n_with(&undo_stack, n, |reversal| {
+        let client = client.clone();
+        let config = config.clone();
+        let app = app.clone();
+        async move {
+            // One update at a time, so a failure knows which adjustments already went through
+            let mut applied = 0;
+            let mut failure = None;
+            for update in reversal.updates {
+                if let Err(e) = adjust_inventory(client.clone(), config.clone(), vec![update]).await
+                {
+                    failure = Some(e.to_string());
+                    break;
+                }
+                applied += 1;
+            }
+
+            let logs: Vec<LogEntry> = reversal.logs.into_iter().take(applied).collect();
+            if !logs.is_empty() {
+                let firebase_client = FirebaseClient::new(&client, config.inner().clone());
+                let log_results =
+                    crate::firebase::write_logs_audited(&app, &firebase_client, logs).await;
+                if log_results.iter().any(|result| result.is_ok()) {
+                    crate::status::mark_last_run(&app, crate::status::RunKind::LogWrite);
+                }
+                for result in log_results {
+                    if let Err(e) = result {
+                        // The inventory is already changed, so logging issues only warn
+                        warn!("⚠️ Warning: Failed to log undo: {}", e);
+                    }
+                }
+            }
+
+            match failure {
+                Some(e) => Err((applied, e)),
+                None => Ok(()),
+            }
+        }
+    })
+    .await;
+
+    info!("✅ Undo completed: {} actions processed", results.len());
+    Ok(results)
+}
+
+// ============================================================================
+// UNDO DAY PREVIEW
+// ============================================================================
+
+/// Compensating adjustment needed to cancel out a variant's logged changes for a day
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct DayUndoImpact {
+    pub inventory_item_id: String,
+    pub variant: String,
+    pub net_to_reverse: i32,
+}
+
+/// Sum the logged changes per inventory item and negate them; items whose changes
+/// already cancel out are left out. Sorted by inventory item id
+pub fn compute_day_undo_impact(logs: &[LogEntry]) -> Vec<DayUndoImpact> {
+    let mut by_item: HashMap<&str, (String, i32)> = HashMap::new();
+    for log in logs {
+        let entry = by_item
+            .entry(log.data.inventory_item_id.as_str())
+            .or_insert_with(|| (format!("{} - {}", log.data.nome, log.data.variant), 0));
+        entry.1 += log.data.rettifica;
+    }
+
+    let mut impacts: Vec<DayUndoImpact> = by_item
+        .into_iter()
+        .filter(|(_, (_, net))| *net != 0)
+        .map(|(inventory_item_id, (variant, net))| DayUndoImpact {
+            inventory_item_id: inventory_item_id.to_string(),
+            variant,
+            net_to_reverse: -net,
+        })
+        .collect();
+    impacts.sort_by(|a, b| a.inventory_item_id.cmp(&b.inventory_item_id));
+    impacts
+}
+
+/// Show the adjustments that would roll back every logged change at a location on
+/// `date` (YYYY-MM-DD), without applying anything
+#[tauri::command]
+pub async fn preview_undo_day(
+    location: String,
+    date: String,
+    client: State<'_, reqwest::Client>,
+    config: State<'_, AppConfig>,
+    shop_info: State<'_, crate::status::ShopInfoCache>,
+) -> Result<Vec<DayUndoImpact>, AppError> {
+    let (logs, _) =
+        crate::analytics::load_day_logs(&client, &config, &shop_info, &location, &date).await?;
+    let impacts = compute_day_undo_impact(&logs);
+
+    info!(
+        "↩️ Undo preview for {} on {}: {} logs, {} variants to reverse",
+        location,
+        date,
+        logs.len(),
+        impacts.len()
+    );
+    Ok(impacts)
+}
+
+// ============================================================================
+// COUNT IMPORT
+// ============================================================================
+
+/// A parsed `SKU,quantity` row from a cycle-count CSV
+#[derive(Debug, Clone, PartialEq)]
+pub struct CountRow {
+    pub line: usize,
+    pub sku: String,
+    pub quantity: i32,
+}
+
+/// A count that was applied (or would be, in a dry run)
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CountApplied {
+    pub line: usize,
+    pub sku: String,
+    pub inventory_item_id: String,
+    pub product_title: String,
+    pub variant_title: String,
+    pub previous_quantity: i32,
+    pub counted_quantity: i32,
+    pub delta: i32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum CountErrorKind {
+    Malformed,
+    Unresolved,
+    Failed,
+}
+
+/// A row that could not be applied, with the reason
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CountError {
+    pub line: usize,
+    pub sku: Option<String>,
+    pub kind: CountErrorKind,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ImportResult {
+    pub dry_run: bool,
+    pub applied: Vec<CountApplied>,
+    pub errors: Vec<CountError>,
+}
+
+/// Parse a `SKU,quantity` CSV (`;` also accepted as separator, optional header row).
+/// Malformed and repeated rows are returned as errors instead of aborting the import.
+pub fn parse_counts_csv(csv: &str) -> (Vec<CountRow>, Vec<CountError>) {
+    let mut rows: Vec<CountRow> = Vec::new();
+    let mut errors = Vec::new();
+
+    for (index, raw_line) in csv.lines().enumerate() {
+        let line = index + 1;
+        let trimmed = raw_line.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+
+        let fields: Vec<&str> = trimmed
+            .split([',', ';'])
+            .map(|field| field.trim().trim_matches('"'))
+            .collect();
+
+        if fields.len() != 2 || fields[0].is_empty() {
+            errors.push(CountError {
+                line,
+                sku: None,
+                kind: CountErrorKind::Malformed,
+                message: format!(
+                    "Riga non valida: attesi SKU e quantità, trovato '{}'",
+                    trimmed
+                ),
+            });
+            continue;
+        }
+
+        let sku = fields[0].to_string();
+        let quantity = match fields[1].parse::<i32>() {
+            Ok(quantity) if quantity >= 0 => quantity,
+            _ if line == 1 && sku.eq_ignore_ascii_case("sku") => continue, // Header row
+            _ => {
+                errors.push(CountError {
+                    line,
+                    sku: Some(sku),
+                    kind: CountErrorKind::Malformed,
+                    message: format!("Quantità non valida: '{}'", fields[1]),
+                });
+                continue;
+            }
+        };
+
+        if rows.iter().any(|row| row.sku.eq_ignore_ascii_case(&sku)) {
+            errors.push(CountError {
+                line,
+                sku: Some(sku),
+                kind: CountErrorKind::Malformed,
+                message: "SKU ripetuto nel file".to_string(),
+            });
+            continue;
+        }
+
+        rows.push(CountRow {
+            line,
+            sku,
+            quantity,
+        });
+    }
+
+    (rows, errors)
+}
+
+/// Adjustment needed to bring `current` to the `counted` quantity
+pub fn count_delta(current: i32, counted: i32) -> i32 {
+    counted - current
+}
+
+/// Apply absolute counts from a `SKU,quantity` CSV at a location, logging each change
+#[tauri::command]
+pub async fn import_counts_csv(
+    csv: String,
+    location: String,
+    dry_run: bool,
+    app: tauri::AppHandle,
+    client: State<'_, reqwest::Client>,
+    config: State<'_, AppConfig>,
+    locations: State<'_, LocationIndex>,
+    sku_cache: State<'_, crate::products::SkuCache>,
+) -> Result<ImportResult, AppError> {
+    let location_id = locations.resolve(&location)?;
+
+    let (rows, mut errors) = parse_counts_csv(&csv);
+    info!(
+        "📥 Importing {} counts for {} ({} malformed rows){}",
+        rows.len(),
+        location,
+        errors.len(),
+        if dry_run { " [DRY RUN]" } else { "" }
+    );
+
+    // Resolve every SKU to its product and inventory item
+    let mut resolved = Vec::new();
+    for row in rows {
+        match crate::products::find_product_by_exact_sku_graphql(
+            client.clone(),
+            config.clone(),
+            sku_cache.clone(),
+            row.sku.clone(),
+        )
+        .await
+        {
+            Ok(Some((product, inventory_item_id))) => {
+                resolved.push((row, product, inventory_item_id))
+            }
+            Ok(None) => errors.push(CountError {
+                line: row.line,
+                sku: Some(row.sku),
+                kind: CountErrorKind::Unresolved,
+                message: "SKU non trovato".to_string(),
+            }),
+            Err(e) => errors.push(CountError {
+                line: row.line,
+                sku: Some(row.sku),
+                kind: CountErrorKind::Failed,
+                message: e.to_string(),
+            }),
+        }
+    }
+
+    let inventory_item_ids: Vec<String> = resolved.iter().map(|(_, _, id)| id.clone()).collect();
+    let mut inventory_levels = HashMap::new();
+    for chunk in config.inventory_level_chunks(&inventory_item_ids) {
+        inventory_levels
+            .extend(get_inventory_levels(client.clone(), config.clone(), chunk.to_vec()).await?);
+    }
+
+    let timestamp = chrono::Utc::now().to_rfc3339();
+    let mut applied = Vec::new();
+    let mut log_entries = Vec::new();
+
+    for (row, product, inventory_item_id) in resolved {
+        let previous_quantity = inventory_levels
+            .get(&inventory_item_id)
+            .and_then(|levels| levels.get(&location_id))
+            .copied()
+            .unwrap_or(0);
+        let delta = count_delta(previous_quantity, row.quantity);
+        let variant = product
+            .variants
+            .iter()
+            .find(|v| v.inventory_item_id == inventory_item_id);
+        let variant_title = variant.map(|v| v.title.clone()).unwrap_or_default();
+        let price = variant
+            .map(|v| v.price.clone())
+            .unwrap_or_else(|| product.price.clone());
+
+        if !dry_run && delta != 0 {
+            if let Err(e) = set_inventory_level(
+                client.clone(),
+                config.clone(),
+                inventory_item_id.clone(),
+                location_id.clone(),
+                row.quantity,
+            )
+            .await
+            {
+                errors.push(CountError {
+                    line: row.line,
+                    sku: Some(row.sku),
+                    kind: CountErrorKind::Failed,
+                    message: e.to_string(),
+                });
+                continue;
+            }
+
+            log_entries.push(LogEntry {
+                // A count correction, not a sale, so analytics leaves it out
+                request_type: "Riconciliazione".to_string(),
+                data: create_inventory_log_data(
+                    product.id.clone(),
+                    variant_title.clone(),
+                    location.clone(),
+                    inventory_item_id.clone(),
+                    product.title.clone(),
+                    price,
+                    delta,
+                    product.images.clone(),
+                ),
+                timestamp: timestamp.clone(),
+            });
+        }
+
+        applied.push(CountApplied {
+            line: row.line,
+            sku: row.sku,
+            inventory_item_id,
+            product_title: product.title,
+            variant_title,
+            previous_quantity,
+            counted_quantity: row.quantity,
+            delta,
+        });
+    }
+
+    if !log_entries.is_empty() {
+        let firebase_client = FirebaseClient::new(&client, config.inner().clone());
+        for result in crate::firebase::write_logs_audited(&app, &firebase_client, log_entries).await
+        {
+            if let Err(e) = result {
+                warn!("⚠️ Warning: Failed to log count import: {}", e);
+            }
+        }
+    }
+
+    errors.sort_by_key(|error| error.line);
+    info!(
+        "✅ Count import completed: {} applied, {} errors",
+        applied.len(),
+        errors.len()
+    );
+    Ok(ImportResult {
+        dry_run,
+        applied,
+        errors,
+    })
+}
+
+// ============================================================================
+// END OF DAY CLOSE
+// ============================================================================
+
+/// Expected (Shopify) vs physically counted quantity for one SKU
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct CloseOutRow {
+    pub sku: String,
+    pub expected: i32,
+    pub counted: i32,
+    pub variance: i32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CloseOutReport {
+    pub location: String,
+    pub date: String,
+    pub rows: Vec<CloseOutRow>,
+    pub unresolved: Vec<String>,
+    pub check_requests_created: usize,
+}
+
+/// Compare counts against expected quantities keyed by lowercase SKU.
+/// Variance is counted minus expected; SKUs without an expected quantity are unresolved.
+pub fn compute_close_out(
+    counts: &[(String, i32)],
+    expected_by_sku: &HashMap<String, i32>,
+) -> (Vec<CloseOutRow>, Vec<String>) {
+    let mut rows = Vec::new();
+    let mut unresolved = Vec::new();
+
+    for (sku, counted) in counts {
+        match expected_by_sku.get(&sku.trim().to_lowercase()) {
+            Some(&expected) => rows.push(CloseOutRow {
+                sku: sku.clone(),
+                expected,
+                counted: *counted,
+                variance: counted - expected,
+            }),
+            None => unresolved.push(sku.clone()),
+        }
+    }
+
+    (rows, unresolved)
+}
+
+/// End-of-day close: compare physical counts with Shopify quantities at a location,
+/// optionally opening a check request for every SKU with a variance
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn close_out_day(
+    location: String,
+    date: String,
+    counts: Vec<(String, i32)>,
+    create_check_requests: Option<bool>,
+    client: State<'_, reqwest::Client>,
+    config: State<'_, AppConfig>,
+    locations: State<'_, LocationIndex>,
+    sku_cache: State<'_, crate::products::SkuCache>,
+) -> Result<CloseOutReport, AppError> {
+    info!(
+        "🧾 End-of-day close for {} on {}: {} counts",
+        location,
+        date,
+        counts.len()
+    );
+    let location_id = locations.resolve(&location)?;
+
+    let skus: Vec<String> = counts.iter().map(|(sku, _)| sku.clone()).collect();
+    let resolved =
+        crate::products::resolve_skus(client.clone(), config.clone(), sku_cache, skus).await?;
+
+    let inventory_item_ids: Vec<String> = resolved
+        .iter()
+        .map(|r| r.inventory_item_id.clone())
+        .collect();
+    let mut inventory_levels = HashMap::new();
+    for chunk in config.inventory_level_chunks(&inventory_item_ids) {
+        inventory_levels
+            .extend(get_inventory_levels(client.clone(), config.clone(), chunk.to_vec()).await?);
+    }
+
+    let expected_by_sku: HashMap<String, i32> = resolved
+        .iter()
+        .map(|r| {
+            let expected = inventory_levels
+                .get(&r.inventory_item_id)
+                .and_then(|levels| levels.get(&location_id))
+                .copied()
+                .unwrap_or(0);
+            (r.sku.to_lowercase(), expected)
+        })
+        .collect();
+
+    let (rows, unresolved) = compute_close_out(&counts, &expected_by_sku);
+
+    let mut check_requests_created = 0;
+    if create_check_requests.unwrap_or(false) {
+        let firebase_client = FirebaseClient::new(&client, config.inner().clone());
+        for row in rows.iter().filter(|row| row.variance != 0) {
+            let Some(item) = resolved
+                .iter()
+                .find(|r| r.sku.eq_ignore_ascii_case(row.sku.trim()))
+            else {
+                continue;
+            };
+
+            let check_request = crate::firebase::CheckRequest {
+                check_all: false,
+                checked: false,
+                checked_at: None,
+                checked_by: None,
+                location: vec![location.clone()],
+                notes: format!(
+                    "Chiusura del {}: attesi {}, contati {} (differenza {})",
+                    date, row.expected, row.counted, row.variance
+                ),
+                priority: "medium".to_string(),
+                product_id: item.product_id.parse().unwrap_or_default(),
+                product_name: item.product_title.clone(),
+                requested_by: "Chiusura giornaliera".to_string(),
+                status: "pending".to_string(),
+                timestamp: chrono::Utc::now().to_rfc3339(),
+                variant_id: item.variant_id.parse().ok(),
+                variant_name: Some(item.variant_title.clone()),
+                image_url: None,
+                closing_notes: None,
+            };
+
+            match firebase_client.create_check_request(check_request).await {
+                Ok(_) => check_requests_created += 1,
+                Err(e) => warn!(
+                    "⚠️ Warning: Failed to create check request for {}: {}",
+                    row.sku, e
+                ),
+            }
+        }
+    }
+
+    let variances = rows.iter().filter(|row| row.variance != 0).count();
+    info!(
+        "✅ Close completed: {} SKUs, {} with variance, {} unresolved",
+        rows.len(),
+        variances,
+        unresolved.len()
+    );
+    Ok(CloseOutReport {
+        location,
+        date,
+        rows,
+        unresolved,
+        check_requests_created,
+    })
+}
+
+// ============================================================================
+// ORPHANED INVENTORY
+// ============================================================================
+
+/// Inventory item ids that have levels but no longer belong to any of the product's variants
+/// (e.g. a deleted variant whose inventory item lingers), sorted
+pub fn find_orphaned_inventory_items(
+    levels: &HashMap<String, HashMap<String, i32>>,
+    variant_inventory_item_ids: &[String],
+) -> Vec<String> {
+    let mut orphaned: Vec<String> = levels
+        .keys()
+        .filter(|id| !variant_inventory_item_ids.contains(id))
+        .cloned()
+        .collect();
+    orphaned.sort();
+    orphaned
+}
+
+/// Report inventory items with levels that are not among the product's current variants.
+/// Shopify only returns levels for the ids it is asked about, so `extra_inventory_item_ids`
+/// (e.g. ids seen in the product's logs) are checked alongside the variants' own ids.
+#[tauri::command]
+pub async fn find_orphaned_inventory(
+    product_id: String,
+    extra_inventory_item_ids: Option<Vec<String>>,
+    client: State<'_, reqwest::Client>,
+    config: State<'_, AppConfig>,
+) -> Result<Vec<String>, AppError> {
+    info!(
+        "🔍 Looking for orphaned inventory on product {}",
+        product_id
+    );
+
+    let product =
+        crate::products::get_product_by_id(client.clone(), config.clone(), product_id.clone())
+            .await?;
+    let variant_ids: Vec<String> = product
+        .variants
+        .iter()
+        .map(|variant| variant.inventory_item_id.clone())
+        .collect();
+
+    let mut candidate_ids = variant_ids.clone();
+    for id in extra_inventory_item_ids.unwrap_or_default() {
+        let id = id.trim().to_string();
+        if !id.is_empty() && !candidate_ids.contains(&id) {
+            candidate_ids.push(id);
+        }
+    }
+
+    let levels = get_inventory_levels(client, config, candidate_ids).await?;
+    let orphaned = find_orphaned_inventory_items(&levels, &variant_ids);
+
+    info!(
+        "✅ Found {} orphaned inventory items on product {}",
+        orphaned.len(),
+        product_id
+    );
+    Ok(orphaned)
+}
+
+// ============================================================================
+// INVENTORY SNAPSHOTS
+// ============================================================================
+
+const SNAPSHOTS_DIR: &str = "snapshots";
+
+/// One variant's quantity in a stored snapshot
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SnapshotItem {
+    pub inventory_item_id: String,
+    pub variant: String,
+    pub quantity: i32,
+}
+
+/// Quantities at a location at the end of a day, stored under `snapshots/` in app data
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct InventorySnapshot {
+    pub location: String,
+    pub date: String, // YYYY-MM-DD
+    /// When the quantities were read (RFC 3339); older snapshots don't have it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub captured_at: Option<String>,
+    pub items: Vec<SnapshotItem>,
+}
+
+/// When `snapshot` was taken: its capture time, or the end of its day (UTC) for
+/// snapshots stored without one
+pub fn snapshot_time(
+    snapshot: &InventorySnapshot,
+) -> Result<chrono::DateTime<chrono::Utc>, String> {
+    if let Some(captured_at) = &snapshot.captured_at {
+        return chrono::DateTime::parse_from_rfc3339(captured_at)
+            .map(|at| at.with_timezone(&chrono::Utc))
+            .map_err(|e| format!("Invalid snapshot capture time '{}': {}", captured_at, e));
+    }
+    chrono::NaiveDate::parse_from_str(&snapshot.date, "%Y-%m-%d")
+        .ok()
+        .and_then(|day| day.and_hms_opt(23, 59, 59))
+        .map(|end_of_day| end_of_day.and_utc())
+        .ok_or_else(|| format!("Invalid snapshot date: {}", snapshot.date))
+}
+
+/// Quantity of `inventory_item_id` in `snapshot`, None when the variant isn't in it
+pub fn snapshot_quantity(snapshot: &InventorySnapshot, inventory_item_id: &str) -> Option<i32> {
+    snapshot
+        .items
+        .iter()
+        .find(|item| item.inventory_item_id == inventory_item_id)
+        .map(|item| item.quantity)
+}
+
+/// Per-variant change between two snapshots; a side is None when the variant is
+/// missing from that snapshot (and counts as 0 in `change`)
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SnapshotDiff {
+    pub inventory_item_id: String,
+    pub variant: String,
+    pub qty_a: Option<i32>,
+    pub qty_b: Option<i32>,
+    pub change: i32,
+}
+
+/// File of the snapshot of `location` on `date`. Both become part of the file name, so
+/// `location` must be a configured store and `date` a YYYY-MM-DD date.
+fn snapshot_path(
+    dir: &std::path::Path,
+    config: &AppConfig,
+    location: &str,
+    date: &str,
+) -> Result<std::path::PathBuf, String> {
+    let name = config
+        .named_locations()
+        .into_iter()
+        .map(|(name, _)| name)
+        .find(|name| name.eq_ignore_ascii_case(location.trim()))
+        .ok_or_else(|| format!("Unknown location: {}", location))?;
+    if date.len() != 10 || chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d").is_err() {
+        return Err(format!(
+            "Invalid snapshot date: {} (expected YYYY-MM-DD)",
+            date
+        ));
+    }
+
+    Ok(dir
+        .join(SNAPSHOTS_DIR)
+        .join(format!("{}-{}.json", name.to_lowercase(), date)))
+}
+
+/// Read the snapshot of `location` on `date` from `dir`
+pub fn read_snapshot(
+    dir: &std::path::Path,
+    config: &AppConfig,
+    location: &str,
+    date: &str,
+) -> Result<InventorySnapshot, String> {
+    let path = snapshot_path(dir, config, location, date)?;
+    let content = std::fs::read_to_string(&path)
+        .map_err(|_| format!("No snapshot for {} on {}", location, date))?;
+    serde_json::from_str(&content).map_err(|e| format!("Failed to parse snapshot: {}", e))
+}
+
+/// Store `snapshot` in `dir`, replacing any snapshot for the same location and date
+pub fn write_snapshot(
+    dir: &std::path::Path,
+    config: &AppConfig,
+    snapshot: &InventorySnapshot,
+) -> Result<(), String> {
+    let path = snapshot_path(dir, config, &snapshot.location, &snapshot.date)?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create snapshots directory: {}", e))?;
+    }
+    let json_content = serde_json::to_string_pretty(snapshot)
+        .map_err(|e| format!("Failed to serialize snapshot: {}", e))?;
+    std::fs::write(path, json_content).map_err(|e| format!("Failed to write snapshot: {}", e))
+}
+
+/// Changed variants between snapshot `a` and snapshot `b`, including variants that only
+/// appear in one of them, sorted by variant then inventory item id
+pub fn diff_snapshots(a: &InventorySnapshot, b: &InventorySnapshot) -> Vec<SnapshotDiff> {
+    let mut diffs: HashMap<String, SnapshotDiff> = HashMap::new();
+
+    for item in &a.items {
+        diffs.insert(
+            item.inventory_item_id.clone(),
+            SnapshotDiff {
+                inventory_item_id: item.inventory_item_id.clone(),
+                variant: item.variant.clone(),
+                qty_a: Some(item.quantity),
+                qty_b: None,
+                change: 0,
+            },
+        );
+    }
+    for item in &b.items {
+        let diff = diffs
+            .entry(item.inventory_item_id.clone())
+            .or_insert_with(|| SnapshotDiff {
+                inventory_item_id: item.inventory_item_id.clone(),
+                variant: item.variant.clone(),
+                qty_a: None,
+                qty_b: None,
+                change: 0,
+            });
+        diff.qty_b = Some(item.quantity);
+    }
+
+    let mut diffs: Vec<SnapshotDiff> = diffs
+        .into_values()
+        .map(|mut diff| {
+            diff.change = diff.qty_b.unwrap_or(0) - diff.qty_a.unwrap_or(0);
+            diff
+        })
+        .filter(|diff| diff.change != 0 || diff.qty_a.is_none() || diff.qty_b.is_none())
+        .collect();
+    diffs.sort_by(|x, y| {
+        x.variant
+            .cmp(&y.variant)
+            .then_with(|| x.inventory_item_id.cmp(&y.inventory_item_id))
+    });
+    diffs
+}
+
+/// Per-variant changes at `location` between the snapshots of `date_a` and `date_b`,
+/// to cross-check against the activity logged in between
+#[tauri::command]
+pub async fn compare_snapshots(
+    location: String,
+    date_a: String,
+    date_b: String,
+    app: tauri::AppHandle,
+    config: State<'_, AppConfig>,
+) -> Result<Vec<SnapshotDiff>, AppError> {
+    let dir = crate::status::app_data_dir(&app)?;
+    let a = read_snapshot(&dir, &config, &location, &date_a)?;
+    let b = read_snapshot(&dir, &config, &location, &date_b)?;
+
+    let diffs = diff_snapshots(&a, &b);
+    info!(
+        "📸 {} variants changed at {} between {} and {}",
+        diffs.len(),
+        location,
+        date_a,
+        date_b
+    );
+    Ok(diffs)
+}
+
+/// Snapshot of the quantities at `location_id` of every variant of `products`, labelled
+/// "product - variant" (levels keyed by inventory_item_id → location_id → available).
+/// Variants without an inventory item are left out; untracked ones count as 0.
+pub fn build_snapshot(
+    location: &str,
+    date: &str,
+    captured_at: &str,
+    products: &[crate::stock::ShopifyProduct],
+    inventory_levels: &HashMap<String, HashMap<String, i32>>,
+    location_id: &str,
+) -> InventorySnapshot {
+    let items = products
+        .iter()
+        .flat_map(|product| {
+            product.variants.iter().filter_map(move |variant| {
+                let inventory_item_id = variant.inventory_item_id?.to_string();
+                let quantity = inventory_levels
+                    .get(&inventory_item_id)
+                    .and_then(|levels| levels.get(location_id))
+                    .copied()
+                    .unwrap_or(0);
+                Some(SnapshotItem {
+                    inventory_item_id,
+                    variant: format!("{} - {}", product.title, variant.title),
+                    quantity,
+                })
+            })
+        })
+        .collect();
+
+    InventorySnapshot {
+        location: location.to_string(),
+        date: date.to_string(),
+        captured_at: Some(captured_at.to_string()),
+        items,
+    }
+}
+
+/// Record the current quantity of every active variant at `location` as the snapshot of
+/// `date` (today in the shop's timezone when not given), for `compare_snapshots` and the
+/// modification history
+#[tauri::command]
+pub async fn capture_snapshot(
+    location: String,
+    date: Option<String>,
+    app: tauri::AppHandle,
+    client: State<'_, reqwest::Client>,
+    config: State<'_, AppConfig>,
+    locations: State<'_, LocationIndex>,
+    shop_info: State<'_, crate::status::ShopInfoCache>,
+) -> Result<InventorySnapshot, AppError> {
+    let location_id = locations.resolve(&location)?;
+    let date = date.unwrap_or_else(|| {
+        crate::utils::today_in_timezone(shop_info.get().map(|info| info.timezone).as_deref())
+    });
+
+    let products = crate::stock::fetch_all_active_products(&client, &config).await?;
+    let inventory_item_ids: Vec<String> = products
+        .iter()
+        .flat_map(|p| p.variants.iter())
+        .filter_map(|v| v.inventory_item_id.map(|id| id.to_string()))
+        .collect();
+    let inventory_levels =
+        get_inventory_levels(client.clone(), config.clone(), inventory_item_ids).await?;
+
+    let snapshot = build_snapshot(
+        &location,
+        &date,
+        &chrono::Utc::now().to_rfc3339(),
+        &products,
+        &inventory_levels,
+        &location_id,
+    );
+    write_snapshot(&crate::status::app_data_dir(&app)?, &config, &snapshot)?;
+    info!(
+        "📸 Captured snapshot of {} variants at {} for {}",
+        snapshot.items.len(),
+        location,
+        date
+    );
+    Ok(snapshot)
+}
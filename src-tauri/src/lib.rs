@@ -1,6 +1,7 @@
 // Main library file for Shopify Inventory App
 // Exposes public modules for testing and Tauri commands
 
+pub mod analytics;
 pub mod firebase;
 pub mod inventory;
 pub mod location;
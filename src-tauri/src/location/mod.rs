@@ -1,7 +1,10 @@
-use crate::utils::{AppConfig, StatusResponse};
+use crate::utils::{send_with_retry, AppConfig, AppError, StatusResponse};
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
 use tauri::Manager;
 
 // ============================================================================
@@ -14,8 +17,11 @@ pub struct LocationInfo {
     pub id: String,
 }
 
+/// All configured stores, with the current one first. `primary_location` and
+/// `secondary_location` are kept for screens that still only know about two stores.
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct LocationConfig {
+    pub locations: Vec<LocationInfo>,
     pub primary_location: LocationInfo,
     pub secondary_location: LocationInfo,
 }
@@ -26,10 +32,122 @@ struct LocationSetting {
 }
 
 // ============================================================================
-// PREDEFINED LOCATIONS (matching old implementation)
+// LOCATION INDEX
 // ============================================================================
 
-pub const LOCATIONS: &[(&str, &str)] = &[("Treviso", "3708157983"), ("Mogliano", "31985336425")];
+/// Store name → Shopify location id, built once from the configuration and kept in app
+/// state. Names are matched case-insensitively.
+#[derive(Debug, Clone, Default)]
+pub struct LocationIndex(HashMap<String, String>);
+
+impl LocationIndex {
+    pub fn from_config(config: &AppConfig) -> Self {
+        let locations = config.named_locations();
+        Self::from_pairs(
+            locations
+                .iter()
+                .map(|(name, id)| (name.as_str(), id.as_str())),
+        )
+    }
+
+    pub fn from_pairs<'a>(pairs: impl IntoIterator<Item = (&'a str, &'a str)>) -> Self {
+        Self(
+            pairs
+                .into_iter()
+                .map(|(name, id)| (name.trim().to_lowercase(), id.to_string()))
+                .collect(),
+        )
+    }
+
+    pub fn location_id_for(&self, name: &str) -> Option<String> {
+        self.0.get(&name.trim().to_lowercase()).cloned()
+    }
+
+    /// Like `location_id_for`, but an unknown name is an error
+    pub fn resolve(&self, name: &str) -> Result<String, AppError> {
+        self.location_id_for(name)
+            .ok_or_else(|| AppError::NotFound(format!("Location '{}' not found", name)))
+    }
+}
+
+// ============================================================================
+// SHOPIFY LOCATIONS
+// ============================================================================
+
+/// Locations loaded from Shopify, cached in app state after the first successful fetch
+#[derive(Default)]
+pub struct ShopifyLocationsCache(Mutex<Option<Vec<LocationInfo>>>);
+
+impl ShopifyLocationsCache {
+    pub fn get(&self) -> Option<Vec<LocationInfo>> {
+        self.0.lock().ok().and_then(|cached| cached.clone())
+    }
+
+    pub fn set(&self, locations: Vec<LocationInfo>) {
+        if let Ok(mut cached) = self.0.lock() {
+            *cached = Some(locations);
+        }
+    }
+
+    /// Forget the cached list so the next request loads it from Shopify again
+    pub fn clear(&self) {
+        if let Ok(mut cached) = self.0.lock() {
+            *cached = None;
+        }
+    }
+}
+
+/// Active locations from a `locations.json` response
+pub fn parse_shopify_locations(data: &Value) -> Vec<LocationInfo> {
+    data["locations"]
+        .as_array()
+        .map(|locations| {
+            locations
+                .iter()
+                .filter(|location| location["active"].as_bool().unwrap_or(true))
+                .filter_map(|location| {
+                    Some(LocationInfo {
+                        name: location["name"].as_str()?.to_string(),
+                        id: location["id"].as_u64()?.to_string(),
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Fetch the shop's active locations from Shopify
+pub async fn fetch_shopify_locations(
+    client: &reqwest::Client,
+    config: &AppConfig,
+) -> Result<Vec<LocationInfo>, String> {
+    let response = send_with_retry(
+        client
+            .get(config.get_api_url("locations.json"))
+            .headers(config.get_headers()),
+        &config,
+    )
+    .await?;
+
+    if !response.status().is_success() {
+        return Err(format!("Failed to fetch locations: {}", response.status()));
+    }
+
+    let data: Value = response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse JSON: {}", e))?;
+    Ok(parse_shopify_locations(&data))
+}
+
+/// The stores from the configuration, used when Shopify isn't asked or can't be reached
+fn predefined_locations(config: &AppConfig) -> Vec<LocationInfo> {
+    config
+        .named_locations()
+        .into_iter()
+        .map(|(name, id)| LocationInfo { name, id })
+        .collect()
+}
 
 // ============================================================================
 // LOCATION MANAGEMENT FUNCTIONS
@@ -45,7 +163,100 @@ fn get_app_data_dir(app: &tauri::AppHandle) -> Result<PathBuf, String> {
 /// Get the location file path
 fn get_location_file_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
     let app_data_dir = get_app_data_dir(app)?;
-    Ok(app_data_dir.join("locationCappelletto.json"))
+    Ok(app_data_dir.join(LOCATION_FILE))
+}
+
+const LOCATION_FILE: &str = "locationCappelletto.json";
+
+/// Event emitted with the new location name after it has been changed
+pub const LOCATION_CHANGED_EVENT: &str = "location-changed";
+
+/// The location stored in `dir`, or None if it was never set
+pub fn read_location_setting(dir: &Path) -> Result<Option<String>, String> {
+    let path = dir.join(LOCATION_FILE);
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let content =
+        fs::read_to_string(&path).map_err(|e| format!("Failed to read location file: {}", e))?;
+    let location_setting: LocationSetting = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse location file: {}", e))?;
+    Ok(Some(location_setting.location))
+}
+
+/// Store `location` in `dir` through a temporary file and rename, so a crash never leaves
+/// a half-written setting. With `expected_current`, the write only happens if the stored
+/// location still matches it (another window may have changed it in the meantime).
+pub fn write_location_setting(
+    dir: &Path,
+    location: &str,
+    expected_current: Option<&str>,
+) -> Result<(), AppError> {
+    if let Some(expected) = expected_current {
+        let current = read_location_setting(dir)?;
+        if current.as_deref() != Some(expected) {
+            return Err(AppError::Conflict(format!(
+                "Location was changed to {} in the meantime, expected {}",
+                current.as_deref().unwrap_or("(not set)"),
+                expected
+            )));
+        }
+    }
+
+    fs::create_dir_all(dir).map_err(|e| format!("Failed to create app data directory: {}", e))?;
+
+    let location_setting = LocationSetting {
+        location: location.to_string(),
+    };
+    let json_content = serde_json::to_string_pretty(&location_setting)
+        .map_err(|e| format!("Failed to serialize location setting: {}", e))?;
+
+    let tmp_file = dir.join(format!("{}.tmp", LOCATION_FILE));
+    fs::write(&tmp_file, json_content)
+        .map_err(|e| format!("Failed to write location file: {}", e))?;
+    fs::rename(&tmp_file, dir.join(LOCATION_FILE))
+        .map_err(|e| format!("Failed to replace location file: {}", e))?;
+    Ok(())
+}
+
+/// Every configured store as seen from `current_location`, which comes first. The
+/// secondary location is the configured secondary store when the current one is the
+/// primary store, and the primary store otherwise, as it was before more than two stores
+/// could be configured. Unknown names are a NotFound error.
+pub fn resolve_location_config(
+    current_location: &str,
+    config: &AppConfig,
+) -> Result<LocationConfig, AppError> {
+    let configured: Vec<LocationInfo> = config
+        .named_locations()
+        .into_iter()
+        .map(|(name, id)| LocationInfo { name, id })
+        .collect();
+
+    let current_index = configured
+        .iter()
+        .position(|location| location.name.eq_ignore_ascii_case(current_location.trim()))
+        .ok_or_else(|| AppError::NotFound(format!("Location '{}' not found", current_location)))?;
+    let secondary_index = if current_index == 0 { 1 } else { 0 };
+    let secondary = configured[secondary_index].clone();
+
+    let mut locations = configured;
+    let current = locations.remove(current_index);
+    locations.insert(0, current);
+
+    Ok(LocationConfig {
+        primary_location: locations[0].clone(),
+        secondary_location: secondary,
+        locations,
+    })
+}
+
+/// The location chosen on this machine, or the configured default when none was chosen
+pub async fn current_location_name(app: tauri::AppHandle, config: &AppConfig) -> String {
+    get_app_location(app)
+        .await
+        .unwrap_or_else(|_| config.default_location_name())
 }
 
 // ============================================================================
@@ -53,7 +264,7 @@ fn get_location_file_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
 // ============================================================================
 
 #[tauri::command]
-pub async fn get_app_location(app: tauri::AppHandle) -> Result<String, String> {
+pub async fn get_app_location(app: tauri::AppHandle) -> Result<String, AppError> {
     let location_file = get_location_file_path(&app)?;
 
     if location_file.exists() {
@@ -65,7 +276,7 @@ pub async fn get_app_location(app: tauri::AppHandle) -> Result<String, String> {
 
         Ok(location_setting.location)
     } else {
-        Err("Location not set".to_string())
+        Err(AppError::Config("Location not set".to_string()))
     }
 }
 
@@ -73,7 +284,7 @@ pub async fn get_app_location(app: tauri::AppHandle) -> Result<String, String> {
 pub async fn set_app_location(
     location: String,
     app: tauri::AppHandle,
-) -> Result<StatusResponse, String> {
+) -> Result<StatusResponse, AppError> {
     let app_data_dir = get_app_data_dir(&app)?;
 
     // Create app data directory if it doesn't exist
@@ -95,64 +306,101 @@ pub async fn set_app_location(
     })
 }
 
+/// Validate and store `location`, notify open windows and return the resolved
+/// primary/secondary config. `expected_current` guards against overwriting a change
+/// made elsewhere since the caller last read the location. The cached Shopify location
+/// list is dropped so it is reloaded for the new store; the `LocationIndex` only maps
+/// configured names to ids and does not depend on the current location.
 #[tauri::command]
-pub async fn get_available_locations() -> Result<Vec<LocationInfo>, String> {
-    Ok(LOCATIONS
-        .iter()
-        .map(|(name, id)| LocationInfo {
-            name: name.to_string(),
-            id: id.to_string(),
-        })
-        .collect())
+pub async fn set_app_location_resolved(
+    location: String,
+    expected_current: Option<String>,
+    app: tauri::AppHandle,
+    config: tauri::State<'_, AppConfig>,
+    locations: tauri::State<'_, LocationIndex>,
+    shopify_locations: tauri::State<'_, ShopifyLocationsCache>,
+) -> Result<LocationConfig, AppError> {
+    use tauri::Emitter;
+
+    // Store the canonical name, whatever case the caller used
+    let location = config
+        .named_locations()
+        .into_iter()
+        .map(|(name, _)| name)
+        .find(|name| name.eq_ignore_ascii_case(location.trim()))
+        .filter(|name| locations.location_id_for(name).is_some())
+        .ok_or_else(|| AppError::NotFound(format!("Location '{}' not found", location)))?;
+
+    write_location_setting(
+        &get_app_data_dir(&app)?,
+        &location,
+        expected_current.as_deref(),
+    )?;
+    println!("📍 Location set to {}", location);
+    shopify_locations.clear();
+
+    if let Err(e) = app.emit(LOCATION_CHANGED_EVENT, &location) {
+        println!("⚠️ Failed to emit {}: {}", LOCATION_CHANGED_EVENT, e);
+    }
+
+    resolve_location_config(&location, &config)
 }
 
+/// Available locations; with `from_shopify` they are loaded (once per session) from
+/// Shopify, falling back to the predefined list when offline
 #[tauri::command]
-pub async fn get_location_by_name(location_name: String) -> Result<LocationInfo, String> {
-    LOCATIONS
-        .iter()
-        .find(|(name, _)| *name == location_name)
-        .map(|(name, id)| LocationInfo {
-            name: name.to_string(),
-            id: id.to_string(),
-        })
-        .ok_or_else(|| format!("Location '{}' not found", location_name))
+pub async fn get_available_locations(
+    from_shopify: Option<bool>,
+    client: tauri::State<'_, reqwest::Client>,
+    config: tauri::State<'_, AppConfig>,
+    cache: tauri::State<'_, ShopifyLocationsCache>,
+) -> Result<Vec<LocationInfo>, AppError> {
+    if !from_shopify.unwrap_or(false) {
+        return Ok(predefined_locations(&config));
+    }
+    if let Some(locations) = cache.get() {
+        return Ok(locations);
+    }
+
+    match fetch_shopify_locations(&client, &config).await {
+        Ok(locations) if !locations.is_empty() => {
+            println!("📍 Loaded {} locations from Shopify", locations.len());
+            cache.set(locations.clone());
+            Ok(locations)
+        }
+        Ok(_) => {
+            println!("⚠️ Shopify returned no active locations, using predefined list");
+            Ok(predefined_locations(&config))
+        }
+        Err(e) => {
+            println!(
+                "⚠️ Could not load locations from Shopify ({}), using predefined list",
+                e
+            );
+            Ok(predefined_locations(&config))
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn get_location_by_name(
+    location_name: String,
+    locations: tauri::State<'_, LocationIndex>,
+) -> Result<LocationInfo, AppError> {
+    let id = locations.resolve(&location_name)?;
+    Ok(LocationInfo {
+        name: location_name,
+        id,
+    })
 }
 
 #[tauri::command]
 pub async fn get_current_location_config(
     app: tauri::AppHandle,
     config: tauri::State<'_, AppConfig>,
-) -> Result<LocationConfig, String> {
+) -> Result<LocationConfig, AppError> {
     // Get the currently set location
-    let current_location = get_app_location(app).await.unwrap_or("Treviso".to_string());
-
-    // Determine primary and secondary locations
-    let (primary, secondary) = if current_location == "Treviso" {
-        (
-            LocationInfo {
-                name: "Treviso".to_string(),
-                id: config.primary_location.clone(),
-            },
-            LocationInfo {
-                name: "Mogliano".to_string(),
-                id: config.secondary_location.clone(),
-            },
-        )
-    } else {
-        (
-            LocationInfo {
-                name: "Mogliano".to_string(),
-                id: config.secondary_location.clone(),
-            },
-            LocationInfo {
-                name: "Treviso".to_string(),
-                id: config.primary_location.clone(),
-            },
-        )
-    };
+    let current_location = current_location_name(app, &config).await;
 
-    Ok(LocationConfig {
-        primary_location: primary,
-        secondary_location: secondary,
-    })
+    resolve_location_config(&current_location, &config)
 }
@@ -5,13 +5,15 @@ use tauri::menu::{Menu, MenuBuilder, MenuItemBuilder, SubmenuBuilder};
 use tauri::{Emitter, Manager};
 
 // Import the command functions from our modules
+use inventario_cappellettoshop_lib::analytics;
 use inventario_cappellettoshop_lib::firebase;
 use inventario_cappellettoshop_lib::inventory;
 use inventario_cappellettoshop_lib::location;
 use inventario_cappellettoshop_lib::products;
 use inventario_cappellettoshop_lib::status;
 use inventario_cappellettoshop_lib::stock;
-use inventario_cappellettoshop_lib::utils::AppConfig;
+use inventario_cappellettoshop_lib::utils::{build_http_client, AppConfig};
+use tracing::{error, warn};
 
 fn create_menu(app: &tauri::AppHandle) -> tauri::Result<Menu<tauri::Wry>> {
     // Create custom menu items
@@ -70,18 +72,41 @@ fn create_menu(app: &tauri::AppHandle) -> tauri::Result<Menu<tauri::Wry>> {
         .build()
 }
 
+/// Load the configuration from the environment and check it, describing every problem
+fn load_config() -> Result<AppConfig, String> {
+    let config = AppConfig::from_env().map_err(|e| {
+        format!(
+            "Failed to load configuration. Please check your .env file: {}",
+            e
+        )
+    })?;
+    config.validate().map_err(|problems| {
+        format!(
+            "Invalid configuration. Please check your .env file:\n  - {}",
+            problems.join("\n  - ")
+        )
+    })?;
+    Ok(config)
+}
+
 fn main() {
+    // RUST_LOG overrides the default level, e.g. RUST_LOG=debug to see Firestore queries
+    let default_level = if cfg!(debug_assertions) {
+        "info"
+    } else {
+        "warn"
+    };
+    tracing_subscriber::fmt()
+        .with_env_filter(
+            tracing_subscriber::EnvFilter::try_from_default_env()
+                .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new(default_level)),
+        )
+        .init();
+
     tauri::Builder::default()
         .plugin(tauri_plugin_updater::Builder::new().build())
         .plugin(tauri_plugin_opener::init())
         .setup(|app| {
-            // Initialize app configuration from environment
-            let config = AppConfig::from_env()
-                .expect("Failed to load configuration. Please check your .env file.");
-
-            // Store config in app state for commands to use
-            app.manage(config);
-
             // Create and set the menu
             let menu = create_menu(app.handle())?;
             app.set_menu(menu)?;
@@ -108,52 +133,177 @@ fn main() {
                 }
             });
 
+            // Initialize app configuration from environment. Without a console (Windows
+            // release builds) a panic would just close the app, so the window shows the
+            // problems instead
+            let config = match load_config() {
+                Ok(config) => config,
+                Err(message) => {
+                    error!("❌ {}", message);
+                    app.manage(status::StartupError(Some(message)));
+                    return Ok(());
+                }
+            };
+            app.manage(status::StartupError::default());
+
+            // Store config in app state for commands to use
+            app.manage(location::LocationIndex::from_config(&config));
+            app.manage(products::SkuCache::new(
+                products::DEFAULT_SKU_CACHE_CAPACITY,
+                std::time::Duration::from_secs(config.sku_cache_ttl_secs),
+            ));
+            app.manage(products::ProductsCache::default());
+            app.manage(build_http_client(config.request_timeout_secs));
+            app.manage(config);
+            app.manage(status::ShopInfoCache::default());
+            app.manage(location::ShopifyLocationsCache::default());
+
+            // Optional extra shop profiles (SHOP1_*, SHOP2_*); the default config above stays primary
+            let (profiles, problems) = AppConfig::load_profiles();
+            for problem in problems {
+                warn!("⚠️ {}", problem);
+            }
+            app.manage(status::ConfigProfiles(profiles));
+            app.manage(inventory::UndoStack::default());
+            app.manage(inventory::OperationStore::default());
+
+            // Prime connections and caches in the background so startup isn't delayed
+            let handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                status::warm_up_backends(
+                    &handle.state::<reqwest::Client>(),
+                    &handle.state::<AppConfig>(),
+                    &handle.state::<status::ShopInfoCache>(),
+                    &handle.state::<location::ShopifyLocationsCache>(),
+                )
+                .await;
+            });
+
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
             // Product commands
             products::get_products,
+            products::get_products_cached,
+            products::force_refresh_products,
             products::get_product_by_id,
+            products::get_products_by_ids,
+            products::get_product_with_locations,
             products::search_products,
             products::search_products_by_sku_graphql,
             products::enhanced_search_products,
             products::search_products_by_name_graphql,
+            products::search_products_by_tag_graphql,
             products::find_product_by_exact_sku_graphql,
+            products::clear_sku_cache,
+            products::find_product_by_barcode_graphql,
+            products::check_sku_exists,
+            products::search_products_annotated,
+            products::get_product_overview,
+            products::get_product_variants,
+            products::get_product_thumbnails,
+            products::resolve_skus,
+            products::validate_skus,
+            products::build_search_index,
+            products::get_search_index_version,
+            products::get_products_status,
+            products::preview_markdown,
+            products::assert_skus_unique,
             // Inventory commands
             inventory::get_inventory_levels,
+            inventory::get_variant_quantity,
+            inventory::find_orphaned_inventory,
+            inventory::audit_product_inventory,
+            inventory::compare_snapshots,
+            inventory::capture_snapshot,
             inventory::get_inventory_levels_for_locations,
             inventory::get_location_config,
             inventory::adjust_inventory,
             inventory::adjust_inventory_graphql,
+            inventory::get_adjustment_reasons,
+            inventory::adjust_inventory_batch_graphql,
             inventory::set_inventory_level,
+            inventory::set_inventory_level_graphql,
+            inventory::set_inventory_level_safe,
             inventory::get_low_stock_products,
+            inventory::get_low_stock_products_by_location,
             // Enhanced inventory commands with Firebase logging
             inventory::decrease_inventory_with_logging,
             inventory::undo_decrease_inventory_with_logging,
             inventory::transfer_inventory_between_locations,
+            inventory::transfer_by_location_name,
+            inventory::undo_last_n,
+            inventory::preview_undo_day,
+            inventory::set_status_if_zero,
+            inventory::set_product_status,
+            inventory::import_counts_csv,
+            inventory::close_out_day,
             // Modification history commands
             inventory::get_product_modification_history,
+            // Analytics commands
+            analytics::get_turnover,
+            analytics::get_sell_through,
+            analytics::get_frequently_sold_together,
+            analytics::get_hourly_activity,
+            analytics::compute_reorder_points,
+            analytics::find_anomalous_logs,
+            analytics::get_inventory_statistics,
             // Firebase commands
             firebase::create_log,
+            firebase::sync_pending_logs,
+            firebase::retry_queued_log,
             firebase::get_logs,
+            firebase::get_logs_with_ids,
+            firebase::delete_log,
             firebase::get_logs_date_range,
+            firebase::stream_logs,
+            firebase::export_logs_csv,
             firebase::get_logs_by_product_id,
             firebase::create_check_request,
             firebase::get_check_requests,
             firebase::update_check_request,
+            firebase::update_check_request_fields,
+            firebase::get_check_activity,
+            firebase::add_product_note,
+            firebase::get_product_notes,
+            firebase::log_reconciliation,
             firebase::get_firebase_config,
             // Location commands
             location::get_app_location,
             location::set_app_location,
+            location::set_app_location_resolved,
             location::get_available_locations,
             location::get_location_by_name,
             location::get_current_location_config,
             // Status commands
             status::test_shopify_connection,
+            status::get_system_health,
+            status::warm_up,
+            status::test_connections_for_profile,
+            status::get_config_source,
+            status::get_startup_error,
             status::greet,
+            status::run_graphql,
+            status::get_shop_info,
+            status::format_price,
+            status::get_last_run_times,
             // Stock management commands
             stock::get_products_with_no_stock,
-            stock::update_products_no_stock_to_draft
+            stock::update_products_no_stock_to_draft,
+            stock::get_draft_products_with_stock,
+            stock::reactivate_in_stock_draft_products,
+            stock::get_stale_draft_products,
+            stock::archive_stale_draft_products,
+            stock::get_excluded_products,
+            stock::add_excluded_product,
+            stock::remove_excluded_product,
+            stock::stream_no_stock_products,
+            stock::find_negative_inventory,
+            stock::find_products_in_inventory_range,
+            stock::get_no_stock_at_location,
+            stock::get_balance_scores,
+            stock::find_zero_price_variants,
+            stock::export_stock_report_csv
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -1,80 +1,474 @@
-use crate::utils::{AppConfig, Product, ProductVariant};
+use crate::utils::{send_with_retry, AppConfig, AppError, Product, ProductVariant, StatusResponse};
 use regex::Regex;
+use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
+use std::collections::HashMap;
 use tauri::State;
+use tracing::{debug, error, info, warn};
+
+/// Compact product info for list views
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ProductSummary {
+    pub id: String,
+    pub title: String,
+    pub handle: String,
+    pub price: String,
+    pub image: Option<String>,
+    pub total_inventory: i32,
+}
+
+impl From<&Product> for ProductSummary {
+    fn from(product: &Product) -> Self {
+        ProductSummary {
+            id: product.id.clone(),
+            title: product.title.clone(),
+            handle: product.handle.clone(),
+            price: product.price.clone(),
+            image: product.images.first().cloned(),
+            total_inventory: product.total_inventory,
+        }
+    }
+}
 
+/// Search result annotated with the stock available at the current store
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AnnotatedProduct {
+    pub product: ProductSummary,
+    pub here_stock: i32,
+}
+
+/// At-a-glance price range and stock spread for a product detail header
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ProductOverview {
+    pub min_price: f64,
+    pub max_price: f64,
+    pub total_variants: usize,
+    pub variants_in_stock: usize,
+    pub total_stock_here: i32,
+    pub total_stock_other: i32,
+}
+
+/// Fetch the catalog, following `page_info` cursors until exhausted or `max_pages`
+/// pages (of 250 products) have been read
 #[tauri::command]
-pub async fn get_products(config: State<'_, AppConfig>) -> Result<Vec<Product>, String> {
-    let client = reqwest::Client::new();
-    let url = config.get_api_url("products.json?limit=250");
+pub async fn get_products(
+    client: State<'_, reqwest::Client>,
+    config: State<'_, AppConfig>,
+    max_pages: Option<u32>,
+) -> Result<Vec<Product>, AppError> {
+    let max_pages = max_pages.filter(|pages| *pages > 0);
+    let mut result = Vec::new();
+    let mut page_info: Option<String> = None;
+    let mut pages = 0;
 
-    let response = client
-        .get(&url)
-        .headers(config.get_headers())
-        .send()
+    loop {
+        let url = match &page_info {
+            Some(cursor) => {
+                config.get_api_url(&format!("products.json?limit=250&page_info={}", cursor))
+            }
+            None => config.get_api_url("products.json?limit=250"),
+        };
+
+        let response =
+            send_with_retry(client.get(&url).headers(config.get_headers()), &config).await?;
+        if !response.status().is_success() {
+            return Err(AppError::from_response(response).await);
+        }
+        let next_page_info = crate::stock::extract_next_page_info(&response);
+
+        let data: Value = response
+            .json()
+            .await
+            .map_err(|e| AppError::Parse(format!("Failed to parse JSON: {}", e)))?;
+
+        let products = data["products"].as_array().ok_or("No products found")?;
+        for product in products {
+            result.push(parse_product_from_json(product)?);
+        }
+
+        pages += 1;
+        info!(
+            "📄 Fetched products page {}: {} products so far",
+            pages,
+            result.len()
+        );
+
+        if max_pages.is_some_and(|max| pages >= max) {
+            break;
+        }
+        match next_page_info {
+            Some(next) => page_info = Some(next),
+            None => break,
+        }
+    }
+
+    Ok(result)
+}
+
+/// Default freshness window for `get_products_cached`
+pub const DEFAULT_PRODUCTS_CACHE_MAX_AGE_SECS: u64 = 300;
+
+/// Last full catalog fetched by `get_products_cached`, kept in app state with the time
+/// it was fetched so reopening the products screen doesn't re-paginate everything
+#[derive(Default)]
+pub struct ProductsCache(std::sync::Mutex<Option<(std::time::Instant, Vec<Product>)>>);
+
+impl ProductsCache {
+    /// The cached catalog, if it was fetched less than `max_age` ago
+    pub fn get(&self, max_age: std::time::Duration) -> Option<Vec<Product>> {
+        let cached = self.0.lock().ok()?;
+        cached
+            .as_ref()
+            .filter(|(fetched_at, _)| fetched_at.elapsed() < max_age)
+            .map(|(_, products)| products.clone())
+    }
+
+    pub fn set(&self, products: Vec<Product>) {
+        if let Ok(mut cached) = self.0.lock() {
+            *cached = Some((std::time::Instant::now(), products));
+        }
+    }
+
+    /// Drop the cached catalog, returning how many products it held
+    pub fn clear(&self) -> usize {
+        self.0
+            .lock()
+            .ok()
+            .and_then(|mut cached| cached.take())
+            .map(|(_, products)| products.len())
+            .unwrap_or(0)
+    }
+}
+
+/// Return the whole catalog from the cache if it is younger than `max_age_secs`
+/// (default 5 minutes), otherwise fetch it again and refresh the cache
+#[tauri::command]
+pub async fn get_products_cached(
+    client: State<'_, reqwest::Client>,
+    config: State<'_, AppConfig>,
+    cache: State<'_, ProductsCache>,
+    max_age_secs: Option<u64>,
+) -> Result<Vec<Product>, AppError> {
+    let max_age =
+        std::time::Duration::from_secs(max_age_secs.unwrap_or(DEFAULT_PRODUCTS_CACHE_MAX_AGE_SECS));
+    if let Some(products) = cache.get(max_age) {
+        info!("⚡ Serving {} products from cache", products.len());
+        return Ok(products);
+    }
+
+    let products = get_products(client, config, None).await?;
+    cache.set(products.clone());
+    Ok(products)
+}
+
+/// Invalidate the cached catalog so the next `get_products_cached` fetches from Shopify
+#[tauri::command]
+pub fn force_refresh_products(cache: State<'_, ProductsCache>) -> StatusResponse {
+    let cleared = cache.clear();
+    info!("🧹 Cleared {} cached products", cleared);
+    StatusResponse {
+        status: "success".to_string(),
+        message: format!("Cleared {} cached products", cleared),
+    }
+}
+
+#[tauri::command]
+pub async fn get_product_by_id(
+    client: State<'_, reqwest::Client>,
+    config: State<'_, AppConfig>,
+    product_id: String,
+) -> Result<Product, AppError> {
+    let product = fetch_product_json(&client, &config, &product_id).await?;
+    parse_product_from_json(&product).map_err(AppError::from)
+}
+
+/// The raw `product` object of `products/{id}.json`
+async fn fetch_product_json(
+    client: &reqwest::Client,
+    config: &AppConfig,
+    product_id: &str,
+) -> Result<Value, AppError> {
+    let url = config.get_api_url(&format!("products/{}.json", product_id));
+
+    let response = send_with_retry(client.get(&url).headers(config.get_headers()), config).await?;
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        return Err(AppError::NotFound(format!(
+            "Product {} not found",
+            product_id
+        )));
+    }
+    if !response.status().is_success() {
+        return Err(AppError::from_response(response).await);
+    }
+
+    let mut data: Value = response
+        .json()
         .await
-        .map_err(|e| format!("Request failed: {}", e))?;
+        .map_err(|e| AppError::Parse(format!("Failed to parse JSON: {}", e)))?;
+
+    Ok(data["product"].take())
+}
+
+/// Most ids Shopify accepts in one `products.json?ids=` request
+const PRODUCTS_BY_IDS_PAGE_SIZE: usize = 250;
+
+/// Arrange `products` in the order of `ids`, dropping ids with no matching product
+/// and repeated ids
+pub fn order_products_by_ids(products: Vec<Product>, ids: &[String]) -> Vec<Product> {
+    let mut by_id: HashMap<String, Product> =
+        products.into_iter().map(|p| (p.id.clone(), p)).collect();
+    ids.iter()
+        .filter_map(|id| by_id.remove(id.trim()))
+        .collect()
+}
+
+/// Products in a `products.json` response, or a `ShopifyApi` error when Shopify answered
+/// with a non-success status
+pub async fn products_from_response(response: reqwest::Response) -> Result<Vec<Product>, AppError> {
+    if !response.status().is_success() {
+        return Err(AppError::from_response(response).await);
+    }
 
     let data: Value = response
         .json()
         .await
-        .map_err(|e| format!("Failed to parse JSON: {}", e))?;
+        .map_err(|e| AppError::Parse(format!("Failed to parse JSON: {}", e)))?;
 
-    let products = data["products"].as_array().ok_or("No products found")?;
+    let mut products = Vec::new();
+    for product in data["products"].as_array().into_iter().flatten() {
+        products.push(parse_product_from_json(product)?);
+    }
+    Ok(products)
+}
 
-    let mut result = Vec::new();
-    for product in products {
-        let parsed_product = parse_product_from_json(product)?;
-        result.push(parsed_product);
+/// Fetch several products in one round trip per 250 ids, in the order requested.
+/// Ids that don't resolve to a product are skipped
+#[tauri::command]
+pub async fn get_products_by_ids(
+    client: State<'_, reqwest::Client>,
+    config: State<'_, AppConfig>,
+    product_ids: Vec<String>,
+) -> Result<Vec<Product>, AppError> {
+    let mut unique_ids: Vec<String> = Vec::new();
+    for id in product_ids.iter().map(|id| id.trim()) {
+        if !id.is_empty() && !unique_ids.iter().any(|seen| seen == id) {
+            unique_ids.push(id.to_string());
+        }
     }
 
-    Ok(result)
+    let mut products = Vec::new();
+    for chunk in unique_ids.chunks(PRODUCTS_BY_IDS_PAGE_SIZE) {
+        let url = config.get_api_url(&format!(
+            "products.json?ids={}&limit={}",
+            chunk.join(","),
+            PRODUCTS_BY_IDS_PAGE_SIZE
+        ));
+
+        let response =
+            send_with_retry(client.get(&url).headers(config.get_headers()), &config).await?;
+        products.extend(products_from_response(response).await?);
+    }
+
+    info!(
+        "📦 Fetched {} of {} requested products",
+        products.len(),
+        unique_ids.len()
+    );
+    Ok(order_products_by_ids(products, &unique_ids))
 }
 
+/// Fill `product.locations` with per-store totals and each variant's `locations` with its
+/// own quantities at every store in `location_names`, from levels keyed by
+/// inventory_item_id → store name → available
+pub fn fill_product_locations(
+    product: &mut Product,
+    levels: &HashMap<String, HashMap<String, i32>>,
+    location_names: &[String],
+) {
+    let mut totals: HashMap<String, i32> = location_names
+        .iter()
+        .map(|name| (name.clone(), 0))
+        .collect();
+
+    for variant in &mut product.variants {
+        let item_levels = levels.get(&variant.inventory_item_id);
+        for name in location_names {
+            let quantity = item_levels.and_then(|l| l.get(name)).copied().unwrap_or(0);
+            variant.locations.insert(name.clone(), quantity);
+            *totals.entry(name.clone()).or_insert(0) += quantity;
+        }
+    }
+
+    product.locations = totals;
+}
+
+/// Fetch a product with its variants' quantities at every store filled in, so the
+/// detail view doesn't have to join product and inventory levels itself
 #[tauri::command]
-pub async fn get_product_by_id(
+pub async fn get_product_with_locations(
+    client: State<'_, reqwest::Client>,
     config: State<'_, AppConfig>,
+    locations: State<'_, crate::location::LocationIndex>,
     product_id: String,
-) -> Result<Product, String> {
-    let client = reqwest::Client::new();
-    let url = config.get_api_url(&format!("products/{}.json", product_id));
+    primary_location_name: String,
+) -> Result<Product, AppError> {
+    locations.resolve(&primary_location_name)?;
 
-    let response = client
-        .get(&url)
-        .headers(config.get_headers())
-        .send()
+    let mut product = get_product_by_id(client.clone(), config.clone(), product_id).await?;
+
+    let inventory_item_ids: Vec<String> = product
+        .variants
+        .iter()
+        .map(|v| v.inventory_item_id.clone())
+        .filter(|id| !id.is_empty())
+        .collect();
+
+    let levels = crate::inventory::fetch_grouped_levels(
+        &client,
+        &config,
+        &inventory_item_ids,
+        &primary_location_name,
+    )
+    .await?;
+
+    let location_names: Vec<String> = config
+        .named_locations()
+        .into_iter()
+        .map(|(name, _)| name)
+        .collect();
+    fill_product_locations(&mut product, &levels.by_location, &location_names);
+    info!(
+        "🏪 Product {} stock by location: {:?}",
+        product.id, product.locations
+    );
+    Ok(product)
+}
+
+/// Fetch only a product's variants (no images/description) for lightweight pickers
+#[tauri::command]
+pub async fn get_product_variants(
+    client: State<'_, reqwest::Client>,
+    config: State<'_, AppConfig>,
+    product_id: String,
+) -> Result<Vec<ProductVariant>, AppError> {
+    let url = config.get_api_url(&format!("products/{}.json?fields=id,variants", product_id));
+
+    let response = send_with_retry(client.get(&url).headers(config.get_headers()), &config).await?;
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        return Err(AppError::NotFound(format!(
+            "Product {} not found",
+            product_id
+        )));
+    }
+    if !response.status().is_success() {
+        return Err(AppError::from_response(response).await);
+    }
+
+    let data: Value = response
+        .json()
         .await
-        .map_err(|e| format!("Request failed: {}", e))?;
+        .map_err(|e| AppError::Parse(format!("Failed to parse JSON: {}", e)))?;
+
+    let product = &data["product"];
+    if product.is_null() {
+        return Err(AppError::NotFound(format!(
+            "Product {} not found",
+            product_id
+        )));
+    }
+
+    Ok(parse_variants_from_json(product))
+}
+
+/// Insert a Shopify CDN size suffix (e.g. `200x200`, `large`) before the file extension,
+/// keeping any query string such as `?v=123` intact
+pub fn image_url_at_size(src: &str, size: &str) -> String {
+    let (path, query) = match src.find('?') {
+        Some(idx) => src.split_at(idx),
+        None => (src, ""),
+    };
+
+    // Only look for the extension in the last path segment
+    let file_start = path.rfind('/').map(|i| i + 1).unwrap_or(0);
+    match path[file_start..].rfind('.') {
+        Some(dot) => {
+            let (stem, ext) = path.split_at(file_start + dot);
+            format!("{}_{}{}{}", stem, size, ext, query)
+        }
+        None => format!("{}_{}{}", path, size, query),
+    }
+}
+
+/// Fetch a product's image URLs resized to the given CDN size for thumbnails
+#[tauri::command]
+pub async fn get_product_thumbnails(
+    client: State<'_, reqwest::Client>,
+    config: State<'_, AppConfig>,
+    product_id: String,
+    size: String,
+) -> Result<Vec<String>, AppError> {
+    let url = config.get_api_url(&format!("products/{}.json?fields=id,images", product_id));
+
+    let response = send_with_retry(client.get(&url).headers(config.get_headers()), &config).await?;
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        return Err(AppError::NotFound(format!(
+            "Product {} not found",
+            product_id
+        )));
+    }
+    if !response.status().is_success() {
+        return Err(AppError::from_response(response).await);
+    }
 
     let data: Value = response
         .json()
         .await
-        .map_err(|e| format!("Failed to parse JSON: {}", e))?;
+        .map_err(|e| AppError::Parse(format!("Failed to parse JSON: {}", e)))?;
 
     let product = &data["product"];
-    parse_product_from_json(product)
+    if product.is_null() {
+        return Err(AppError::NotFound(format!(
+            "Product {} not found",
+            product_id
+        )));
+    }
+
+    Ok(product["images"]
+        .as_array()
+        .map(|imgs| {
+            imgs.iter()
+                .filter_map(|img| img["src"].as_str())
+                .map(|src| image_url_at_size(src, &size))
+                .collect()
+        })
+        .unwrap_or_default())
 }
 
 #[tauri::command]
 pub async fn search_products(
+    client: State<'_, reqwest::Client>,
     config: State<'_, AppConfig>,
     query: String,
-) -> Result<Vec<Product>, String> {
-    let client = reqwest::Client::new();
+    status: Option<String>,
+    vendor: Option<String>,
+) -> Result<Vec<Product>, AppError> {
+    let status = crate::inventory::validate_product_status(status.as_deref().unwrap_or("active"))?;
     let encoded_query = urlencoding::encode(&query);
-    let url = config.get_api_url(&format!("products.json?title={}&limit=250", encoded_query));
+    let mut endpoint = format!(
+        "products.json?title={}&status={}&limit=250",
+        encoded_query, status
+    );
+    if let Some(vendor) = vendor.as_deref().filter(|v| !v.trim().is_empty()) {
+        endpoint.push_str(&format!("&vendor={}", urlencoding::encode(vendor.trim())));
+    }
+    let url = config.get_api_url(&endpoint);
 
-    let response = client
-        .get(&url)
-        .headers(config.get_headers())
-        .send()
-        .await
-        .map_err(|e| format!("Request failed: {}", e))?;
+    let response = send_with_retry(client.get(&url).headers(config.get_headers()), &config).await?;
 
     let data: Value = response
         .json()
         .await
-        .map_err(|e| format!("Failed to parse JSON: {}", e))?;
+        .map_err(|e| AppError::Parse(format!("Failed to parse JSON: {}", e)))?;
 
     let products = data["products"].as_array().ok_or("No products found")?;
 
@@ -90,16 +484,16 @@ pub async fn search_products(
 /// Search products by SKU using GraphQL - much more efficient than REST pagination
 #[tauri::command]
 pub async fn search_products_by_sku_graphql(
+    client: State<'_, reqwest::Client>,
     config: State<'_, AppConfig>,
     sku: String,
-) -> Result<Vec<Product>, String> {
-    let client = reqwest::Client::new();
+) -> Result<Vec<Product>, AppError> {
     let graphql_url = format!(
         "https://{}/admin/api/{}/graphql.json",
         config.shop_domain, config.api_version
     );
 
-    println!("🎯 GraphQL SKU Search for: '{}'", sku);
+    info!("🎯 GraphQL SKU Search for: '{}'", sku);
 
     // Use GraphQL to search for products by SKU - much more efficient
     let query = format!(
@@ -112,6 +506,9 @@ pub async fn search_products_by_sku_graphql(
                         title
                         handle
                         descriptionHtml
+                        tags
+                        productType
+                        vendor
                         updatedAt
                         priceRangeV2 {{
                             minVariantPrice {{
@@ -132,10 +529,15 @@ pub async fn search_products_by_sku_graphql(
                                     title
                                     inventoryItem {{
                                         id
+                                        unitCost {{
+                                            amount
+                                        }}
                                     }}
                                     inventoryQuantity
                                     price
+                                    compareAtPrice
                                     sku
+                                    barcode
                                 }}
                             }}
                         }}
@@ -157,28 +559,28 @@ pub async fn search_products_by_sku_graphql(
         .json(&request_body)
         .send()
         .await
-        .map_err(|e| format!("GraphQL request failed: {}", e))?;
+        .map_err(|e| config.request_error(e))?;
 
     let response_text = response
         .text()
         .await
         .map_err(|e| format!("Failed to read response: {}", e))?;
 
-    println!("📡 GraphQL Response received");
+    debug!("📡 GraphQL Response received");
 
     let data: Value = serde_json::from_str(&response_text)
-        .map_err(|e| format!("Failed to parse GraphQL response: {}", e))?;
+        .map_err(|e| AppError::Parse(format!("Failed to parse GraphQL response: {}", e)))?;
 
     if let Some(errors) = data["errors"].as_array() {
-        println!("❌ GraphQL Errors: {:?}", errors);
-        return Err(format!("GraphQL errors: {:?}", errors));
+        error!("❌ GraphQL Errors: {:?}", errors);
+        return Err(format!("GraphQL errors: {:?}", errors).into());
     }
 
     let products = data["data"]["products"]["edges"]
         .as_array()
         .ok_or("No products found in GraphQL response")?;
 
-    println!("📊 GraphQL returned {} products", products.len());
+    info!("📊 GraphQL returned {} products", products.len());
 
     let mut result = Vec::new();
     for edge in products {
@@ -195,12 +597,12 @@ pub async fn search_products_by_sku_graphql(
         });
 
         if has_matching_sku {
-            println!("✅ Found product with matching SKU: {}", product.title);
+            info!("✅ Found product with matching SKU: {}", product.title);
             result.push(product);
         }
     }
 
-    println!(
+    info!(
         "🎯 GraphQL SKU search for '{}' found {} products",
         sku,
         result.len()
@@ -208,19 +610,196 @@ pub async fn search_products_by_sku_graphql(
     Ok(result)
 }
 
+// ============================================================================
+// SKU CACHE
+// ============================================================================
+
+/// Most SKUs kept in the SKU cache before the least recently used one is evicted
+pub const DEFAULT_SKU_CACHE_CAPACITY: usize = 500;
+
+struct SkuCacheEntry {
+    product_id: String,
+    inventory_item_id: String,
+    inserted_at: std::time::Instant,
+    last_used: u64,
+}
+
+struct SkuCacheInner {
+    entries: HashMap<String, SkuCacheEntry>,
+    clock: u64,
+}
+
+/// LRU cache of SKU → (product_id, inventory_item_id), kept in app state so repeated
+/// lookups of the same SKU skip the GraphQL search. Entries expire after `ttl`.
+pub struct SkuCache {
+    inner: std::sync::Mutex<SkuCacheInner>,
+    capacity: usize,
+    ttl: std::time::Duration,
+}
+
+impl SkuCache {
+    pub fn new(capacity: usize, ttl: std::time::Duration) -> Self {
+        Self {
+            inner: std::sync::Mutex::new(SkuCacheInner {
+                entries: HashMap::new(),
+                clock: 0,
+            }),
+            capacity: capacity.max(1),
+            ttl,
+        }
+    }
+
+    fn key(sku: &str) -> String {
+        sku.trim().to_lowercase()
+    }
+
+    /// The cached (product_id, inventory_item_id) for `sku`, if present and not expired
+    pub fn get(&self, sku: &str) -> Option<(String, String)> {
+        let mut inner = self.inner.lock().ok()?;
+        let key = Self::key(sku);
+
+        let expired = inner
+            .entries
+            .get(&key)
+            .map(|entry| entry.inserted_at.elapsed() >= self.ttl)?;
+        if expired {
+            inner.entries.remove(&key);
+            return None;
+        }
+
+        inner.clock += 1;
+        let now = inner.clock;
+        let entry = inner.entries.get_mut(&key)?;
+        entry.last_used = now;
+        Some((entry.product_id.clone(), entry.inventory_item_id.clone()))
+    }
+
+    pub fn insert(&self, sku: &str, product_id: String, inventory_item_id: String) {
+        let Ok(mut inner) = self.inner.lock() else {
+            return;
+        };
+        let key = Self::key(sku);
+
+        if !inner.entries.contains_key(&key) && inner.entries.len() >= self.capacity {
+            let least_recent = inner
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(key, _)| key.clone());
+            if let Some(least_recent) = least_recent {
+                inner.entries.remove(&least_recent);
+            }
+        }
+
+        inner.clock += 1;
+        let last_used = inner.clock;
+        inner.entries.insert(
+            key,
+            SkuCacheEntry {
+                product_id,
+                inventory_item_id,
+                inserted_at: std::time::Instant::now(),
+                last_used,
+            },
+        );
+    }
+
+    pub fn remove(&self, sku: &str) {
+        if let Ok(mut inner) = self.inner.lock() {
+            inner.entries.remove(&Self::key(sku));
+        }
+    }
+
+    pub fn clear(&self) {
+        if let Ok(mut inner) = self.inner.lock() {
+            inner.entries.clear();
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner
+            .lock()
+            .map(|inner| inner.entries.len())
+            .unwrap_or(0)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Whether a cached SKU lookup still holds for `product` (a `products/{id}.json` product):
+/// the product is still active, like the search on a cache miss requires, and the cached
+/// variant still carries `sku`
+pub fn cached_sku_matches(product: &Value, inventory_item_id: &str, sku: &str) -> bool {
+    if product["status"].as_str() != Some("active") {
+        return false;
+    }
+    product["variants"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|variant| {
+            variant["inventory_item_id"]
+                .as_u64()
+                .is_some_and(|id| id.to_string() == inventory_item_id)
+        })
+        .any(|variant| {
+            variant["sku"]
+                .as_str()
+                .is_some_and(|variant_sku| variant_sku.trim().eq_ignore_ascii_case(sku.trim()))
+        })
+}
+
+/// Drop every cached SKU lookup so the next searches hit Shopify again
+#[tauri::command]
+pub fn clear_sku_cache(sku_cache: State<'_, SkuCache>) -> StatusResponse {
+    let cleared = sku_cache.len();
+    sku_cache.clear();
+    info!("🧹 Cleared {} cached SKU lookups", cleared);
+    StatusResponse {
+        status: "success".to_string(),
+        message: format!("Cleared {} cached SKUs", cleared),
+    }
+}
+
 /// Find exact product by SKU using GraphQL - returns the first exact match with variant info
 #[tauri::command]
 pub async fn find_product_by_exact_sku_graphql(
+    client: State<'_, reqwest::Client>,
     config: State<'_, AppConfig>,
+    sku_cache: State<'_, SkuCache>,
     sku: String,
-) -> Result<Option<(Product, String)>, String> {
-    let client = reqwest::Client::new();
+) -> Result<Option<(Product, String)>, AppError> {
+    if let Some((product_id, inventory_item_id)) = sku_cache.get(&sku) {
+        // A plain fetch by id is much cheaper than the GraphQL search; make sure the entry
+        // still matches what the search would find before trusting it
+        let cached = fetch_product_json(&client, &config, &product_id)
+            .await
+            .ok()
+            .filter(|product| cached_sku_matches(product, &inventory_item_id, &sku))
+            .and_then(|product| parse_product_from_json(&product).ok());
+        match cached {
+            Some(product) => {
+                info!("⚡ SKU cache hit for '{}'", sku);
+                return Ok(Some((product, inventory_item_id)));
+            }
+            None => {
+                info!(
+                    "🧹 Cached lookup for SKU '{}' is stale, searching again",
+                    sku
+                );
+                sku_cache.remove(&sku);
+            }
+        }
+    }
+
     let graphql_url = format!(
         "https://{}/admin/api/{}/graphql.json",
         config.shop_domain, config.api_version
     );
 
-    println!("🎯 Looking for EXACT SKU match via GraphQL: '{}'", sku);
+    info!("🎯 Looking for EXACT SKU match via GraphQL: '{}'", sku);
 
     // Search for products that contain this SKU
     let query = format!(
@@ -233,6 +812,9 @@ pub async fn find_product_by_exact_sku_graphql(
                         title
                         handle
                         descriptionHtml
+                        tags
+                        productType
+                        vendor
                         updatedAt
                         priceRangeV2 {{
                             minVariantPrice {{
@@ -253,10 +835,15 @@ pub async fn find_product_by_exact_sku_graphql(
                                     title
                                     inventoryItem {{
                                         id
+                                        unitCost {{
+                                            amount
+                                        }}
                                     }}
                                     inventoryQuantity
                                     price
+                                    compareAtPrice
                                     sku
+                                    barcode
                                 }}
                             }}
                         }}
@@ -278,7 +865,7 @@ pub async fn find_product_by_exact_sku_graphql(
         .json(&request_body)
         .send()
         .await
-        .map_err(|e| format!("GraphQL request failed: {}", e))?;
+        .map_err(|e| config.request_error(e))?;
 
     let response_text = response
         .text()
@@ -286,18 +873,18 @@ pub async fn find_product_by_exact_sku_graphql(
         .map_err(|e| format!("Failed to read response: {}", e))?;
 
     let data: Value = serde_json::from_str(&response_text)
-        .map_err(|e| format!("Failed to parse GraphQL response: {}", e))?;
+        .map_err(|e| AppError::Parse(format!("Failed to parse GraphQL response: {}", e)))?;
 
     if let Some(errors) = data["errors"].as_array() {
-        println!("❌ GraphQL Errors: {:?}", errors);
-        return Err(format!("GraphQL errors: {:?}", errors));
+        error!("❌ GraphQL Errors: {:?}", errors);
+        return Err(format!("GraphQL errors: {:?}", errors).into());
     }
 
     let products = data["data"]["products"]["edges"]
         .as_array()
         .ok_or("No products found in GraphQL response")?;
 
-    println!(
+    info!(
         "📊 GraphQL returned {} products for exact SKU search",
         products.len()
     );
@@ -314,55 +901,483 @@ pub async fn find_product_by_exact_sku_graphql(
         for variant in &product_clone.variants {
             if let Some(variant_sku) = &variant.sku {
                 if variant_sku.eq_ignore_ascii_case(&sku) {
-                    println!(
+                    info!(
                         "✅ EXACT MATCH FOUND! Product: '{}', Variant: '{}', SKU: '{}'",
                         product.title, variant.title, variant_sku
                     );
+                    sku_cache.insert(&sku, product.id.clone(), variant.inventory_item_id.clone());
                     return Ok(Some((product, variant.inventory_item_id.clone())));
                 }
             }
         }
     }
 
-    println!("❌ No exact SKU match found for: '{}'", sku);
-    Ok(None)
+    error!("❌ No exact SKU match found for: '{}'", sku);
+    Ok(None)
+}
+
+/// Pick the product and inventory item whose variant carries exactly `barcode`
+/// from a `productVariants` GraphQL response
+pub fn find_barcode_match(
+    data: &Value,
+    barcode: &str,
+) -> Result<Option<(Product, String)>, String> {
+    let edges = data["data"]["productVariants"]["edges"]
+        .as_array()
+        .ok_or("No variants found in GraphQL response")?;
+
+    for edge in edges {
+        let product_node = &edge["node"]["product"];
+        if product_node.is_null() {
+            continue;
+        }
+        let product = convert_graphql_product_to_product(product_node)?;
+
+        if let Some(variant) = product
+            .variants
+            .iter()
+            .find(|v| v.barcode.as_deref().map(str::trim) == Some(barcode.trim()))
+        {
+            let inventory_item_id = variant.inventory_item_id.clone();
+            return Ok(Some((product, inventory_item_id)));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Look up the product carrying an EAN/UPC barcode, for warehouse scanners
+#[tauri::command]
+pub async fn find_product_by_barcode_graphql(
+    client: State<'_, reqwest::Client>,
+    config: State<'_, AppConfig>,
+    barcode: String,
+) -> Result<Option<(Product, String)>, AppError> {
+    let graphql_url = format!(
+        "https://{}/admin/api/{}/graphql.json",
+        config.shop_domain, config.api_version
+    );
+
+    info!("🎯 Looking for barcode match via GraphQL: '{}'", barcode);
+
+    let query = format!(
+        r#"
+        {{
+            productVariants(first: 10, query: "barcode:{}") {{
+                edges {{
+                    node {{
+                        product {{
+                            id
+                            title
+                            handle
+                            descriptionHtml
+                            tags
+                            productType
+                            vendor
+                            updatedAt
+                            priceRangeV2 {{
+                                minVariantPrice {{
+                                    amount
+                                }}
+                            }}
+                            images(first: 5) {{
+                                edges {{
+                                    node {{
+                                        src
+                                    }}
+                                }}
+                            }}
+                            variants(first: 50) {{
+                                edges {{
+                                    node {{
+                                        id
+                                        title
+                                        inventoryItem {{
+                                            id
+                                            unitCost {{
+                                                amount
+                                            }}
+                                        }}
+                                        inventoryQuantity
+                                        price
+                                        compareAtPrice
+                                        sku
+                                        barcode
+                                    }}
+                                }}
+                            }}
+                        }}
+                    }}
+                }}
+            }}
+        }}
+        "#,
+        escape_query_value(barcode.trim())
+    );
+
+    let response = client
+        .post(&graphql_url)
+        .headers(config.get_headers())
+        .json(&json!({ "query": query }))
+        .send()
+        .await
+        .map_err(|e| config.request_error(e))?;
+
+    let response_text = response
+        .text()
+        .await
+        .map_err(|e| format!("Failed to read response: {}", e))?;
+
+    let data: Value = serde_json::from_str(&response_text)
+        .map_err(|e| AppError::Parse(format!("Failed to parse GraphQL response: {}", e)))?;
+
+    if let Some(errors) = data["errors"].as_array() {
+        error!("❌ GraphQL Errors: {:?}", errors);
+        return Err(format!("GraphQL errors: {:?}", errors).into());
+    }
+
+    let found = find_barcode_match(&data, &barcode)?;
+    match &found {
+        Some((product, inventory_item_id)) => info!(
+            "✅ BARCODE MATCH FOUND! Product: '{}', inventory item: {}",
+            product.title, inventory_item_id
+        ),
+        None => error!("❌ No barcode match found for: '{}'", barcode),
+    }
+    Ok(found)
+}
+
+/// Product id and title of the variant carrying exactly `sku` (case-insensitive) in a
+/// `productVariants` GraphQL response
+pub fn find_sku_owner(data: &Value, sku: &str) -> Result<Option<(String, String)>, String> {
+    let edges = data["data"]["productVariants"]["edges"]
+        .as_array()
+        .ok_or("No variants found in GraphQL response")?;
+
+    Ok(edges
+        .iter()
+        .map(|edge| &edge["node"])
+        .find(|node| {
+            node["sku"]
+                .as_str()
+                .is_some_and(|s| s.trim().eq_ignore_ascii_case(sku.trim()))
+        })
+        .map(|node| {
+            let gql_id = node["product"]["id"].as_str().unwrap_or("");
+            let id = gql_id.split('/').last().unwrap_or(gql_id).to_string();
+            let title = node["product"]["title"]
+                .as_str()
+                .unwrap_or("Unknown")
+                .to_string();
+            (id, title)
+        }))
+}
+
+/// Check whether a SKU is already used by any variant, whatever the product status.
+/// Returns the owning product's id and title, or None if the SKU is free
+#[tauri::command]
+pub async fn check_sku_exists(
+    client: State<'_, reqwest::Client>,
+    config: State<'_, AppConfig>,
+    sku: String,
+) -> Result<Option<(String, String)>, AppError> {
+    let graphql_url = format!(
+        "https://{}/admin/api/{}/graphql.json",
+        config.shop_domain, config.api_version
+    );
+
+    info!("🔎 Checking whether SKU '{}' is already used", sku);
+
+    // No status filter: drafts and archived products still own their SKUs
+    let query = format!(
+        r#"
+        {{
+            productVariants(first: 25, query: "sku:{}") {{
+                edges {{
+                    node {{
+                        sku
+                        product {{
+                            id
+                            title
+                        }}
+                    }}
+                }}
+            }}
+        }}
+        "#,
+        escape_query_value(sku.trim())
+    );
+
+    let response = client
+        .post(&graphql_url)
+        .headers(config.get_headers())
+        .json(&json!({ "query": query }))
+        .send()
+        .await
+        .map_err(|e| config.request_error(e))?;
+
+    let data: Value = response
+        .json()
+        .await
+        .map_err(|e| AppError::Parse(format!("Failed to parse GraphQL response: {}", e)))?;
+
+    if let Some(errors) = data["errors"].as_array() {
+        error!("❌ GraphQL Errors: {:?}", errors);
+        return Err(format!("GraphQL errors: {:?}", errors).into());
+    }
+
+    let owner = find_sku_owner(&data, &sku)?;
+    match &owner {
+        Some((product_id, title)) => warn!(
+            "⚠️ SKU '{}' already used by '{}' (ID: {})",
+            sku, title, product_id
+        ),
+        None => info!("✅ SKU '{}' is free", sku),
+    }
+    Ok(owner)
+}
+
+/// A SKU resolved to the product and inventory item that carries it
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ResolvedSku {
+    pub sku: String,
+    pub product_id: String,
+    pub product_title: String,
+    pub variant_id: String,
+    pub variant_title: String,
+    pub inventory_item_id: String,
+}
+
+/// Whether a requested SKU exists in the store
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SkuValidation {
+    pub sku: String,
+    pub found: bool,
+    pub product_id: Option<String>,
+}
+
+/// Resolve each distinct SKU by exact match; SKUs that don't exist are left out.
+/// Lookup failures are returned as errors so they are never mistaken for unknown SKUs.
+#[tauri::command]
+pub async fn resolve_skus(
+    client: State<'_, reqwest::Client>,
+    config: State<'_, AppConfig>,
+    sku_cache: State<'_, SkuCache>,
+    skus: Vec<String>,
+) -> Result<Vec<ResolvedSku>, AppError> {
+    let mut seen = std::collections::HashSet::new();
+    let unique_skus: Vec<String> = skus
+        .into_iter()
+        .map(|sku| sku.trim().to_string())
+        .filter(|sku| !sku.is_empty() && seen.insert(sku.to_lowercase()))
+        .collect();
+
+    let lookups = crate::firebase::run_bounded(unique_skus, config.bulk_concurrency, |sku| {
+        let client = client.clone();
+        let config = config.clone();
+        let sku_cache = sku_cache.clone();
+        async move {
+            find_product_by_exact_sku_graphql(client.clone(), config, sku_cache, sku.clone())
+                .await
+                .map(|found| {
+                    found.map(|(product, inventory_item_id)| {
+                        let variant = product
+                            .variants
+                            .iter()
+                            .find(|v| v.inventory_item_id == inventory_item_id);
+                        ResolvedSku {
+                            sku,
+                            variant_id: variant.map(|v| v.variant_id.clone()).unwrap_or_default(),
+                            variant_title: variant.map(|v| v.title.clone()).unwrap_or_default(),
+                            product_id: product.id,
+                            product_title: product.title,
+                            inventory_item_id,
+                        }
+                    })
+                })
+        }
+    })
+    .await;
+
+    let mut resolved = Vec::new();
+    for lookup in lookups {
+        if let Some(found) = lookup? {
+            resolved.push(found);
+        }
+    }
+
+    info!("🔎 Resolved {} distinct SKUs", resolved.len());
+    Ok(resolved)
+}
+
+/// Per-SKU validation flags in the same order as `skus` (matching is case-insensitive)
+pub fn build_sku_validations(skus: &[String], resolved: &[ResolvedSku]) -> Vec<SkuValidation> {
+    let by_sku: HashMap<String, &ResolvedSku> = resolved
+        .iter()
+        .map(|r| (r.sku.trim().to_lowercase(), r))
+        .collect();
+
+    skus.iter()
+        .map(|sku| {
+            let product_id = by_sku
+                .get(&sku.trim().to_lowercase())
+                .map(|r| r.product_id.clone());
+            SkuValidation {
+                sku: sku.clone(),
+                found: product_id.is_some(),
+                product_id,
+            }
+        })
+        .collect()
+}
+
+/// Check that every SKU in a scanned list exists before running a bulk operation
+#[tauri::command]
+pub async fn validate_skus(
+    client: State<'_, reqwest::Client>,
+    config: State<'_, AppConfig>,
+    sku_cache: State<'_, SkuCache>,
+    skus: Vec<String>,
+) -> Result<Vec<SkuValidation>, AppError> {
+    let resolved = resolve_skus(client.clone(), config, sku_cache, skus.clone()).await?;
+    let validations = build_sku_validations(&skus, &resolved);
+
+    let missing = validations.iter().filter(|v| !v.found).count();
+    info!(
+        "✅ Validated {} SKUs: {} not found",
+        validations.len(),
+        missing
+    );
+    Ok(validations)
+}
+
+/// Map a GraphQL `nodes` response to product id → lowercase status.
+/// Ids that don't resolve to a product come back as `null` and are omitted.
+pub fn parse_nodes_status(response: &Value) -> HashMap<String, String> {
+    response["data"]["nodes"]
+        .as_array()
+        .map(|nodes| {
+            nodes
+                .iter()
+                .filter_map(|node| {
+                    let id = node["id"].as_str()?.rsplit('/').next()?.to_string();
+                    let status = node["status"].as_str()?.to_lowercase();
+                    Some((id, status))
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Current status (active/draft/archived) of many products, fetched via `nodes` in chunks
+#[tauri::command]
+pub async fn get_products_status(
+    client: State<'_, reqwest::Client>,
+    config: State<'_, AppConfig>,
+    product_ids: Vec<String>,
+) -> Result<HashMap<String, String>, AppError> {
+    let url = config.get_api_url("graphql.json");
+    let query = r#"
+        query ProductStatuses($ids: [ID!]!) {
+            nodes(ids: $ids) {
+                ... on Product {
+                    id
+                    status
+                }
+            }
+        }
+    "#;
+
+    // `nodes` accepts at most 250 ids per query
+    let mut statuses = HashMap::new();
+    for chunk in product_ids.chunks(config.bulk_chunk_size.clamp(1, 250)) {
+        let ids: Vec<String> = chunk
+            .iter()
+            .map(|id| format!("gid://shopify/Product/{}", id))
+            .collect();
+
+        let response = client
+            .post(&url)
+            .headers(config.get_headers())
+            .json(&json!({ "query": query, "variables": { "ids": ids } }))
+            .send()
+            .await
+            .map_err(|e| config.request_error(e))?;
+
+        let data: Value = response
+            .json()
+            .await
+            .map_err(|e| AppError::Parse(format!("Failed to parse GraphQL response: {}", e)))?;
+
+        if let Some(errors) = data.get("errors") {
+            return Err(format!("GraphQL errors: {}", errors).into());
+        }
+
+        statuses.extend(parse_nodes_status(&data));
+    }
+
+    info!(
+        "📋 Fetched status for {}/{} products",
+        statuses.len(),
+        product_ids.len()
+    );
+    Ok(statuses)
 }
 
 /// Enhanced search that looks for both title and SKU matches
 #[tauri::command]
 pub async fn enhanced_search_products(
+    client: State<'_, reqwest::Client>,
     config: State<'_, AppConfig>,
+    sku_cache: State<'_, SkuCache>,
     query: String,
-) -> Result<Vec<Product>, String> {
-    println!("🚀 Enhanced search starting for query: '{}'", query);
+) -> Result<Vec<Product>, AppError> {
+    info!("🚀 Enhanced search starting for query: '{}'", query);
     let mut result = Vec::new();
     let mut found_product_ids = std::collections::HashSet::new();
 
     // PHASE 1: Check if query is an exact SKU match
     if query.trim().len() > 5 {
         // SKUs are typically longer than 5 characters
-        println!("🔍 Phase 1: Checking for exact SKU match");
-        match find_product_by_exact_sku_graphql(config.clone(), query.trim().to_string()).await {
+        info!("🔍 Phase 1: Checking for exact SKU match");
+        match find_product_by_exact_sku_graphql(
+            client.clone(),
+            config.clone(),
+            sku_cache.clone(),
+            query.trim().to_string(),
+        )
+        .await
+        {
             Ok(Some((product, _variant_id))) => {
-                println!("✅ Found exact SKU match, returning immediately");
+                info!("✅ Found exact SKU match, returning immediately");
                 found_product_ids.insert(product.id.clone());
                 result.push(product);
                 return Ok(result); // Return immediately for exact SKU match
             }
             Ok(None) => {
-                println!("🔍 No exact SKU match found, continuing to title search");
+                info!("🔍 No exact SKU match found, continuing to title search");
             }
             Err(e) => {
-                println!("⚠️ SKU search failed: {}", e);
+                warn!("⚠️ SKU search failed: {}", e);
             }
         }
     }
 
     // PHASE 2: Title search using GraphQL
-    println!("🔍 Phase 2: GraphQL title search");
-    match search_products_by_name_graphql(config.clone(), query.clone(), None, None).await {
+    info!("🔍 Phase 2: GraphQL title search");
+    match search_products_by_name_graphql(
+        client.clone(),
+        config.clone(),
+        query.clone(),
+        None,
+        None,
+        None,
+        None,
+    )
+    .await
+    {
         Ok(title_products) => {
-            println!(
+            info!(
                 "✅ GraphQL title search returned {} products",
                 title_products.len()
             );
@@ -374,21 +1389,20 @@ pub async fn enhanced_search_products(
             }
         }
         Err(e) => {
-            println!(
+            warn!(
                 "⚠️ GraphQL title search failed, falling back to REST: {}",
                 e
             );
             // Fallback to REST API title search if GraphQL fails
-            let client = reqwest::Client::new();
             let encoded_query = urlencoding::encode(&query);
             let title_url =
                 config.get_api_url(&format!("products.json?title={}&limit=250", encoded_query));
 
-            match client
-                .get(&title_url)
-                .headers(config.get_headers())
-                .send()
-                .await
+            match send_with_retry(
+                client.get(&title_url).headers(config.get_headers()),
+                &config,
+            )
+            .await
             {
                 Ok(title_response) => {
                     if let Ok(title_data) = title_response.json::<Value>().await {
@@ -404,20 +1418,20 @@ pub async fn enhanced_search_products(
                         }
                     }
                 }
-                Err(e) => println!("❌ REST fallback also failed: {}", e),
+                Err(e) => error!("❌ REST fallback also failed: {}", e),
             }
         }
     }
 
     // PHASE 3: If we still have few results, also search by SKU (partial matches)
     if result.len() < 10 {
-        println!(
+        info!(
             "🔍 Phase 3: SKU partial search (current results: {})",
             result.len()
         );
-        match search_products_by_sku_graphql(config.clone(), query.clone()).await {
+        match search_products_by_sku_graphql(client.clone(), config.clone(), query.clone()).await {
             Ok(sku_results) => {
-                println!("✅ SKU search returned {} products", sku_results.len());
+                info!("✅ SKU search returned {} products", sku_results.len());
                 for product in sku_results {
                     // Avoid duplicates from title search
                     if !found_product_ids.contains(&product.id) {
@@ -426,12 +1440,12 @@ pub async fn enhanced_search_products(
                 }
             }
             Err(e) => {
-                println!("⚠️ SKU search failed: {}", e);
+                warn!("⚠️ SKU search failed: {}", e);
             }
         }
     }
 
-    println!(
+    info!(
         "🎯 Enhanced search for '{}' completed: {} total results",
         query,
         result.len()
@@ -489,11 +1503,19 @@ fn convert_graphql_product_to_product(product_node: &Value) -> Result<Product, S
                 inventory_quantity: variant_node["inventoryQuantity"].as_i64().unwrap_or(0) as i32,
                 price: variant_node["price"].as_str().unwrap_or("0.00").to_string(),
                 sku: variant_node["sku"].as_str().map(|s| s.to_string()),
+                barcode: variant_node["barcode"].as_str().map(|s| s.to_string()),
+                locations: std::collections::HashMap::new(),
+                compare_at_price: variant_node["compareAtPrice"]
+                    .as_str()
+                    .map(|s| s.to_string()),
+                cost: variant_node["inventoryItem"]["unitCost"]["amount"]
+                    .as_str()
+                    .map(|s| s.to_string()),
             }
         })
         .collect();
 
-    Ok(Product {
+    let mut product = Product {
         id,
         title,
         handle,
@@ -503,7 +1525,49 @@ fn convert_graphql_product_to_product(product_node: &Value) -> Result<Product, S
         images,
         variants,
         locations: std::collections::HashMap::new(),
-    })
+        warnings: Vec::new(),
+        tags: parse_product_tags(&product_node["tags"]),
+        product_type: product_node["productType"]
+            .as_str()
+            .unwrap_or("")
+            .to_string(),
+        vendor: product_node["vendor"].as_str().unwrap_or("").to_string(),
+    };
+    flag_missing_inventory_items(&mut product);
+
+    Ok(product)
+}
+
+/// Product tags from either the REST comma-separated string or the GraphQL list,
+/// trimmed and without empty entries
+pub fn parse_product_tags(tags: &Value) -> Vec<String> {
+    let raw: Vec<&str> = match tags {
+        Value::String(tags) => tags.split(',').collect(),
+        Value::Array(tags) => tags.iter().filter_map(|tag| tag.as_str()).collect(),
+        _ => Vec::new(),
+    };
+    raw.into_iter()
+        .map(str::trim)
+        .filter(|tag| !tag.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Record a warning for every variant without an inventory_item_id, since inventory
+/// operations on those variants would silently fail
+pub fn flag_missing_inventory_items(product: &mut Product) {
+    for variant in &product.variants {
+        if variant.inventory_item_id.trim().is_empty() {
+            warn!(
+                "⚠️ Product {} variant '{}' has no inventory_item_id",
+                product.id, variant.title
+            );
+            product.warnings.push(format!(
+                "Variante '{}' senza inventory_item_id: operazioni di inventario non disponibili",
+                variant.title
+            ));
+        }
+    }
 }
 
 fn clean_html_description(html: &str) -> String {
@@ -527,7 +1591,37 @@ fn clean_html_description(html: &str) -> String {
     final_text.trim().to_string()
 }
 
-fn parse_product_from_json(product: &Value) -> Result<Product, String> {
+/// Parse the REST `variants` array of a product, keeping variants without an inventory item
+pub fn parse_variants_from_json(product: &Value) -> Vec<ProductVariant> {
+    product["variants"]
+        .as_array()
+        .map(|vars| {
+            vars.iter()
+                .filter_map(|var| {
+                    Some(ProductVariant {
+                        variant_id: var["id"].as_u64()?.to_string(),
+                        // Kept rather than dropped when missing (see flag_missing_inventory_items)
+                        inventory_item_id: var["inventory_item_id"]
+                            .as_u64()
+                            .map(|id| id.to_string())
+                            .unwrap_or_default(),
+                        title: var["title"].as_str().unwrap_or("Default").to_string(),
+                        inventory_quantity: var["inventory_quantity"].as_i64().unwrap_or(0) as i32,
+                        price: var["price"].as_str().unwrap_or("0.00").to_string(),
+                        sku: var["sku"].as_str().map(|s| s.to_string()),
+                        barcode: var["barcode"].as_str().map(|s| s.to_string()),
+                        locations: std::collections::HashMap::new(),
+                        compare_at_price: var["compare_at_price"].as_str().map(|s| s.to_string()),
+                        // The REST variant doesn't carry the inventory item's cost
+                        cost: None,
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+pub fn parse_product_from_json(product: &Value) -> Result<Product, String> {
     let id = product["id"]
         .as_u64()
         .ok_or("Missing product id")?
@@ -555,23 +1649,7 @@ fn parse_product_from_json(product: &Value) -> Result<Product, String> {
         })
         .unwrap_or_default();
 
-    let variants: Vec<ProductVariant> = product["variants"]
-        .as_array()
-        .map(|vars| {
-            vars.iter()
-                .filter_map(|var| {
-                    Some(ProductVariant {
-                        variant_id: var["id"].as_u64()?.to_string(),
-                        inventory_item_id: var["inventory_item_id"].as_u64()?.to_string(),
-                        title: var["title"].as_str().unwrap_or("Default").to_string(),
-                        inventory_quantity: var["inventory_quantity"].as_i64().unwrap_or(0) as i32,
-                        price: var["price"].as_str().unwrap_or("0.00").to_string(),
-                        sku: var["sku"].as_str().map(|s| s.to_string()),
-                    })
-                })
-                .collect()
-        })
-        .unwrap_or_default();
+    let variants = parse_variants_from_json(product);
 
     let total_inventory: i32 = variants.iter().map(|v| v.inventory_quantity).sum();
 
@@ -580,7 +1658,7 @@ fn parse_product_from_json(product: &Value) -> Result<Product, String> {
         .map(|v| v.price.clone())
         .unwrap_or_else(|| "0.00".to_string());
 
-    Ok(Product {
+    let mut product = Product {
         id,
         title,
         handle,
@@ -590,18 +1668,192 @@ fn parse_product_from_json(product: &Value) -> Result<Product, String> {
         variants,
         total_inventory,
         locations: std::collections::HashMap::new(), // Will be populated by inventory functions
-    })
+        warnings: Vec::new(),
+        tags: parse_product_tags(&product["tags"]),
+        product_type: product["product_type"].as_str().unwrap_or("").to_string(),
+        vendor: product["vendor"].as_str().unwrap_or("").to_string(),
+    };
+    flag_missing_inventory_items(&mut product);
+
+    Ok(product)
+}
+
+/// Escape a value interpolated into a GraphQL string literal: backslashes first, then quotes
+pub fn escape_query_value(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Build the status/vendor part of a products search query, e.g. `status:draft vendor:\"X\"`.
+/// Status defaults to active; backslashes and quotes in the vendor are escaped
+pub fn build_search_filters(
+    status: Option<&str>,
+    vendor: Option<&str>,
+) -> Result<String, AppError> {
+    let status = crate::inventory::validate_product_status(status.unwrap_or("active"))?;
+    let mut filters = format!("status:{}", status);
+    if let Some(vendor) = vendor.map(str::trim).filter(|v| !v.is_empty()) {
+        filters.push_str(&format!(" vendor:\\\"{}\\\"", escape_query_value(vendor)));
+    }
+    Ok(filters)
+}
+
+/// Products search clause for a tag, e.g. `tag:'SALDI ESTIVI'`. The tag is always quoted
+/// so tags containing spaces match as a whole
+pub fn build_tag_query(tag: &str) -> Result<String, AppError> {
+    let tag = tag.trim();
+    if tag.is_empty() {
+        return Err(AppError::Other("Tag must not be empty".to_string()));
+    }
+    Ok(format!(
+        "tag:'{}'",
+        tag.replace('\\', "")
+            .replace('\'', "\\\\'")
+            .replace('"', "\\\"")
+    ))
+}
+
+/// Keep the first occurrence of each product id, preserving order
+pub fn dedupe_products_by_id(products: Vec<Product>) -> Vec<Product> {
+    let mut seen = std::collections::HashSet::new();
+    products
+        .into_iter()
+        .filter(|product| seen.insert(product.id.clone()))
+        .collect()
+}
+
+/// All products carrying `tag` (any status), following the GraphQL cursor until every
+/// page has been read
+#[tauri::command]
+pub async fn search_products_by_tag_graphql(
+    client: State<'_, reqwest::Client>,
+    config: State<'_, AppConfig>,
+    tag: String,
+    sort_key: Option<String>,
+) -> Result<Vec<Product>, AppError> {
+    let graphql_url = format!(
+        "https://{}/admin/api/{}/graphql.json",
+        config.shop_domain, config.api_version
+    );
+    let tag_query = build_tag_query(&tag)?;
+    let sort_key = sort_key.unwrap_or_else(|| "TITLE".to_string());
+
+    info!(
+        "🏷️ GraphQL Search for tag: '{}' sorted by {}",
+        tag, sort_key
+    );
+
+    let mut result = Vec::new();
+    let mut cursor: Option<String> = None;
+    loop {
+        let after = cursor
+            .as_ref()
+            .map(|c| format!(", after: \"{}\"", c))
+            .unwrap_or_default();
+        let query = format!(
+            r#"
+            {{
+                products(first: 100, query: "{}", sortKey: {}{}) {{
+                    pageInfo {{
+                        hasNextPage
+                        endCursor
+                    }}
+                    edges {{
+                        node {{
+                            id
+                            title
+                            handle
+                            descriptionHtml
+                            tags
+                            productType
+                            vendor
+                            priceRangeV2 {{
+                                minVariantPrice {{
+                                    amount
+                                }}
+                            }}
+                            images(first: 5) {{
+                                edges {{
+                                    node {{
+                                        src
+                                    }}
+                                }}
+                            }}
+                            variants(first: 50) {{
+                                edges {{
+                                    node {{
+                                        id
+                                        title
+                                        inventoryItem {{
+                                            id
+                                            unitCost {{
+                                                amount
+                                            }}
+                                        }}
+                                        inventoryQuantity
+                                        price
+                                        compareAtPrice
+                                        sku
+                                        barcode
+                                    }}
+                                }}
+                            }}
+                        }}
+                    }}
+                }}
+            }}
+            "#,
+            tag_query, sort_key, after
+        );
+        debug!("📋 GraphQL Query: {}", query);
+
+        let response = send_with_retry(
+            client
+                .post(&graphql_url)
+                .headers(config.get_headers())
+                .json(&json!({ "query": query })),
+            &config,
+        )
+        .await?;
+
+        let data: Value = response
+            .json()
+            .await
+            .map_err(|e| format!("Failed to parse GraphQL JSON: {}", e))?;
+
+        if let Some(errors) = data.get("errors") {
+            return Err(format!("GraphQL errors: {}", errors).into());
+        }
+
+        let page = &data["data"]["products"];
+        let edges = page["edges"]
+            .as_array()
+            .ok_or("No products found in GraphQL response")?;
+        for edge in edges {
+            result.push(convert_graphql_product_to_product(&edge["node"])?);
+        }
+
+        cursor = page["pageInfo"]["endCursor"].as_str().map(str::to_string);
+        if !page["pageInfo"]["hasNextPage"].as_bool().unwrap_or(false) || cursor.is_none() {
+            break;
+        }
+    }
+
+    let result = dedupe_products_by_id(result);
+    info!("📊 Found {} products tagged '{}'", result.len(), tag);
+    Ok(result)
 }
 
 /// Search products by partial name using GraphQL (more flexible than REST)
 #[tauri::command]
 pub async fn search_products_by_name_graphql(
+    client: State<'_, reqwest::Client>,
     config: State<'_, AppConfig>,
     name: String,
     sort_key: Option<String>,
     sort_reverse: Option<bool>,
-) -> Result<Vec<Product>, String> {
-    let client = reqwest::Client::new();
+    status: Option<String>,
+    vendor: Option<String>,
+) -> Result<Vec<Product>, AppError> {
     let graphql_url = format!(
         "https://{}/admin/api/{}/graphql.json",
         config.shop_domain, config.api_version
@@ -610,18 +1862,22 @@ pub async fn search_products_by_name_graphql(
     // Use the provided sort key or default to RELEVANCE
     let sort_key = sort_key.unwrap_or_else(|| "RELEVANCE".to_string());
     let sort_reverse = sort_reverse.unwrap_or(false);
+    let filters = build_search_filters(status.as_deref(), vendor.as_deref())?;
 
     // Build the GraphQL query with wildcard for partial matching, sorting, and reverse option
     let query = format!(
         r#"
         {{
-            products(first: 40, query: "title:{}* status:active", sortKey: {}, reverse: {}) {{
+            products(first: 40, query: "title:{}* {}", sortKey: {}, reverse: {}) {{
                 edges {{
                     node {{
                         id
                         title
                         handle
                         descriptionHtml
+                        tags
+                        productType
+                        vendor
                         updatedAt
                         priceRangeV2 {{
                             minVariantPrice {{
@@ -642,10 +1898,15 @@ pub async fn search_products_by_name_graphql(
                                     title
                                     inventoryItem {{
                                         id
+                                        unitCost {{
+                                            amount
+                                        }}
                                     }}
                                     inventoryQuantity
                                     price
+                                    compareAtPrice
                                     sku
+                                    barcode
                                 }}
                             }}
                         }}
@@ -655,15 +1916,16 @@ pub async fn search_products_by_name_graphql(
         }}
         "#,
         name.replace("\"", "\\\""), // Escape quotes in search term
+        filters,
         sort_key,
         sort_reverse
     );
 
-    println!(
+    info!(
         "🔍 GraphQL Search for name: '{}' with sort key: '{}', reverse: {}",
         name, sort_key, sort_reverse
     );
-    println!("📋 GraphQL Query: {}", query);
+    debug!("📋 GraphQL Query: {}", query);
 
     let payload = json!({
         "query": query
@@ -675,7 +1937,7 @@ pub async fn search_products_by_name_graphql(
         .json(&payload)
         .send()
         .await
-        .map_err(|e| format!("GraphQL request failed: {}", e))?;
+        .map_err(|e| config.request_error(e))?;
 
     let data: Value = response
         .json()
@@ -684,14 +1946,14 @@ pub async fn search_products_by_name_graphql(
 
     // Check for GraphQL errors
     if let Some(errors) = data.get("errors") {
-        return Err(format!("GraphQL errors: {}", errors));
+        return Err(format!("GraphQL errors: {}", errors).into());
     }
 
     let products = data["data"]["products"]["edges"]
         .as_array()
         .ok_or("No products found in GraphQL response")?;
 
-    println!(
+    info!(
         "📊 GraphQL returned {} products for '{}'",
         products.len(),
         name
@@ -742,9 +2004,11 @@ pub async fn search_products_by_name_graphql(
                 vars.iter()
                     .filter_map(|var| {
                         let var_node = &var["node"];
-                        let gql_inventory_item_id = var_node["inventoryItem"]["id"].as_str()?;
-                        let inventory_item_id =
-                            gql_inventory_item_id.split('/').last()?.to_string();
+                        let inventory_item_id = var_node["inventoryItem"]["id"]
+                            .as_str()
+                            .and_then(|gid| gid.split('/').last())
+                            .unwrap_or("")
+                            .to_string();
 
                         let gql_variant_id = var_node["id"].as_str()?;
                         let variant_id = gql_variant_id.split('/').last()?.to_string();
@@ -757,6 +2021,14 @@ pub async fn search_products_by_name_graphql(
                                 as i32,
                             price: var_node["price"].as_str().unwrap_or("0.00").to_string(),
                             sku: var_node["sku"].as_str().map(|s| s.to_string()),
+                            barcode: var_node["barcode"].as_str().map(|s| s.to_string()),
+                            locations: std::collections::HashMap::new(),
+                            compare_at_price: var_node["compareAtPrice"]
+                                .as_str()
+                                .map(|s| s.to_string()),
+                            cost: var_node["inventoryItem"]["unitCost"]["amount"]
+                                .as_str()
+                                .map(|s| s.to_string()),
                         })
                     })
                     .collect()
@@ -765,7 +2037,7 @@ pub async fn search_products_by_name_graphql(
 
         let total_inventory: i32 = variants.iter().map(|v| v.inventory_quantity).sum();
 
-        let product = Product {
+        let mut product = Product {
             id,
             title,
             handle,
@@ -775,16 +2047,529 @@ pub async fn search_products_by_name_graphql(
             variants,
             total_inventory,
             locations: std::collections::HashMap::new(),
+            warnings: Vec::new(),
+            tags: parse_product_tags(&product_node["tags"]),
+            product_type: product_node["productType"]
+                .as_str()
+                .unwrap_or("")
+                .to_string(),
+            vendor: product_node["vendor"].as_str().unwrap_or("").to_string(),
         };
+        flag_missing_inventory_items(&mut product);
 
-        println!("✅ Parsed product: {} (ID: {})", product.title, product.id);
+        info!("✅ Parsed product: {} (ID: {})", product.title, product.id);
         result.push(product);
     }
 
-    println!(
+    info!(
         "🎯 GraphQL search for '{}' returned {} products",
         name,
         result.len()
     );
     Ok(result)
 }
+
+/// Annotate products with their total stock at `location_id`, using levels keyed by
+/// inventory_item_id → location_id → available (the `get_inventory_levels` shape)
+pub fn annotate_products(
+    products: &[Product],
+    inventory_levels: &HashMap<String, HashMap<String, i32>>,
+    location_id: &str,
+) -> Vec<AnnotatedProduct> {
+    products
+        .iter()
+        .map(|product| {
+            let here_stock = product
+                .variants
+                .iter()
+                .filter_map(|variant| inventory_levels.get(&variant.inventory_item_id))
+                .filter_map(|levels| levels.get(location_id))
+                .sum();
+
+            AnnotatedProduct {
+                product: ProductSummary::from(product),
+                here_stock,
+            }
+        })
+        .collect()
+}
+
+/// Search products and annotate each with its stock at the current store
+#[tauri::command]
+pub async fn search_products_annotated(
+    app: tauri::AppHandle,
+    client: State<'_, reqwest::Client>,
+    config: State<'_, AppConfig>,
+    locations: State<'_, crate::location::LocationIndex>,
+    sku_cache: State<'_, SkuCache>,
+    query: String,
+) -> Result<Vec<AnnotatedProduct>, AppError> {
+    let products =
+        enhanced_search_products(client.clone(), config.clone(), sku_cache, query.clone()).await?;
+    if products.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let current_location = crate::location::current_location_name(app, &config).await;
+    let location_id = locations.resolve(&current_location)?;
+
+    // One bulk inventory fetch for every variant in the result set
+    let inventory_item_ids: Vec<String> = products
+        .iter()
+        .flat_map(|p| p.variants.iter().map(|v| v.inventory_item_id.clone()))
+        .collect();
+    let inventory_levels =
+        crate::inventory::get_inventory_levels(client.clone(), config.clone(), inventory_item_ids)
+            .await?;
+
+    let annotated = annotate_products(&products, &inventory_levels, &location_id);
+    info!(
+        "🏪 Annotated {} products for '{}' with stock at {}",
+        annotated.len(),
+        query,
+        current_location
+    );
+    Ok(annotated)
+}
+
+/// Build a product overview from levels keyed by inventory_item_id → store name →
+/// available; `here` is the current store and every other store counts as "other"
+pub fn compute_product_overview(
+    product: &Product,
+    inventory_levels: &HashMap<String, HashMap<String, i32>>,
+    here: &str,
+) -> ProductOverview {
+    let prices: Vec<f64> = product
+        .variants
+        .iter()
+        .filter_map(|v| v.price.parse::<f64>().ok())
+        .collect();
+    let min_price = prices.iter().copied().fold(f64::INFINITY, f64::min);
+    let max_price = prices.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+
+    let mut variants_in_stock = 0;
+    let mut total_stock_here = 0;
+    let mut total_stock_other = 0;
+
+    for variant in &product.variants {
+        let levels = inventory_levels.get(&variant.inventory_item_id);
+        let total: i32 = levels.map(|l| l.values().sum()).unwrap_or(0);
+        let here = levels.and_then(|l| l.get(here)).copied().unwrap_or(0);
+
+        if total > 0 {
+            variants_in_stock += 1;
+        }
+        total_stock_here += here;
+        total_stock_other += total - here;
+    }
+
+    ProductOverview {
+        min_price: if prices.is_empty() { 0.0 } else { min_price },
+        max_price: if prices.is_empty() { 0.0 } else { max_price },
+        total_variants: product.variants.len(),
+        variants_in_stock,
+        total_stock_here,
+        total_stock_other,
+    }
+}
+
+/// Price range and stock spread (current store vs other store) for a product
+#[tauri::command]
+pub async fn get_product_overview(
+    app: tauri::AppHandle,
+    client: State<'_, reqwest::Client>,
+    config: State<'_, AppConfig>,
+    product_id: String,
+) -> Result<ProductOverview, AppError> {
+    let product = get_product_by_id(client.clone(), config.clone(), product_id.clone()).await?;
+
+    let current_location = crate::location::current_location_name(app, &config).await;
+    let inventory_item_ids: Vec<String> = product
+        .variants
+        .iter()
+        .map(|v| v.inventory_item_id.clone())
+        .collect();
+    let current_location = crate::location::resolve_location_config(&current_location, &config)?
+        .primary_location
+        .name;
+    let inventory_levels = crate::inventory::fetch_grouped_levels(
+        &client,
+        &config,
+        &inventory_item_ids,
+        &current_location,
+    )
+    .await?;
+
+    let overview =
+        compute_product_overview(&product, &inventory_levels.by_location, &current_location);
+    info!(
+        "📊 Overview for product {}: {:.2}-{:.2}, {} variants ({} in stock), {} at {}, {} elsewhere",
+        product_id,
+        overview.min_price,
+        overview.max_price,
+        overview.total_variants,
+        overview.variants_in_stock,
+        overview.total_stock_here,
+        current_location,
+        overview.total_stock_other
+    );
+    Ok(overview)
+}
+
+// ============================================================================
+// SEARCH INDEX
+// ============================================================================
+
+/// Compact catalog entry for instant client-side search
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SearchIndexEntry {
+    pub id: String,
+    pub title: String,
+    pub normalized_title: String,
+    pub skus: Vec<String>,
+    pub total_inventory: i32,
+}
+
+/// Whole-catalog search index; `version` changes whenever the catalog does
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SearchIndex {
+    pub version: String,
+    pub entries: Vec<SearchIndexEntry>,
+}
+
+/// Lowercase, strip accents and punctuation, and collapse whitespace for matching
+pub fn normalize_title(title: &str) -> String {
+    let folded: String = title
+        .to_lowercase()
+        .chars()
+        .map(|c| match c {
+            'à' | 'á' | 'â' | 'ä' => 'a',
+            'è' | 'é' | 'ê' | 'ë' => 'e',
+            'ì' | 'í' | 'î' | 'ï' => 'i',
+            'ò' | 'ó' | 'ô' | 'ö' => 'o',
+            'ù' | 'ú' | 'û' | 'ü' => 'u',
+            c if c.is_alphanumeric() => c,
+            _ => ' ',
+        })
+        .collect();
+
+    folded.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Build index entries from the paginated catalog
+pub fn build_search_entries(products: &[crate::stock::ShopifyProduct]) -> Vec<SearchIndexEntry> {
+    products
+        .iter()
+        .map(|product| SearchIndexEntry {
+            id: product.id.to_string(),
+            title: product.title.clone(),
+            normalized_title: normalize_title(&product.title),
+            skus: product
+                .variants
+                .iter()
+                .filter_map(|v| v.sku.clone())
+                .filter(|sku| !sku.trim().is_empty())
+                .collect(),
+            total_inventory: product.variants.iter().map(|v| v.inventory_quantity).sum(),
+        })
+        .collect()
+}
+
+/// Cache key derived from the active product count and the latest product update
+pub fn search_index_version(product_count: u64, last_updated_at: &str) -> String {
+    format!("{}-{}", product_count, last_updated_at)
+}
+
+async fn fetch_search_index_version(
+    client: &reqwest::Client,
+    config: &AppConfig,
+) -> Result<String, String> {
+    let query = r#"
+        query {
+            productsCount(query: "status:active") {
+                count
+            }
+            products(first: 1, sortKey: UPDATED_AT, reverse: true, query: "status:active") {
+                edges {
+                    node {
+                        updatedAt
+                    }
+                }
+            }
+        }
+    "#;
+
+    let response = client
+        .post(config.get_api_url("graphql.json"))
+        .headers(config.get_headers())
+        .json(&json!({ "query": query }))
+        .send()
+        .await
+        .map_err(|e| format!("GraphQL request failed: {}", config.request_error(e)))?;
+
+    let data: Value = response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse GraphQL response: {}", e))?;
+
+    if let Some(errors) = data.get("errors") {
+        return Err(format!("GraphQL errors: {}", errors));
+    }
+
+    let count = data["data"]["productsCount"]["count"].as_u64().unwrap_or(0);
+    let last_updated_at = data["data"]["products"]["edges"][0]["node"]["updatedAt"]
+        .as_str()
+        .unwrap_or("");
+
+    Ok(search_index_version(count, last_updated_at))
+}
+
+/// Current catalog version, for the frontend to decide whether its cached index is stale
+#[tauri::command]
+pub async fn get_search_index_version(
+    client: State<'_, reqwest::Client>,
+    config: State<'_, AppConfig>,
+) -> Result<String, AppError> {
+    fetch_search_index_version(&client, &config)
+        .await
+        .map_err(AppError::from)
+}
+
+/// Load the whole active catalog into a compact index the frontend can search locally
+#[tauri::command]
+pub async fn build_search_index(
+    client: State<'_, reqwest::Client>,
+    config: State<'_, AppConfig>,
+) -> Result<SearchIndex, AppError> {
+    info!("🗂️ Building catalog search index...");
+
+    // Read the version first so concurrent edits make the index look stale, not fresh
+    let version = fetch_search_index_version(&client, &config).await?;
+    let products = crate::stock::fetch_all_active_products(&client, &config).await?;
+    let entries = build_search_entries(&products);
+
+    info!(
+        "✅ Search index built: {} entries (version {})",
+        entries.len(),
+        version
+    );
+    Ok(SearchIndex { version, entries })
+}
+
+/// A variant's price before and after a markdown; nothing is written to Shopify
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct PriceChange {
+    pub product_id: String,
+    pub product_title: String,
+    pub variant_id: String,
+    pub variant_title: String,
+    pub current_price: String,
+    pub new_price: String,
+    pub compare_at_price: String,
+}
+
+/// Price after taking `percent_off` percent off, rounded to the cent
+pub fn markdown_price(price: f64, percent_off: f64) -> f64 {
+    (price * (100.0 - percent_off)).round() / 100.0
+}
+
+/// Compute the markdown for every variant of the given REST products.
+/// The compare-at price keeps an existing higher compare-at, otherwise it's the current price
+pub fn build_price_changes(products: &[Value], percent_off: f64) -> Vec<PriceChange> {
+    let mut changes = Vec::new();
+
+    for product in products {
+        let product_id = product["id"].as_u64().map(|id| id.to_string());
+        let Some(product_id) = product_id else {
+            continue;
+        };
+        let product_title = product["title"].as_str().unwrap_or("").to_string();
+
+        for variant in product["variants"].as_array().into_iter().flatten() {
+            let Some(variant_id) = variant["id"].as_u64() else {
+                continue;
+            };
+            let Some(current) = variant["price"]
+                .as_str()
+                .and_then(|p| p.parse::<f64>().ok())
+            else {
+                continue;
+            };
+            let compare_at = variant["compare_at_price"]
+                .as_str()
+                .and_then(|p| p.parse::<f64>().ok())
+                .filter(|compare| *compare > current)
+                .unwrap_or(current);
+
+            changes.push(PriceChange {
+                product_id: product_id.clone(),
+                product_title: product_title.clone(),
+                variant_id: variant_id.to_string(),
+                variant_title: variant["title"].as_str().unwrap_or("Default").to_string(),
+                current_price: format!("{:.2}", current),
+                new_price: format!("{:.2}", markdown_price(current, percent_off)),
+                compare_at_price: format!("{:.2}", compare_at),
+            });
+        }
+    }
+
+    changes
+}
+
+/// Preview a markdown on a collection so the owner can review it before committing.
+/// Only reads from Shopify; follows pagination so large collections are complete
+#[tauri::command]
+pub async fn preview_markdown(
+    client: State<'_, reqwest::Client>,
+    config: State<'_, AppConfig>,
+    collection_id: String,
+    percent_off: f64,
+) -> Result<Vec<PriceChange>, AppError> {
+    if !(percent_off > 0.0 && percent_off < 100.0) {
+        return Err(format!(
+            "Invalid markdown: {}% (must be between 0 and 100)",
+            percent_off
+        )
+        .into());
+    }
+
+    info!(
+        "🏷️ Previewing {}% markdown for collection {}",
+        percent_off, collection_id
+    );
+
+    let mut products = Vec::new();
+    let mut page_info: Option<String> = None;
+
+    loop {
+        // Shopify rejects other filters alongside page_info, so only the first page carries them
+        let url = match &page_info {
+            Some(cursor) => {
+                config.get_api_url(&format!("products.json?limit=250&page_info={}", cursor))
+            }
+            None => config.get_api_url(&format!(
+                "products.json?limit=250&collection_id={}",
+                collection_id
+            )),
+        };
+
+        let response =
+            send_with_retry(client.get(&url).headers(config.get_headers()), &config).await?;
+        if !response.status().is_success() {
+            return Err(AppError::from_response(response).await);
+        }
+        let next_page_info = crate::stock::extract_next_page_info(&response);
+
+        let data: Value = response
+            .json()
+            .await
+            .map_err(|e| AppError::Parse(format!("Failed to parse JSON: {}", e)))?;
+
+        if let Some(page) = data["products"].as_array() {
+            products.extend(page.iter().cloned());
+        }
+
+        match next_page_info {
+            Some(next) => page_info = Some(next),
+            None => break,
+        }
+    }
+
+    let changes = build_price_changes(&products, percent_off);
+    info!(
+        "✅ Markdown preview: {} variants across {} products",
+        changes.len(),
+        products.len()
+    );
+    Ok(changes)
+}
+
+/// One variant carrying a SKU that is shared with other variants
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SkuOccurrence {
+    pub product_id: String,
+    pub product_title: String,
+    pub variant_title: String,
+    /// Draft and archived products are checked too, since they can be reactivated
+    pub product_status: String,
+}
+
+/// A SKU used by more than one variant, which makes barcode scans ambiguous
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct DuplicateSku {
+    pub sku: String,
+    pub occurrences: Vec<SkuOccurrence>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SkuUniquenessReport {
+    pub unique: bool,
+    pub duplicates: Vec<DuplicateSku>,
+}
+
+/// Group variants by trimmed SKU and keep the SKUs used more than once, sorted by SKU.
+/// Variants without a SKU are ignored
+pub fn find_duplicate_skus(products: &[crate::stock::ShopifyProduct]) -> Vec<DuplicateSku> {
+    let mut by_sku: HashMap<String, Vec<SkuOccurrence>> = HashMap::new();
+
+    for product in products {
+        for variant in &product.variants {
+            let Some(sku) = variant.sku.as_deref().map(str::trim) else {
+                continue;
+            };
+            if sku.is_empty() {
+                continue;
+            }
+            by_sku
+                .entry(sku.to_string())
+                .or_default()
+                .push(SkuOccurrence {
+                    product_id: product.id.to_string(),
+                    product_title: product.title.clone(),
+                    variant_title: variant.title.clone(),
+                    product_status: product.status.clone(),
+                });
+        }
+    }
+
+    let mut duplicates: Vec<DuplicateSku> = by_sku
+        .into_iter()
+        .filter(|(_, occurrences)| occurrences.len() > 1)
+        .map(|(sku, occurrences)| DuplicateSku { sku, occurrences })
+        .collect();
+    duplicates.sort_by(|a, b| a.sku.cmp(&b.sku));
+    duplicates
+}
+
+pub fn build_sku_uniqueness_report(
+    products: &[crate::stock::ShopifyProduct],
+) -> SkuUniquenessReport {
+    let duplicates = find_duplicate_skus(products);
+    SkuUniquenessReport {
+        unique: duplicates.is_empty(),
+        duplicates,
+    }
+}
+
+/// Check the whole catalog, whatever the product status, for shared SKUs before enabling
+/// barcode mode
+#[tauri::command]
+pub async fn assert_skus_unique(
+    client: State<'_, reqwest::Client>,
+    config: State<'_, AppConfig>,
+) -> Result<SkuUniquenessReport, AppError> {
+    info!("🔎 Checking SKU uniqueness for barcode mode...");
+
+    let products = crate::stock::fetch_all_products_any_status(&client, &config).await?;
+    let report = build_sku_uniqueness_report(&products);
+
+    if report.unique {
+        info!("✅ All SKUs are unique across {} products", products.len());
+    } else {
+        warn!(
+            "⚠️ {} SKUs are shared by multiple variants; barcode scans would be ambiguous",
+            report.duplicates.len()
+        );
+    }
+    Ok(report)
+}
@@ -1,19 +1,121 @@
-use crate::utils::{AppConfig, StatusResponse};
-use tauri::State;
+use crate::utils::{
+    format_price as format_amount, send_with_retry, AppConfig, AppError, StatusResponse,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::{Manager, State};
+
+// ============================================================================
+// SHOP INFORMATION
+// ============================================================================
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ShopInfo {
+    pub name: String,
+    pub currency: String,
+    pub timezone: String, // IANA name, e.g. "Europe/Rome"
+    pub domain: String,
+}
+
+/// Shop info cached in app state; it rarely changes so it is fetched once per session
+#[derive(Default)]
+pub struct ShopInfoCache(Mutex<Option<ShopInfo>>);
+
+impl ShopInfoCache {
+    pub fn get(&self) -> Option<ShopInfo> {
+        self.0.lock().ok().and_then(|cached| cached.clone())
+    }
+
+    pub fn set(&self, info: ShopInfo) {
+        if let Ok(mut cached) = self.0.lock() {
+            *cached = Some(info);
+        }
+    }
+}
+
+/// Parse the `shop.json` REST response into ShopInfo
+pub fn parse_shop_info(data: &Value) -> Result<ShopInfo, String> {
+    let shop = data.get("shop").ok_or("Missing shop object in response")?;
+
+    Ok(ShopInfo {
+        name: shop["name"].as_str().unwrap_or("").to_string(),
+        currency: shop["currency"].as_str().unwrap_or("EUR").to_string(),
+        timezone: shop["iana_timezone"]
+            .as_str()
+            .unwrap_or("Europe/Rome")
+            .to_string(),
+        domain: shop["domain"].as_str().unwrap_or("").to_string(),
+    })
+}
 
 #[tauri::command]
 pub async fn test_shopify_connection(
+    client: State<'_, reqwest::Client>,
+    config: State<'_, AppConfig>,
+) -> Result<StatusResponse, AppError> {
+    check_shopify_connection(&client, &config)
+        .await
+        .map_err(AppError::from)
+}
+
+/// Named shop profiles loaded from `SHOP<N>_*` env prefixes (see `AppConfig::profiles_from_vars`)
+#[derive(Default)]
+pub struct ConfigProfiles(pub HashMap<String, AppConfig>);
+
+impl ConfigProfiles {
+    pub fn get(&self, profile_name: &str) -> Option<&AppConfig> {
+        self.0.get(&profile_name.trim().to_lowercase())
+    }
+}
+
+/// Test the Shopify connection of a named shop profile, e.g. "shop2"
+#[tauri::command]
+pub async fn test_connections_for_profile(
+    profile_name: String,
+    client: State<'_, reqwest::Client>,
+    profiles: State<'_, ConfigProfiles>,
+) -> Result<StatusResponse, AppError> {
+    let config = profiles
+        .get(&profile_name)
+        .ok_or_else(|| format!("Profile '{}' not found", profile_name))?;
+    println!(
+        "🔌 Testing connection for profile {} ({})",
+        profile_name, config.shop_domain
+    );
+    check_shopify_connection(&client, config)
+        .await
+        .map_err(AppError::from)
+}
+
+/// Why the app couldn't start (e.g. an invalid `.env`), shown by the frontend instead of
+/// the main view. None when the config loaded fine.
+#[derive(Default)]
+pub struct StartupError(pub Option<String>);
+
+#[tauri::command]
+pub fn get_startup_error(startup_error: State<'_, StartupError>) -> Option<String> {
+    startup_error.0.clone()
+}
+
+/// Whether the running config came from `.env` at runtime or was embedded at build time
+#[tauri::command]
+pub async fn get_config_source(
     config: State<'_, AppConfig>,
+) -> Result<crate::utils::ConfigSource, AppError> {
+    Ok(config.config_source)
+}
+
+async fn check_shopify_connection(
+    client: &reqwest::Client,
+    config: &AppConfig,
 ) -> Result<StatusResponse, String> {
-    let client = reqwest::Client::new();
     let url = config.get_api_url("shop.json");
 
-    let response = client
-        .get(&url)
-        .headers(config.get_headers())
-        .send()
-        .await
-        .map_err(|e| format!("Request failed: {}", e))?;
+    let response = send_with_retry(client.get(&url).headers(config.get_headers()), &config).await?;
 
     if response.status().is_success() {
         Ok(StatusResponse {
@@ -28,6 +130,452 @@ pub async fn test_shopify_connection(
     }
 }
 
+/// Get the shop's name, currency, timezone and domain (cached after the first call)
+#[tauri::command]
+pub async fn get_shop_info(
+    client: State<'_, reqwest::Client>,
+    config: State<'_, AppConfig>,
+    cache: State<'_, ShopInfoCache>,
+) -> Result<ShopInfo, AppError> {
+    if let Some(info) = cache.get() {
+        return Ok(info);
+    }
+
+    let info = fetch_shop_info(&client, &config).await?;
+    cache.set(info.clone());
+
+    Ok(info)
+}
+
+/// Fetch shop info from `shop.json`, bypassing the cache
+async fn fetch_shop_info(
+    client: &reqwest::Client,
+    config: &AppConfig,
+) -> Result<ShopInfo, AppError> {
+    let url = config.get_api_url("shop.json");
+
+    let response = send_with_retry(client.get(&url).headers(config.get_headers()), &config).await?;
+
+    if !response.status().is_success() {
+        return Err(AppError::from_response(response).await);
+    }
+
+    let data: Value = response
+        .json()
+        .await
+        .map_err(|e| AppError::Parse(format!("Failed to parse JSON: {}", e)))?;
+
+    let info = parse_shop_info(&data)?;
+    println!(
+        "🏬 Shop info: {} ({}, {})",
+        info.name, info.currency, info.timezone
+    );
+
+    Ok(info)
+}
+
+/// Format a price in the shop's currency (EUR until shop info has been loaded)
+#[tauri::command]
+pub fn format_price(amount: String, cache: State<'_, ShopInfoCache>) -> String {
+    let currency = cache
+        .get()
+        .map(|info| info.currency)
+        .unwrap_or_else(|| "EUR".to_string());
+    format_amount(&amount, &currency)
+}
+
+// ============================================================================
+// SYSTEM HEALTH
+// ============================================================================
+
+/// Outcome of one diagnostics sub-check
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct HealthCheck {
+    pub ok: bool,
+    pub latency_ms: u64,
+    pub error: Option<String>,
+}
+
+/// Status dashboard data: each check reports its own result and latency
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SystemHealth {
+    pub shopify: HealthCheck,
+    pub shop_name: Option<String>,
+    pub plan: Option<String>,
+    pub api_version: String,
+    pub locations: HealthCheck,
+    pub primary_location_found: bool,
+    pub secondary_location_found: bool,
+    pub firestore: HealthCheck,
+}
+
+/// The shop's plan as shown in the admin (falls back to the internal plan name)
+pub fn parse_shop_plan(data: &Value) -> Option<String> {
+    let shop = data.get("shop")?;
+    shop["plan_display_name"]
+        .as_str()
+        .or_else(|| shop["plan_name"].as_str())
+        .map(|plan| plan.to_string())
+}
+
+/// Whether `location_id` is among the locations of a `locations.json` response
+pub fn location_listed(data: &Value, location_id: &str) -> bool {
+    data["locations"]
+        .as_array()
+        .map(|locations| {
+            locations.iter().any(|location| {
+                location["id"]
+                    .as_u64()
+                    .map(|id| id.to_string())
+                    .or_else(|| location["id"].as_str().map(|id| id.to_string()))
+                    .is_some_and(|id| id == location_id)
+            })
+        })
+        .unwrap_or(false)
+}
+
+/// Run `check`, timing it; on success its value is returned alongside the check result
+async fn timed_check<T, F>(check: F) -> (HealthCheck, Option<T>)
+where
+    F: std::future::Future<Output = Result<T, String>>,
+{
+    let started = std::time::Instant::now();
+    let result = check.await;
+    let latency_ms = started.elapsed().as_millis() as u64;
+
+    match result {
+        Ok(value) => (
+            HealthCheck {
+                ok: true,
+                latency_ms,
+                error: None,
+            },
+            Some(value),
+        ),
+        Err(e) => (
+            HealthCheck {
+                ok: false,
+                latency_ms,
+                error: Some(e),
+            },
+            None,
+        ),
+    }
+}
+
+/// GET a Shopify REST endpoint once (no retries, so latency is the real round trip)
+async fn get_shopify_json(
+    client: &reqwest::Client,
+    config: &AppConfig,
+    endpoint: &str,
+) -> Result<Value, String> {
+    let response = client
+        .get(config.get_api_url(endpoint))
+        .headers(config.get_headers())
+        .send()
+        .await
+        .map_err(|e| config.request_error(e).to_string())?;
+
+    if !response.status().is_success() {
+        return Err(format!("Shopify responded with {}", response.status()));
+    }
+    response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse JSON: {}", e))
+}
+
+/// Detailed diagnostics: Shopify shop and plan, configured locations and Firestore
+#[tauri::command]
+pub async fn get_system_health(
+    client: State<'_, reqwest::Client>,
+    config: State<'_, AppConfig>,
+) -> Result<SystemHealth, AppError> {
+    println!("🩺 Running system health checks...");
+
+    let (shopify, shop) = timed_check(get_shopify_json(&client, &config, "shop.json")).await;
+    let (locations, location_list) =
+        timed_check(get_shopify_json(&client, &config, "locations.json")).await;
+    let firebase_client = crate::firebase::FirebaseClient::new(&client, config.inner().clone());
+    let (firestore, _) = timed_check(firebase_client.ping()).await;
+
+    let found = |location_id: &str| {
+        location_list
+            .as_ref()
+            .is_some_and(|data| location_listed(data, location_id))
+    };
+
+    let health = SystemHealth {
+        shop_name: shop
+            .as_ref()
+            .and_then(|data| data["shop"]["name"].as_str())
+            .map(|name| name.to_string()),
+        plan: shop.as_ref().and_then(parse_shop_plan),
+        shopify,
+        api_version: config.api_version.clone(),
+        primary_location_found: found(&config.primary_location),
+        secondary_location_found: found(&config.secondary_location),
+        locations,
+        firestore,
+    };
+
+    println!(
+        "✅ Health: shopify={} locations={} firestore={}",
+        health.shopify.ok, health.locations.ok, health.firestore.ok
+    );
+    Ok(health)
+}
+
+/// Reject anything that is not a plain read-only GraphQL query
+pub fn validate_read_only_query(query: &str) -> Result<(), String> {
+    if query.trim().is_empty() {
+        return Err("GraphQL query is empty".to_string());
+    }
+
+    if query.to_lowercase().contains("mutation") {
+        return Err("Only read-only queries are allowed: mutations are rejected".to_string());
+    }
+
+    Ok(())
+}
+
+/// Run an arbitrary read-only GraphQL query and return the raw JSON (debug builds only)
+#[tauri::command]
+pub async fn run_graphql(
+    client: State<'_, reqwest::Client>,
+    config: State<'_, AppConfig>,
+    query: String,
+) -> Result<Value, AppError> {
+    if !cfg!(debug_assertions) {
+        return Err(AppError::Config(
+            "run_graphql is only available in development builds".to_string(),
+        ));
+    }
+
+    validate_read_only_query(&query)?;
+
+    println!("🧪 Running debug GraphQL query");
+    let url = config.get_api_url("graphql.json");
+
+    let response = client
+        .post(&url)
+        .headers(config.get_headers())
+        .json(&json!({ "query": query }))
+        .send()
+        .await
+        .map_err(|e| config.request_error(e))?;
+
+    response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse GraphQL response: {}", e))
+        .map_err(AppError::from)
+}
+
+// ============================================================================
+// WARM UP
+// ============================================================================
+
+/// Which backends answered the startup warm-up requests
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct WarmUpReport {
+    pub shop_info: bool,
+    pub locations: bool,
+    pub graphql: bool,
+    pub firestore: bool,
+}
+
+/// Run the warm-up requests concurrently, filling the shop info and locations caches.
+/// Failures are only logged: warming up is best effort.
+pub async fn warm_up_with<S, L, G, F>(
+    shop_info_cache: &ShopInfoCache,
+    locations_cache: &crate::location::ShopifyLocationsCache,
+    shop_info: S,
+    locations: L,
+    graphql: G,
+    firestore: F,
+) -> WarmUpReport
+where
+    S: std::future::Future<Output = Result<ShopInfo, String>>,
+    L: std::future::Future<Output = Result<Vec<crate::location::LocationInfo>, String>>,
+    G: std::future::Future<Output = Result<(), String>>,
+    F: std::future::Future<Output = Result<(), String>>,
+{
+    let (shop_info, locations, graphql, firestore) =
+        tokio::join!(shop_info, locations, graphql, firestore);
+
+    let mut report = WarmUpReport::default();
+    match shop_info {
+        Ok(info) => {
+            shop_info_cache.set(info);
+            report.shop_info = true;
+        }
+        Err(e) => println!("⚠️ Warm-up: shop info failed: {}", e),
+    }
+    match locations {
+        Ok(locations) if !locations.is_empty() => {
+            locations_cache.set(locations);
+            report.locations = true;
+        }
+        Ok(_) => println!("⚠️ Warm-up: Shopify returned no active locations"),
+        Err(e) => println!("⚠️ Warm-up: locations failed: {}", e),
+    }
+    match graphql {
+        Ok(()) => report.graphql = true,
+        Err(e) => println!("⚠️ Warm-up: GraphQL failed: {}", e),
+    }
+    match firestore {
+        Ok(()) => report.firestore = true,
+        Err(e) => println!("⚠️ Warm-up: Firestore failed: {}", e),
+    }
+
+    report
+}
+
+/// Lightweight GraphQL round trip to open the GraphQL connection
+async fn ping_graphql(client: &reqwest::Client, config: &AppConfig) -> Result<(), String> {
+    let response = client
+        .post(config.get_api_url("graphql.json"))
+        .headers(config.get_headers())
+        .json(&json!({ "query": "{ shop { name } }" }))
+        .send()
+        .await
+        .map_err(|e| config.request_error(e).to_string())?;
+
+    if response.status().is_success() {
+        Ok(())
+    } else {
+        Err(format!("Shopify responded with {}", response.status()))
+    }
+}
+
+/// Prime the shared HTTP client's connections to Shopify REST, GraphQL and Firestore and
+/// load the shop info and locations caches, so the first real operation doesn't pay for it
+pub async fn warm_up_backends(
+    client: &reqwest::Client,
+    config: &AppConfig,
+    shop_info_cache: &ShopInfoCache,
+    locations_cache: &crate::location::ShopifyLocationsCache,
+) -> WarmUpReport {
+    println!("🔥 Warming up backends...");
+    let firebase_client = crate::firebase::FirebaseClient::new(client, config.clone());
+
+    let report = warm_up_with(
+        shop_info_cache,
+        locations_cache,
+        async {
+            fetch_shop_info(client, config)
+                .await
+                .map_err(|e| e.to_string())
+        },
+        crate::location::fetch_shopify_locations(client, config),
+        ping_graphql(client, config),
+        firebase_client.ping(),
+    )
+    .await;
+
+    println!(
+        "✅ Warm-up: shop_info={} locations={} graphql={} firestore={}",
+        report.shop_info, report.locations, report.graphql, report.firestore
+    );
+    report
+}
+
+/// Warm up all backends on demand (also run in the background at startup)
+#[tauri::command]
+pub async fn warm_up(
+    client: State<'_, reqwest::Client>,
+    config: State<'_, AppConfig>,
+    shop_info_cache: State<'_, ShopInfoCache>,
+    locations_cache: State<'_, crate::location::ShopifyLocationsCache>,
+) -> Result<WarmUpReport, AppError> {
+    Ok(warm_up_backends(&client, &config, &shop_info_cache, &locations_cache).await)
+}
+
+// ============================================================================
+// LAST RUN TIMES
+// ============================================================================
+
+const LAST_RUN_FILE: &str = "lastRunCappelletto.json";
+
+/// Timestamps (RFC3339) of the last successful run of each operation
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct LastRunTimes {
+    pub last_no_stock_scan: Option<String>,
+    pub last_reconciliation: Option<String>,
+    pub last_log_write: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum RunKind {
+    NoStockScan,
+    Reconciliation,
+    LogWrite,
+}
+
+/// Read the stored last run times from `dir` (all empty if nothing was stored yet)
+pub fn read_last_run_times(dir: &Path) -> Result<LastRunTimes, String> {
+    let path = dir.join(LAST_RUN_FILE);
+    if !path.exists() {
+        return Ok(LastRunTimes::default());
+    }
+
+    let content =
+        fs::read_to_string(&path).map_err(|e| format!("Failed to read last run file: {}", e))?;
+    serde_json::from_str(&content).map_err(|e| format!("Failed to parse last run file: {}", e))
+}
+
+/// Serializes the read-modify-write of the last run file between concurrent operations
+static LAST_RUN_LOCK: Mutex<()> = Mutex::new(());
+
+/// Store `timestamp` as the last successful run of `kind` in `dir`, through a temporary
+/// file and rename so a crash never leaves a half-written file
+pub fn record_last_run(dir: &Path, kind: RunKind, timestamp: &str) -> Result<(), String> {
+    let _guard = LAST_RUN_LOCK
+        .lock()
+        .map_err(|_| "Last run file lock poisoned".to_string())?;
+    let mut times = read_last_run_times(dir)?;
+    let value = Some(timestamp.to_string());
+    match kind {
+        RunKind::NoStockScan => times.last_no_stock_scan = value,
+        RunKind::Reconciliation => times.last_reconciliation = value,
+        RunKind::LogWrite => times.last_log_write = value,
+    }
+
+    fs::create_dir_all(dir).map_err(|e| format!("Failed to create app data directory: {}", e))?;
+    let json_content = serde_json::to_string_pretty(&times)
+        .map_err(|e| format!("Failed to serialize last run times: {}", e))?;
+    let tmp_file = dir.join(format!("{}.tmp", LAST_RUN_FILE));
+    fs::write(&tmp_file, json_content)
+        .map_err(|e| format!("Failed to write last run file: {}", e))?;
+    fs::rename(&tmp_file, dir.join(LAST_RUN_FILE))
+        .map_err(|e| format!("Failed to replace last run file: {}", e))
+}
+
+pub(crate) fn app_data_dir(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_data_dir()
+        .map_err(|e| format!("Could not get app data directory: {}", e))
+}
+
+/// Record a successful run now; failures only warn since the operation itself succeeded
+pub fn mark_last_run(app: &tauri::AppHandle, kind: RunKind) {
+    let result = app_data_dir(app)
+        .and_then(|dir| record_last_run(&dir, kind, &chrono::Utc::now().to_rfc3339()));
+    if let Err(e) = result {
+        println!(
+            "⚠️ Warning: Failed to record last run for {:?}: {}",
+            kind, e
+        );
+    }
+}
+
+/// When the store was last scanned, reconciled and written to the log
+#[tauri::command]
+pub async fn get_last_run_times(app: tauri::AppHandle) -> Result<LastRunTimes, AppError> {
+    Ok(read_last_run_times(&app_data_dir(&app)?)?)
+}
+
 #[tauri::command]
 pub fn greet(name: &str) -> String {
     format!("Hello, {}! You've been greeted from Rust!", name)
@@ -1,23 +1,98 @@
-use crate::utils::AppConfig;
-use futures::future::join_all;
+use crate::utils::{send_with_retry, throttle_for_call_limit, AppConfig, AppError};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
-use std::collections::HashSet;
-use std::time::Duration;
+use std::collections::{HashMap, HashSet};
 use tauri::State;
-use tokio::time::sleep;
 
-// Products to exclude from automatic draft status (add IDs here)
-const EXCLUDED_PRODUCT_IDS: &[u64] = &[3587363962985]; // Excluded as requested
+// Products excluded from automatic draft status until an exclusion list is saved
+const DEFAULT_EXCLUDED_PRODUCT_IDS: &[u64] = &[3587363962985]; // Excluded as requested
 
-// Convert to strings for comparison since Shopify IDs can be strings or numbers
-lazy_static::lazy_static! {
-    static ref EXCLUDED_IDS_SET: HashSet<String> = {
-        EXCLUDED_PRODUCT_IDS
-            .iter()
-            .map(|id| id.to_string())
-            .collect()
-    };
+const EXCLUDED_PRODUCTS_FILE: &str = "excludedProductsCappelletto.json";
+
+/// The built-in exclusion set, used until the exclusions are edited from the app
+pub fn default_excluded_products() -> HashSet<String> {
+    DEFAULT_EXCLUDED_PRODUCT_IDS
+        .iter()
+        .map(|id| id.to_string())
+        .collect()
+}
+
+/// Read the excluded product ids stored in `dir` (the defaults if none were saved yet).
+/// Ids are kept as strings since Shopify IDs can be strings or numbers
+pub fn read_excluded_products(dir: &std::path::Path) -> Result<HashSet<String>, String> {
+    let path = dir.join(EXCLUDED_PRODUCTS_FILE);
+    if !path.exists() {
+        return Ok(default_excluded_products());
+    }
+
+    let content = std::fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read excluded products: {}", e))?;
+    serde_json::from_str(&content).map_err(|e| format!("Failed to parse excluded products: {}", e))
+}
+
+/// Replace the excluded product ids stored in `dir`
+pub fn write_excluded_products(
+    dir: &std::path::Path,
+    excluded: &HashSet<String>,
+) -> Result<(), String> {
+    let mut ids: Vec<&String> = excluded.iter().collect();
+    ids.sort();
+
+    std::fs::create_dir_all(dir)
+        .map_err(|e| format!("Failed to create app data directory: {}", e))?;
+    let json_content = serde_json::to_string_pretty(&ids)
+        .map_err(|e| format!("Failed to serialize excluded products: {}", e))?;
+    std::fs::write(dir.join(EXCLUDED_PRODUCTS_FILE), json_content)
+        .map_err(|e| format!("Failed to write excluded products: {}", e))
+}
+
+fn sorted_ids(excluded: HashSet<String>) -> Vec<String> {
+    let mut ids: Vec<String> = excluded.into_iter().collect();
+    ids.sort();
+    ids
+}
+
+fn parse_product_id(product_id: &str) -> Result<String, String> {
+    product_id
+        .trim()
+        .parse::<u64>()
+        .map(|id| id.to_string())
+        .map_err(|_| format!("Invalid product ID: {}", product_id))
+}
+
+/// Product ids currently excluded from automatic status changes
+#[tauri::command]
+pub async fn get_excluded_products(app: tauri::AppHandle) -> Result<Vec<String>, AppError> {
+    let dir = crate::status::app_data_dir(&app)?;
+    Ok(sorted_ids(read_excluded_products(&dir)?))
+}
+
+/// Exclude a product from automatic status changes; returns the updated list
+#[tauri::command]
+pub async fn add_excluded_product(
+    product_id: String,
+    app: tauri::AppHandle,
+) -> Result<Vec<String>, AppError> {
+    let product_id = parse_product_id(&product_id)?;
+    let dir = crate::status::app_data_dir(&app)?;
+    let mut excluded = read_excluded_products(&dir)?;
+    excluded.insert(product_id);
+    write_excluded_products(&dir, &excluded)?;
+    Ok(sorted_ids(excluded))
+}
+
+/// Stop excluding a product; returns the updated list
+#[tauri::command]
+pub async fn remove_excluded_product(
+    product_id: String,
+    app: tauri::AppHandle,
+) -> Result<Vec<String>, AppError> {
+    let product_id = parse_product_id(&product_id)?;
+    let dir = crate::status::app_data_dir(&app)?;
+    let mut excluded = read_excluded_products(&dir)?;
+    excluded.remove(&product_id);
+    write_excluded_products(&dir, &excluded)?;
+    Ok(sorted_ids(excluded))
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -41,15 +116,21 @@ pub struct UpdateResult {
     pub title: String,
     pub success: bool,
     pub error: Option<String>,
+    /// Intentionally left unchanged: excluded, or restocked since the scan
+    #[serde(default)]
+    pub skipped: bool,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct UpdateSummary {
     pub total_found: usize,
     pub excluded_count: usize,
     pub eligible_count: usize,
     pub successful_updates: usize,
     pub failed_updates: usize,
+    /// Products returned more than once by the scan and dropped before analysis
+    #[serde(default)]
+    pub duplicates_removed: usize,
 }
 
 /// Shopify Products API response for pagination
@@ -60,42 +141,776 @@ struct ProductsResponse {
 
 #[derive(Debug, Deserialize)]
 pub struct ShopifyProduct {
-    id: u64,
-    title: String,
-    status: String,
-    variants: Vec<ShopifyVariant>,
+    pub(crate) id: u64,
+    pub(crate) title: String,
+    pub(crate) status: String,
+    pub(crate) variants: Vec<ShopifyVariant>,
+    #[serde(default)]
+    pub(crate) updated_at: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
-struct ShopifyVariant {
-    inventory_quantity: i32,
+pub(crate) struct ShopifyVariant {
+    pub(crate) inventory_quantity: i32,
+    #[serde(default)]
+    pub(crate) title: String,
+    #[serde(default)]
+    pub(crate) inventory_item_id: Option<u64>,
+    #[serde(default)]
+    pub(crate) sku: Option<String>,
+    #[serde(default)]
+    pub(crate) price: Option<String>,
+}
+
+/// A variant whose price is zero or missing (usually a data-entry error)
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ZeroPriceVariant {
+    pub product_id: String,
+    pub product_title: String,
+    pub variant_title: String,
+    pub sku: Option<String>,
+}
+
+/// A variant whose available quantity at a location is below zero
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct NegativeStock {
+    pub product_id: String,
+    pub variant_title: String,
+    pub location: String,
+    pub quantity: i32,
+}
+
+/// How evenly a product's stock is spread across the configured locations
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct BalanceScore {
+    pub product_id: String,
+    pub product_title: String,
+    pub score: f64,
+}
+
+/// A variant whose quantity at a location falls inside a requested range
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct StockRangeMatch {
+    pub product_id: String,
+    pub product_title: String,
+    pub variant_title: String,
+    pub inventory_item_id: String,
+    pub sku: Option<String>,
+    pub quantity: i32,
+}
+
+/// An active product with nothing available at one store, with what the other stores hold
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ProductNoStockAtLocation {
+    pub id: String,
+    pub title: String,
+    /// Store name → available units of the product there
+    pub other_locations: HashMap<String, i32>,
+    pub total_elsewhere: i32,
 }
 
 /// Tauri command to get products with no stock (dry run)
 #[tauri::command]
 pub async fn get_products_with_no_stock(
+    app: tauri::AppHandle,
+    client: State<'_, reqwest::Client>,
     config: State<'_, AppConfig>,
-) -> Result<StockUpdateResult, String> {
+) -> Result<StockUpdateResult, AppError> {
     println!("🔍 Starting dry run scan for products with no stock...");
-    scan_and_update_products(&*config, true).await
+    let excluded = read_excluded_products(&crate::status::app_data_dir(&app)?)?;
+    let result = scan_and_update_products(&client, &config, &excluded, true, Some(&app)).await?;
+    crate::status::mark_last_run(&app, crate::status::RunKind::NoStockScan);
+    Ok(result)
 }
 
 /// Tauri command to update products with no stock to draft status
 #[tauri::command]
 pub async fn update_products_no_stock_to_draft(
+    app: tauri::AppHandle,
+    client: State<'_, reqwest::Client>,
     config: State<'_, AppConfig>,
-) -> Result<StockUpdateResult, String> {
+) -> Result<StockUpdateResult, AppError> {
     println!("⚡ Starting live update of products with no stock...");
-    scan_and_update_products(&*config, false).await
+    let excluded = read_excluded_products(&crate::status::app_data_dir(&app)?)?;
+    let result = scan_and_update_products(&client, &config, &excluded, false, Some(&app)).await?;
+    crate::status::mark_last_run(&app, crate::status::RunKind::NoStockScan);
+    Ok(result)
+}
+
+/// Tauri command to get draft products that have stock again (dry run)
+#[tauri::command]
+pub async fn get_draft_products_with_stock(
+    app: tauri::AppHandle,
+    client: State<'_, reqwest::Client>,
+    config: State<'_, AppConfig>,
+) -> Result<StockUpdateResult, AppError> {
+    println!("🔍 Starting dry run scan for draft products with stock...");
+    let excluded = read_excluded_products(&crate::status::app_data_dir(&app)?)?;
+    Ok(scan_and_reactivate_products(&client, &config, &excluded, true).await?)
+}
+
+/// Tauri command to set draft products that have stock again back to active
+#[tauri::command]
+pub async fn reactivate_in_stock_draft_products(
+    app: tauri::AppHandle,
+    client: State<'_, reqwest::Client>,
+    config: State<'_, AppConfig>,
+) -> Result<StockUpdateResult, AppError> {
+    println!("⚡ Starting live reactivation of draft products with stock...");
+    let excluded = read_excluded_products(&crate::status::app_data_dir(&app)?)?;
+    Ok(scan_and_reactivate_products(&client, &config, &excluded, false).await?)
+}
+
+/// Tauri command to get draft products with no stock untouched for `days_inactive` days
+/// (dry run of `archive_stale_draft_products`)
+#[tauri::command]
+pub async fn get_stale_draft_products(
+    days_inactive: i32,
+    app: tauri::AppHandle,
+    client: State<'_, reqwest::Client>,
+    config: State<'_, AppConfig>,
+) -> Result<StockUpdateResult, AppError> {
+    println!("🔍 Starting dry run scan for stale draft products...");
+    let excluded = read_excluded_products(&crate::status::app_data_dir(&app)?)?;
+    Ok(scan_and_archive_products(&client, &config, &excluded, days_inactive, true).await?)
+}
+
+/// Tauri command to archive draft products with no stock untouched for `days_inactive` days
+#[tauri::command]
+pub async fn archive_stale_draft_products(
+    days_inactive: i32,
+    app: tauri::AppHandle,
+    client: State<'_, reqwest::Client>,
+    config: State<'_, AppConfig>,
+) -> Result<StockUpdateResult, AppError> {
+    println!("⚡ Starting live archiving of stale draft products...");
+    let excluded = read_excluded_products(&crate::status::app_data_dir(&app)?)?;
+    Ok(scan_and_archive_products(&client, &config, &excluded, days_inactive, false).await?)
+}
+
+/// Event emitted for each product found with no stock during a streamed scan
+pub const NO_STOCK_PRODUCT_FOUND_EVENT: &str = "no-stock-product-found";
+/// Event emitted once a streamed scan has finished, carrying only the summary
+pub const NO_STOCK_SCAN_COMPLETE_EVENT: &str = "no-stock-scan-complete";
+
+/// Final payload of a streamed no-stock scan
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct NoStockScanComplete {
+    pub total_scanned: usize,
+    pub summary: UpdateSummary,
+}
+
+/// Destination for scan events; implemented by the Tauri app handle and by test mocks
+pub trait ScanEventEmitter {
+    fn emit_event(&self, event: &str, payload: serde_json::Value) -> Result<(), String>;
+}
+
+impl<R: tauri::Runtime> ScanEventEmitter for tauri::AppHandle<R> {
+    fn emit_event(&self, event: &str, payload: serde_json::Value) -> Result<(), String> {
+        use tauri::Emitter;
+        self.emit(event, payload)
+            .map_err(|e| format!("Failed to emit {}: {}", event, e))
+    }
+}
+
+/// Event emitted while the full-catalog stock scan fetches pages and updates products
+pub const STOCK_SCAN_PROGRESS_EVENT: &str = "stock-scan-progress";
+
+/// Payload of `stock-scan-progress`. `phase` is "fetching" (total unknown until the last
+/// page) or "updating" (total is the number of products being updated).
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ScanProgress {
+    pub processed: usize,
+    pub total: Option<usize>,
+    pub phase: String,
+}
+
+/// Emit a scan progress event if there is somewhere to send it (the CLI passes None)
+pub fn emit_scan_progress(
+    emitter: Option<&dyn ScanEventEmitter>,
+    phase: &str,
+    processed: usize,
+    total: Option<usize>,
+) {
+    if let Some(emitter) = emitter {
+        emit_progress(emitter, STOCK_SCAN_PROGRESS_EVENT, phase, processed, total);
+    }
+}
+
+/// Emit a `ScanProgress` payload as `event`, logging (not failing) when it can't be sent
+pub fn emit_progress(
+    emitter: &dyn ScanEventEmitter,
+    event: &str,
+    phase: &str,
+    processed: usize,
+    total: Option<usize>,
+) {
+    let progress = ScanProgress {
+        processed,
+        total,
+        phase: phase.to_string(),
+    };
+    let payload = serde_json::to_value(&progress).unwrap_or_default();
+    if let Err(e) = emitter.emit_event(event, payload) {
+        println!("⚠️ {}", e);
+    }
+}
+
+/// Analyze one page of products, emitting a found event per product with no stock
+pub fn stream_no_stock_page<E: ScanEventEmitter>(
+    emitter: &E,
+    products: Vec<ShopifyProduct>,
+    excluded: &HashSet<String>,
+    found: &mut Vec<ProductNoStock>,
+) {
+    for product in find_products_with_no_stock(products, excluded) {
+        let payload = serde_json::to_value(&product).unwrap_or_default();
+        if let Err(e) = emitter.emit_event(NO_STOCK_PRODUCT_FOUND_EVENT, payload) {
+            println!("⚠️ {}", e);
+        }
+        found.push(product);
+    }
+}
+
+/// Tauri command that scans for products with no stock (dry run), streaming each match
+/// as a `no-stock-product-found` event and finishing with `no-stock-scan-complete`
+#[tauri::command]
+pub async fn stream_no_stock_products(
+    app: tauri::AppHandle,
+    client: State<'_, reqwest::Client>,
+    config: State<'_, AppConfig>,
+) -> Result<UpdateSummary, AppError> {
+    println!("📡 Starting streamed scan for products with no stock...");
+
+    let excluded = read_excluded_products(&crate::status::app_data_dir(&app)?)?;
+    let mut found = Vec::new();
+    let total_scanned = for_each_product_page(&client, &config, |products| {
+        stream_no_stock_page(&app, products, &excluded, &mut found)
+    })
+    .await?;
+
+    let summary = generate_summary(&found, &[]);
+    let complete = NoStockScanComplete {
+        total_scanned,
+        summary: summary.clone(),
+    };
+    let payload = serde_json::to_value(&complete).unwrap_or_default();
+    app.emit_event(NO_STOCK_SCAN_COMPLETE_EVENT, payload)?;
+    crate::status::mark_last_run(&app, crate::status::RunKind::NoStockScan);
+
+    println!(
+        "✅ Streamed scan complete: {} products scanned, {} with no stock",
+        total_scanned, summary.total_found
+    );
+    Ok(summary)
+}
+
+/// Report every variant/location with a negative quantity. Levels are keyed by
+/// inventory_item_id → location_id → available; `location_names` maps location ids to
+/// store names (unknown ids are reported as-is).
+pub fn detect_negative_stock(
+    products: &[ShopifyProduct],
+    inventory_levels: &HashMap<String, HashMap<String, i32>>,
+    location_names: &HashMap<String, String>,
+) -> Vec<NegativeStock> {
+    let mut negatives = Vec::new();
+
+    for product in products {
+        for variant in &product.variants {
+            let Some(inventory_item_id) = variant.inventory_item_id else {
+                continue;
+            };
+            let Some(levels) = inventory_levels.get(&inventory_item_id.to_string()) else {
+                continue;
+            };
+
+            for (location_id, &quantity) in levels {
+                if quantity < 0 {
+                    negatives.push(NegativeStock {
+                        product_id: product.id.to_string(),
+                        variant_title: variant.title.clone(),
+                        location: location_names
+                            .get(location_id)
+                            .cloned()
+                            .unwrap_or_else(|| location_id.clone()),
+                        quantity,
+                    });
+                }
+            }
+        }
+    }
+
+    negatives.sort_by(|a, b| {
+        (&a.product_id, &a.variant_title, &a.location).cmp(&(
+            &b.product_id,
+            &b.variant_title,
+            &b.location,
+        ))
+    });
+    negatives
+}
+
+/// Fetch inventory levels (inventory_item_id → location_id → available) for every
+/// variant of `products`, in bulk-sized chunks, calling `on_chunk(processed, total)`
+/// after each chunk
+async fn fetch_levels_for_products(
+    client: &State<'_, reqwest::Client>,
+    config: &State<'_, AppConfig>,
+    products: &[ShopifyProduct],
+    mut on_chunk: impl FnMut(usize, usize),
+) -> Result<HashMap<String, HashMap<String, i32>>, String> {
+    let inventory_item_ids: Vec<String> = products
+        .iter()
+        .flat_map(|p| p.variants.iter())
+        .filter_map(|v| v.inventory_item_id.map(|id| id.to_string()))
+        .collect();
+    println!(
+        "📦 Checking {} inventory items across {} products",
+        inventory_item_ids.len(),
+        products.len()
+    );
+
+    let mut inventory_levels = HashMap::new();
+    let mut processed = 0;
+    for chunk in config.inventory_level_chunks(&inventory_item_ids) {
+        let levels =
+            crate::inventory::get_inventory_levels(client.clone(), config.clone(), chunk.to_vec())
+                .await?;
+        inventory_levels.extend(levels);
+        processed += chunk.len();
+        println!(
+            "   📊 Checked {}/{} inventory items",
+            processed,
+            inventory_item_ids.len()
+        );
+        on_chunk(processed, inventory_item_ids.len());
+    }
+    Ok(inventory_levels)
+}
+
+/// Event emitted while the negative inventory scan runs. Its `ScanProgress` phase is
+/// "fetching" (products read so far, total unknown) then "checking" (inventory items).
+pub const NEGATIVE_INVENTORY_PROGRESS_EVENT: &str = "negative-inventory-progress";
+
+/// Data-integrity watchdog: scan the catalog and report negative inventory at any location,
+/// reporting `negative-inventory-progress` to the window along the way
+#[tauri::command]
+pub async fn find_negative_inventory(
+    app: tauri::AppHandle,
+    client: State<'_, reqwest::Client>,
+    config: State<'_, AppConfig>,
+) -> Result<Vec<NegativeStock>, AppError> {
+    println!("🔍 Scanning store for negative inventory...");
+
+    let mut all_products = Vec::new();
+    for_each_product_page(&client, &config, |products| {
+        all_products.extend(products);
+        emit_progress(
+            &app,
+            NEGATIVE_INVENTORY_PROGRESS_EVENT,
+            "fetching",
+            all_products.len(),
+            None,
+        );
+    })
+    .await?;
+    let inventory_levels =
+        fetch_levels_for_products(&client, &config, &all_products, |processed, total| {
+            emit_progress(
+                &app,
+                NEGATIVE_INVENTORY_PROGRESS_EVENT,
+                "checking",
+                processed,
+                Some(total),
+            )
+        })
+        .await?;
+
+    let location_names: HashMap<String, String> = config
+        .named_locations()
+        .into_iter()
+        .map(|(name, id)| (id, name))
+        .collect();
+
+    let negatives = detect_negative_stock(&all_products, &inventory_levels, &location_names);
+    println!("⚠️ Found {} negative inventory levels", negatives.len());
+    Ok(negatives)
+}
+
+/// Products with nothing available at `location_id` (levels keyed by
+/// inventory_item_id → location_id → available). Quantities at the other ids of
+/// `location_names` are reported by store name; products with the most stock elsewhere,
+/// i.e. the easiest transfers, come first. Products without inventory items are skipped.
+pub fn find_no_stock_at_location(
+    products: &[ShopifyProduct],
+    inventory_levels: &HashMap<String, HashMap<String, i32>>,
+    location_id: &str,
+    location_names: &HashMap<String, String>,
+) -> Vec<ProductNoStockAtLocation> {
+    let quantity_at = |product: &ShopifyProduct, location: &str| -> i32 {
+        product
+            .variants
+            .iter()
+            .filter_map(|v| v.inventory_item_id)
+            .filter_map(|id| inventory_levels.get(&id.to_string()))
+            .filter_map(|levels| levels.get(location))
+            .map(|q| (*q).max(0))
+            .sum()
+    };
+
+    let mut empty: Vec<ProductNoStockAtLocation> = products
+        .iter()
+        .filter(|product| {
+            product
+                .variants
+                .iter()
+                .any(|v| v.inventory_item_id.is_some())
+        })
+        .filter(|product| quantity_at(product, location_id) == 0)
+        .map(|product| {
+            let other_locations: HashMap<String, i32> = location_names
+                .iter()
+                .filter(|(id, _)| id.as_str() != location_id)
+                .map(|(id, name)| (name.clone(), quantity_at(product, id)))
+                .collect();
+            ProductNoStockAtLocation {
+                id: product.id.to_string(),
+                title: product.title.clone(),
+                total_elsewhere: other_locations.values().sum(),
+                other_locations,
+            }
+        })
+        .collect();
+
+    empty.sort_by(|a, b| {
+        b.total_elsewhere
+            .cmp(&a.total_elsewhere)
+            .then_with(|| a.id.cmp(&b.id))
+    });
+    empty
+}
+
+/// "What's empty in my store": active products with nothing available at `location_name`,
+/// even if they are stocked at another store
+#[tauri::command]
+pub async fn get_no_stock_at_location(
+    location_name: String,
+    client: State<'_, reqwest::Client>,
+    config: State<'_, AppConfig>,
+    locations: State<'_, crate::location::LocationIndex>,
+) -> Result<Vec<ProductNoStockAtLocation>, AppError> {
+    let location_id = locations.resolve(&location_name)?;
+    println!(
+        "🔍 Scanning for products with no stock at {}...",
+        location_name
+    );
+
+    let all_products = fetch_all_active_products(&client, &config).await?;
+    let inventory_levels =
+        fetch_levels_for_products(&client, &config, &all_products, |_, _| {}).await?;
+    let location_names: HashMap<String, String> = config
+        .named_locations()
+        .into_iter()
+        .map(|(name, id)| (id, name))
+        .collect();
+
+    let empty = find_no_stock_at_location(
+        &all_products,
+        &inventory_levels,
+        &location_id,
+        &location_names,
+    );
+    println!(
+        "✅ Found {} products with no stock at {}",
+        empty.len(),
+        location_name
+    );
+    Ok(empty)
+}
+
+/// Variants whose quantity at `location_id` is within `[min, max]` (inclusive).
+/// Variants not stocked at the location are skipped
+pub fn select_variants_in_range(
+    products: &[ShopifyProduct],
+    inventory_levels: &HashMap<String, HashMap<String, i32>>,
+    location_id: &str,
+    min: i32,
+    max: i32,
+) -> Vec<StockRangeMatch> {
+    let mut matches = Vec::new();
+
+    for product in products {
+        for variant in &product.variants {
+            let Some(inventory_item_id) = variant.inventory_item_id else {
+                continue;
+            };
+            let Some(&quantity) = inventory_levels
+                .get(&inventory_item_id.to_string())
+                .and_then(|levels| levels.get(location_id))
+            else {
+                continue;
+            };
+
+            if (min..=max).contains(&quantity) {
+                matches.push(StockRangeMatch {
+                    product_id: product.id.to_string(),
+                    product_title: product.title.clone(),
+                    variant_title: variant.title.clone(),
+                    inventory_item_id: inventory_item_id.to_string(),
+                    sku: variant.sku.clone(),
+                    quantity,
+                });
+            }
+        }
+    }
+
+    matches
 }
 
-/// Core function that scans all products and optionally updates them
+/// Targeted restocking: variants whose quantity at `location` is between `min` and `max`
+#[tauri::command]
+pub async fn find_products_in_inventory_range(
+    min: i32,
+    max: i32,
+    location: String,
+    client: State<'_, reqwest::Client>,
+    config: State<'_, AppConfig>,
+    locations: State<'_, crate::location::LocationIndex>,
+) -> Result<Vec<StockRangeMatch>, AppError> {
+    if min > max {
+        return Err(format!("Invalid range: min {} is greater than max {}", min, max).into());
+    }
+    let location_id = locations.resolve(&location)?;
+
+    println!(
+        "🔍 Scanning for variants with {}..={} units at {}...",
+        min, max, location
+    );
+
+    let all_products = fetch_all_active_products(&client, &config).await?;
+    let inventory_levels =
+        fetch_levels_for_products(&client, &config, &all_products, |_, _| {}).await?;
+
+    let matches =
+        select_variants_in_range(&all_products, &inventory_levels, &location_id, min, max);
+    println!("✅ Found {} variants in range", matches.len());
+    Ok(matches)
+}
+
+/// Balance of per-location quantities: 1.0 when stock is split evenly, 0.0 when some
+/// location has none. Computed as `locations * min / total`; `None` without any stock.
+pub fn balance_score(quantities: &[i32]) -> Option<f64> {
+    let quantities: Vec<i64> = quantities.iter().map(|q| (*q).max(0) as i64).collect();
+    let total: i64 = quantities.iter().sum();
+    let min = quantities.iter().min()?;
+    if total == 0 {
+        return None;
+    }
+    Some((quantities.len() as i64 * min) as f64 / total as f64)
+}
+
+/// Score every product with stock by how evenly it is spread over `location_ids`,
+/// most skewed first so the best rebalancing candidates come on top
+pub fn compute_balance_scores(
+    products: &[ShopifyProduct],
+    inventory_levels: &HashMap<String, HashMap<String, i32>>,
+    location_ids: &[String],
+) -> Vec<BalanceScore> {
+    let mut scores: Vec<BalanceScore> = products
+        .iter()
+        .filter_map(|product| {
+            let per_location: Vec<i32> = location_ids
+                .iter()
+                .map(|location_id| {
+                    product
+                        .variants
+                        .iter()
+                        .filter_map(|v| v.inventory_item_id)
+                        .filter_map(|id| inventory_levels.get(&id.to_string()))
+                        .filter_map(|levels| levels.get(location_id))
+                        .map(|q| (*q).max(0))
+                        .sum()
+                })
+                .collect();
+
+            balance_score(&per_location).map(|score| BalanceScore {
+                product_id: product.id.to_string(),
+                product_title: product.title.clone(),
+                score,
+            })
+        })
+        .collect();
+
+    scores.sort_by(|a, b| {
+        a.score
+            .total_cmp(&b.score)
+            .then_with(|| a.product_id.cmp(&b.product_id))
+    });
+    scores
+}
+
+/// Rank products by how unevenly their stock is split between the stores
+#[tauri::command]
+pub async fn get_balance_scores(
+    client: State<'_, reqwest::Client>,
+    config: State<'_, AppConfig>,
+) -> Result<Vec<BalanceScore>, AppError> {
+    println!("⚖️ Computing stock balance scores...");
+
+    let all_products = fetch_all_active_products(&client, &config).await?;
+    let inventory_levels =
+        fetch_levels_for_products(&client, &config, &all_products, |_, _| {}).await?;
+    let location_ids: Vec<String> = config
+        .named_locations()
+        .into_iter()
+        .map(|(_, id)| id)
+        .collect();
+
+    let scores = compute_balance_scores(&all_products, &inventory_levels, &location_ids);
+    println!("✅ Scored {} products with stock", scores.len());
+    Ok(scores)
+}
+
+/// Flag variants whose price parses to zero or cannot be parsed at all
+pub fn detect_zero_price_variants(products: &[ShopifyProduct]) -> Vec<ZeroPriceVariant> {
+    products
+        .iter()
+        .flat_map(|product| {
+            product
+                .variants
+                .iter()
+                .filter(|variant| {
+                    variant
+                        .price
+                        .as_deref()
+                        .and_then(|price| price.trim().parse::<f64>().ok())
+                        .is_none_or(|price| price <= 0.0)
+                })
+                .map(move |variant| ZeroPriceVariant {
+                    product_id: product.id.to_string(),
+                    product_title: product.title.clone(),
+                    variant_title: variant.title.clone(),
+                    sku: variant.sku.clone(),
+                })
+        })
+        .collect()
+}
+
+/// Data-quality check: scan the catalog for variants priced at zero or without a price
+#[tauri::command]
+pub async fn find_zero_price_variants(
+    client: State<'_, reqwest::Client>,
+    config: State<'_, AppConfig>,
+) -> Result<Vec<ZeroPriceVariant>, AppError> {
+    println!("🔍 Scanning store for zero-priced variants...");
+
+    let all_products = fetch_all_active_products(&client, &config).await?;
+    let zero_priced = detect_zero_price_variants(&all_products);
+
+    println!(
+        "⚠️ Found {} zero-priced variants across {} products",
+        zero_priced.len(),
+        all_products.len()
+    );
+    Ok(zero_priced)
+}
+
+/// Event emitted while a stock report is being assembled
+pub const STOCK_REPORT_PROGRESS_EVENT: &str = "stock-report-progress";
+
+/// Quote a CSV field when it contains a separator, quote or newline
+pub fn csv_escape(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// CSV of product_title, variant_title, sku, quantity at `location_id` (levels keyed by
+/// inventory_item_id → location_id → available)
+pub fn build_stock_report_csv(
+    products: &[ShopifyProduct],
+    inventory_levels: &HashMap<String, HashMap<String, i32>>,
+    location_id: &str,
+) -> String {
+    let mut csv = String::from("product_title,variant_title,sku,quantity\n");
+
+    for product in products {
+        for variant in &product.variants {
+            let quantity = variant
+                .inventory_item_id
+                .and_then(|id| inventory_levels.get(&id.to_string()))
+                .and_then(|levels| levels.get(location_id))
+                .copied()
+                .unwrap_or(0);
+
+            csv.push_str(&format!(
+                "{},{},{},{}\n",
+                csv_escape(&product.title),
+                csv_escape(&variant.title),
+                csv_escape(variant.sku.as_deref().unwrap_or("")),
+                quantity
+            ));
+        }
+    }
+
+    csv
+}
+
+/// Export current stock at a store as CSV, emitting `stock-report-progress` while
+/// inventory levels are fetched
+#[tauri::command]
+pub async fn export_stock_report_csv(
+    location: String,
+    app: tauri::AppHandle,
+    client: State<'_, reqwest::Client>,
+    config: State<'_, AppConfig>,
+    locations: State<'_, crate::location::LocationIndex>,
+) -> Result<String, AppError> {
+    println!("📄 Exporting stock report for {}...", location);
+    let location_id = locations.resolve(&location)?;
+
+    let all_products = fetch_all_active_products(&client, &config).await?;
+    let inventory_item_ids: Vec<String> = all_products
+        .iter()
+        .flat_map(|p| p.variants.iter())
+        .filter_map(|v| v.inventory_item_id.map(|id| id.to_string()))
+        .collect();
+
+    let mut inventory_levels = HashMap::new();
+    let mut processed = 0;
+    for chunk in config.inventory_level_chunks(&inventory_item_ids) {
+        let levels =
+            crate::inventory::get_inventory_levels(client.clone(), config.clone(), chunk.to_vec())
+                .await?;
+        inventory_levels.extend(levels);
+        processed += chunk.len();
+
+        let progress = json!({ "processed": processed, "total": inventory_item_ids.len() });
+        if let Err(e) = app.emit_event(STOCK_REPORT_PROGRESS_EVENT, progress) {
+            println!("⚠️ {}", e);
+        }
+    }
+
+    let csv = build_stock_report_csv(&all_products, &inventory_levels, &location_id);
+    println!(
+        "✅ Stock report ready: {} variants across {} products",
+        inventory_item_ids.len(),
+        all_products.len()
+    );
+    Ok(csv)
+}
+
+/// Core function that scans all products and optionally updates them, reporting
+/// `stock-scan-progress` to `progress` when given
 async fn scan_and_update_products(
+    client: &reqwest::Client,
     config: &AppConfig,
+    excluded: &HashSet<String>,
     dry_run: bool,
+    progress: Option<&dyn ScanEventEmitter>,
 ) -> Result<StockUpdateResult, String> {
-    let client = reqwest::Client::new();
-
     println!("📍 Shop: {}", config.shop_domain);
     println!("🔧 API Version: {}", config.api_version);
     if dry_run {
@@ -104,14 +919,23 @@ async fn scan_and_update_products(
         println!("⚡ LIVE MODE - Products will be set to draft status");
     }
 
-    // Step 1: Fetch all products with concurrent requests
+    // Step 1: Fetch all active products, one page at a time
     println!("\n📄 Fetching all products...");
-    let all_products = fetch_all_products_concurrent(&client, config).await?;
+    let mut all_products = Vec::new();
+    for_each_product_page(client, config, |products| {
+        all_products.extend(products);
+        emit_scan_progress(progress, "fetching", all_products.len(), None);
+    })
+    .await?;
     println!("✅ Fetched {} total products", all_products.len());
+    let (all_products, duplicates_removed) = dedupe_shopify_products(all_products);
+    if duplicates_removed > 0 {
+        println!("🧹 Dropped {} duplicate products", duplicates_removed);
+    }
 
     // Step 2: Find products with no stock
     println!("\n🔍 Analyzing inventory...");
-    let products_with_no_stock = find_products_with_no_stock(all_products);
+    let products_with_no_stock = find_products_with_no_stock(all_products, excluded);
     println!(
         "🎯 Found {} active products with no stock",
         products_with_no_stock.len()
@@ -121,11 +945,13 @@ async fn scan_and_update_products(
     let mut update_results = Vec::new();
     if !dry_run && !products_with_no_stock.is_empty() {
         println!("\n📝 Updating products to draft status...");
-        update_results = update_products_to_draft(&client, config, &products_with_no_stock).await?;
+        update_results =
+            update_products_to_draft(client, config, &products_with_no_stock, progress).await?;
     }
 
     // Step 4: Generate summary
-    let summary = generate_summary(&products_with_no_stock, &update_results);
+    let mut summary = generate_summary(&products_with_no_stock, &update_results);
+    summary.duplicates_removed = duplicates_removed;
 
     // Step 5: Print results
     print_results(&products_with_no_stock, &update_results, &summary, dry_run);
@@ -137,115 +963,231 @@ async fn scan_and_update_products(
     })
 }
 
-/// Fetch all products using concurrent requests for better performance
-pub async fn fetch_all_products_concurrent(
+/// Core function that scans draft products and optionally sets those with stock back
+/// to active
+async fn scan_and_reactivate_products(
     client: &reqwest::Client,
     config: &AppConfig,
-) -> Result<Vec<ShopifyProduct>, String> {
-    let mut all_products = Vec::new();
-    let mut page_info: Option<String> = None;
-    let mut page_count = 0;
+    excluded: &HashSet<String>,
+    dry_run: bool,
+) -> Result<StockUpdateResult, String> {
+    if dry_run {
+        println!("🧪 DRY RUN MODE - No changes will be made");
+    } else {
+        println!("⚡ LIVE MODE - Products will be set to active status");
+    }
 
-    loop {
-        page_count += 1;
+    println!("\n📄 Fetching draft products...");
+    let mut draft_products = Vec::new();
+    for_each_product_page_with_status(client, config, "draft", |products| {
+        draft_products.extend(products)
+    })
+    .await?;
+    println!("✅ Fetched {} draft products", draft_products.len());
 
-        // Create batch of concurrent requests (3 pages at a time for rate limiting)
-        let mut tasks = Vec::new();
-        let batch_size = 3;
+    let products_with_stock = find_draft_products_with_stock(draft_products, excluded);
+    println!(
+        "🎯 Found {} draft products with stock",
+        products_with_stock.len()
+    );
 
-        for i in 0..batch_size {
-            if i == 0 || page_info.is_some() {
-                let task_client = client.clone();
-                let task_config = config.clone();
-                let task_page_info = page_info.clone();
+    let mut update_results = Vec::new();
+    if !dry_run && !products_with_stock.is_empty() {
+        println!("\n📝 Updating products to active status...");
+        update_results = set_products_status(client, config, &products_with_stock, "active").await;
+    }
 
-                tasks.push(tokio::spawn(async move {
-                    fetch_single_page(&task_client, &task_config, task_page_info).await
-                }));
+    let summary = generate_summary(&products_with_stock, &update_results);
+    println!(
+        "✅ Reactivation scan complete: {} found, {} excluded, {} reactivated, {} failed",
+        summary.total_found,
+        summary.excluded_count,
+        summary.successful_updates,
+        summary.failed_updates
+    );
 
-                // Only first request uses current page_info, others will be None (handled by API)
-                if i == 0 {
-                    page_info = None;
-                }
-            }
-        }
+    Ok(StockUpdateResult {
+        products_found: products_with_stock,
+        update_results,
+        summary,
+    })
+}
 
-        // Execute batch and collect results
-        let results = join_all(tasks).await;
-        let mut next_page_info = None;
-        let mut batch_products = Vec::new();
-
-        for result in results {
-            match result {
-                Ok(Ok((products, page_info_opt))) => {
-                    batch_products.extend(products);
-                    if next_page_info.is_none() {
-                        next_page_info = page_info_opt;
-                    }
-                }
-                Ok(Err(e)) => return Err(e),
-                Err(e) => return Err(format!("Task failed: {}", e)),
-            }
-        }
+/// Core function that scans draft products and optionally archives those with no stock
+/// whose `updated_at` is older than `days_inactive` days
+async fn scan_and_archive_products(
+    client: &reqwest::Client,
+    config: &AppConfig,
+    excluded: &HashSet<String>,
+    days_inactive: i32,
+    dry_run: bool,
+) -> Result<StockUpdateResult, String> {
+    if days_inactive < 1 {
+        return Err(format!(
+            "days_inactive must be at least 1, got {}",
+            days_inactive
+        ));
+    }
+    if dry_run {
+        println!("🧪 DRY RUN MODE - No changes will be made");
+    } else {
+        println!("⚡ LIVE MODE - Products will be set to archived status");
+    }
 
-        if batch_products.is_empty() {
+    println!("\n📄 Fetching draft products...");
+    let mut draft_products = Vec::new();
+    for_each_product_page_with_status(client, config, "draft", |products| {
+        draft_products.extend(products)
+    })
+    .await?;
+    println!("✅ Fetched {} draft products", draft_products.len());
+
+    let cutoff = chrono::Utc::now() - chrono::Duration::days(days_inactive as i64);
+    let stale_products = find_stale_draft_products(draft_products, excluded, cutoff);
+    println!(
+        "🎯 Found {} draft products with no stock not updated since {}",
+        stale_products.len(),
+        cutoff.format("%Y-%m-%d")
+    );
+
+    let mut update_results = Vec::new();
+    if !dry_run && !stale_products.is_empty() {
+        println!("\n📝 Updating products to archived status...");
+        update_results = set_products_status(client, config, &stale_products, "archived").await;
+    }
+
+    let summary = generate_summary(&stale_products, &update_results);
+    println!(
+        "✅ Archive scan complete: {} found, {} excluded, {} archived, {} failed",
+        summary.total_found,
+        summary.excluded_count,
+        summary.successful_updates,
+        summary.failed_updates
+    );
+
+    Ok(StockUpdateResult {
+        products_found: stale_products,
+        update_results,
+        summary,
+    })
+}
+
+/// Fetch all active products (see `for_each_product_page`)
+pub async fn fetch_all_active_products(
+    client: &reqwest::Client,
+    config: &AppConfig,
+) -> Result<Vec<ShopifyProduct>, AppError> {
+    let mut all_products = Vec::new();
+    for_each_product_page(client, config, |products| all_products.extend(products)).await?;
+    Ok(all_products)
+}
+
+/// Fetch every product whether active, draft or archived
+pub async fn fetch_all_products_any_status(
+    client: &reqwest::Client,
+    config: &AppConfig,
+) -> Result<Vec<ShopifyProduct>, AppError> {
+    let mut all_products = Vec::new();
+    for status in ["active", "draft", "archived"] {
+        for_each_product_page_with_status(client, config, status, |products| {
+            all_products.extend(products)
+        })
+        .await?;
+    }
+    Ok(all_products)
+}
+
+/// Fetch all active products page by page, handing each batch to `on_page` as soon as it arrives
+pub async fn for_each_product_page<F>(
+    client: &reqwest::Client,
+    config: &AppConfig,
+    on_page: F,
+) -> Result<usize, AppError>
+where
+    F: FnMut(Vec<ShopifyProduct>),
+{
+    for_each_product_page_with_status(client, config, "active", on_page).await
+}
+
+/// Like `for_each_product_page`, for products with the given status
+pub async fn for_each_product_page_with_status<F>(
+    client: &reqwest::Client,
+    config: &AppConfig,
+    status: &str,
+    on_page: F,
+) -> Result<usize, AppError>
+where
+    F: FnMut(Vec<ShopifyProduct>),
+{
+    follow_product_pages(
+        |page_info| fetch_single_page(client, config, status, page_info),
+        on_page,
+    )
+    .await
+}
+
+/// Walk the Link-header chain: every page is requested with the cursor returned by the
+/// previous one, so each page is fetched exactly once. Pages can't be requested in
+/// parallel because a cursor is only known once the page before it has arrived; the call
+/// budget is respected between pages instead.
+pub async fn follow_product_pages<Fetch, Fut, F>(
+    mut fetch_page: Fetch,
+    mut on_page: F,
+) -> Result<usize, AppError>
+where
+    Fetch: FnMut(Option<String>) -> Fut,
+    Fut: std::future::Future<Output = Result<(Vec<ShopifyProduct>, Option<String>), AppError>>,
+    F: FnMut(Vec<ShopifyProduct>),
+{
+    let mut total_products = 0;
+    let mut page_info: Option<String> = None;
+    let mut page_count = 0;
+
+    loop {
+        page_count += 1;
+        let (products, next_page_info) = fetch_page(page_info.take()).await?;
+
+        if products.is_empty() {
             break;
         }
 
-        println!(
-            "   📄 Page {}: {} products",
-            page_count,
-            batch_products.len()
-        );
-        all_products.extend(batch_products);
+        println!("   📄 Page {}: {} products", page_count, products.len());
+        total_products += products.len();
+        on_page(products);
 
         page_info = next_page_info;
         if page_info.is_none() {
             break;
         }
 
-        // Small delay to respect rate limits
-        sleep(Duration::from_millis(100)).await;
+        // Respect rate limits, backing off only when the call bucket is nearly full
+        throttle_for_call_limit().await;
     }
 
-    Ok(all_products)
+    Ok(total_products)
 }
 
 /// Fetch a single page of products
 async fn fetch_single_page(
     client: &reqwest::Client,
     config: &AppConfig,
+    status: &str,
     page_info: Option<String>,
-) -> Result<(Vec<ShopifyProduct>, Option<String>), String> {
+) -> Result<(Vec<ShopifyProduct>, Option<String>), AppError> {
     let mut url = config.get_api_url("products.json");
-    url.push_str("?limit=250&fields=id,title,status,variants");
+    url.push_str("?limit=250&fields=id,title,status,variants,updated_at");
 
     if let Some(info) = page_info {
         url.push_str(&format!("&page_info={}", info));
     } else {
         // Only add status filter on first request (not when paginating)
-        url.push_str("&status=active");
+        url.push_str(&format!("&status={}", status));
     }
 
-    let response = client
-        .get(&url)
-        .headers(config.get_headers())
-        .send()
-        .await
-        .map_err(|e| format!("Request failed: {}", e))?;
-
-    let status = response.status();
-    if !status.is_success() {
-        let error_text = response
-            .text()
-            .await
-            .unwrap_or_else(|_| "Unable to read error".to_string());
-        return Err(format!(
-            "HTTP error! status: {} - {} | Response: {}",
-            status,
-            status.canonical_reason().unwrap_or("Unknown"),
-            error_text
-        ));
+    let response = send_with_retry(client.get(&url).headers(config.get_headers()), &config).await?;
+
+    if !response.status().is_success() {
+        return Err(AppError::from_response(response).await);
     }
 
     // Extract pagination info from Link header
@@ -254,19 +1196,19 @@ async fn fetch_single_page(
     let data: ProductsResponse = response
         .json()
         .await
-        .map_err(|e| format!("Failed to parse JSON: {}", e))?;
+        .map_err(|e| AppError::Parse(format!("Failed to parse JSON: {}", e)))?;
 
     Ok((data.products, next_page_info))
 }
 
 /// Extract next page info from Link header
-fn extract_next_page_info(response: &reqwest::Response) -> Option<String> {
-    response
-        .headers()
-        .get("Link")?
-        .to_str()
-        .ok()?
-        .split(',')
+pub(crate) fn extract_next_page_info(response: &reqwest::Response) -> Option<String> {
+    next_page_info_from_link(response.headers().get("Link")?.to_str().ok()?)
+}
+
+/// Parse the `page_info` cursor of the `rel="next"` entry of a Shopify `Link` header
+pub fn next_page_info_from_link(link: &str) -> Option<String> {
+    link.split(',')
         .find(|link| link.contains("rel=\"next\""))?
         .split('?')
         .nth(1)?
@@ -278,8 +1220,11 @@ fn extract_next_page_info(response: &reqwest::Response) -> Option<String> {
         .map(|s| s.to_string())
 }
 
-/// Find products that are active but have no stock
-pub fn find_products_with_no_stock(products: Vec<ShopifyProduct>) -> Vec<ProductNoStock> {
+/// Find products that are active but have no stock, flagging those in `excluded`
+pub fn find_products_with_no_stock(
+    products: Vec<ShopifyProduct>,
+    excluded: &HashSet<String>,
+) -> Vec<ProductNoStock> {
     products
         .into_iter()
         .filter_map(|product| {
@@ -290,7 +1235,7 @@ pub fn find_products_with_no_stock(products: Vec<ShopifyProduct>) -> Vec<Product
                     .any(|variant| variant.inventory_quantity > 0);
 
                 if !has_stock {
-                    let is_excluded = EXCLUDED_IDS_SET.contains(&product.id.to_string());
+                    let is_excluded = excluded.contains(&product.id.to_string());
                     return Some(ProductNoStock {
                         id: product.id.to_string(),
                         title: product.title,
@@ -304,13 +1249,15 @@ pub fn find_products_with_no_stock(products: Vec<ShopifyProduct>) -> Vec<Product
         .collect()
 }
 
-/// Update products to draft status
+/// Update products to draft status, reporting each one to `progress` when given
 pub async fn update_products_to_draft(
     client: &reqwest::Client,
     config: &AppConfig,
     products: &[ProductNoStock],
+    progress: Option<&dyn ScanEventEmitter>,
 ) -> Result<Vec<UpdateResult>, String> {
     let mut results = Vec::new();
+    let mut processed = HashSet::new();
 
     for (index, product) in products.iter().enumerate() {
         println!(
@@ -321,6 +1268,12 @@ pub async fn update_products_to_draft(
             product.id
         );
 
+        if !processed.insert(product.id.clone()) {
+            println!("   ⏭️ Already processed in this run - Skipping");
+            emit_scan_progress(progress, "updating", index + 1, Some(products.len()));
+            continue;
+        }
+
         if product.is_excluded {
             println!("   🛡️ EXCLUDED - Skipping update");
             results.push(UpdateResult {
@@ -328,18 +1281,41 @@ pub async fn update_products_to_draft(
                 title: product.title.clone(),
                 success: true, // Consider excluded as "success" (intentionally skipped)
                 error: Some("Excluded from updates".to_string()),
+                skipped: true,
             });
+            emit_scan_progress(progress, "updating", index + 1, Some(products.len()));
             continue;
         }
 
-        match update_single_product_status(client, config, &product.id, "draft").await {
-            Ok(_) => {
+        // Re-check stock right before drafting: the product may have been restocked
+        // since the scan fetched it
+        let outcome = crate::inventory::apply_status_if_zero(
+            &product.id,
+            "draft",
+            || product_has_zero_inventory(client, config, &product.id),
+            || update_single_product_status(client, config, &product.id, "draft"),
+        )
+        .await;
+
+        match outcome {
+            Ok(result) if result.applied => {
                 println!("   ✅ Successfully set to draft");
                 results.push(UpdateResult {
                     product_id: product.id.clone(),
                     title: product.title.clone(),
                     success: true,
                     error: None,
+                    skipped: false,
+                });
+            }
+            Ok(result) => {
+                println!("   ⏭️ Skipped - inventory changed since the scan");
+                results.push(UpdateResult {
+                    product_id: product.id.clone(),
+                    title: product.title.clone(),
+                    success: true, // Intentionally skipped, like excluded products
+                    error: Some(result.message),
+                    skipped: true,
                 });
             }
             Err(e) => {
@@ -348,50 +1324,192 @@ pub async fn update_products_to_draft(
                     product_id: product.id.clone(),
                     title: product.title.clone(),
                     success: false,
-                    error: Some(e),
+                    error: Some(e.to_string()),
+                    skipped: false,
                 });
             }
         }
+        emit_scan_progress(progress, "updating", index + 1, Some(products.len()));
 
         // Rate limiting delay
         if index < products.len() - 1 {
-            sleep(Duration::from_millis(250)).await;
+            throttle_for_call_limit().await;
         }
     }
 
     Ok(results)
 }
 
+/// Drop repeated products (same id), keeping the first; returns how many were dropped
+pub fn dedupe_shopify_products(products: Vec<ShopifyProduct>) -> (Vec<ShopifyProduct>, usize) {
+    let before = products.len();
+    let mut seen = HashSet::new();
+    let unique: Vec<ShopifyProduct> = products
+        .into_iter()
+        .filter(|product| seen.insert(product.id))
+        .collect();
+    let removed = before - unique.len();
+    (unique, removed)
+}
+
+/// Find draft products with at least one variant in stock, flagging those in `excluded`
+pub fn find_draft_products_with_stock(
+    products: Vec<ShopifyProduct>,
+    excluded: &HashSet<String>,
+) -> Vec<ProductNoStock> {
+    products
+        .into_iter()
+        .filter(|product| product.status == "draft")
+        .filter(|product| product.variants.iter().any(|v| v.inventory_quantity > 0))
+        .map(|product| ProductNoStock {
+            is_excluded: excluded.contains(&product.id.to_string()),
+            id: product.id.to_string(),
+            title: product.title,
+            status: product.status,
+        })
+        .collect()
+}
+
+/// Find draft products with no variant in stock whose `updated_at` is before `cutoff`,
+/// flagging those in `excluded`. Products without a parseable `updated_at` are left out
+pub fn find_stale_draft_products(
+    products: Vec<ShopifyProduct>,
+    excluded: &HashSet<String>,
+    cutoff: chrono::DateTime<chrono::Utc>,
+) -> Vec<ProductNoStock> {
+    products
+        .into_iter()
+        .filter(|product| product.status == "draft")
+        .filter(|product| product.variants.iter().all(|v| v.inventory_quantity <= 0))
+        .filter(|product| {
+            product
+                .updated_at
+                .as_deref()
+                .and_then(|updated| chrono::DateTime::parse_from_rfc3339(updated).ok())
+                .is_some_and(|updated| updated < cutoff)
+        })
+        .map(|product| ProductNoStock {
+            is_excluded: excluded.contains(&product.id.to_string()),
+            id: product.id.to_string(),
+            title: product.title,
+            status: product.status,
+        })
+        .collect()
+}
+
+/// Set each product to `status`, skipping excluded ones
+async fn set_products_status(
+    client: &reqwest::Client,
+    config: &AppConfig,
+    products: &[ProductNoStock],
+    status: &str,
+) -> Vec<UpdateResult> {
+    let mut results = Vec::new();
+
+    for (index, product) in products.iter().enumerate() {
+        println!(
+            "   📝 ({}/{}) Setting to {}: \"{}\" (ID: {})",
+            index + 1,
+            products.len(),
+            status,
+            product.title,
+            product.id
+        );
+
+        if product.is_excluded {
+            println!("   🛡️ EXCLUDED - Skipping update");
+            results.push(UpdateResult {
+                product_id: product.id.clone(),
+                title: product.title.clone(),
+                success: true, // Consider excluded as "success" (intentionally skipped)
+                error: Some("Excluded from updates".to_string()),
+                skipped: true,
+            });
+            continue;
+        }
+
+        let outcome = update_single_product_status(client, config, &product.id, status).await;
+        if let Err(e) = &outcome {
+            println!("   ❌ Failed to update: {}", e);
+        } else {
+            println!("   ✅ Successfully set to {}", status);
+        }
+        results.push(UpdateResult {
+            product_id: product.id.clone(),
+            title: product.title.clone(),
+            success: outcome.is_ok(),
+            error: outcome.err().map(|e| e.to_string()),
+            skipped: false,
+        });
+
+        // Rate limiting delay
+        if index < products.len() - 1 {
+            throttle_for_call_limit().await;
+        }
+    }
+
+    results
+}
+
+/// Fresh check that no variant of the product has stock (variant quantities are
+/// totals across all locations)
+async fn product_has_zero_inventory(
+    client: &reqwest::Client,
+    config: &AppConfig,
+    product_id: &str,
+) -> Result<bool, AppError> {
+    let url = config.get_api_url(&format!("products/{}.json?fields=id,variants", product_id));
+
+    let response = send_with_retry(client.get(&url).headers(config.get_headers()), &config).await?;
+
+    if !response.status().is_success() {
+        return Err(AppError::from_response(response).await);
+    }
+
+    let data: serde_json::Value = response
+        .json()
+        .await
+        .map_err(|e| AppError::Parse(format!("Failed to parse JSON: {}", e)))?;
+
+    let has_stock = data["product"]["variants"]
+        .as_array()
+        .map(|variants| {
+            variants
+                .iter()
+                .any(|v| v["inventory_quantity"].as_i64().unwrap_or(0) > 0)
+        })
+        .unwrap_or(false);
+
+    Ok(!has_stock)
+}
+
 /// Update a single product's status
 async fn update_single_product_status(
     client: &reqwest::Client,
     config: &AppConfig,
     product_id: &str,
     status: &str,
-) -> Result<(), String> {
+) -> Result<(), AppError> {
     let url = config.get_api_url(&format!("products/{}.json", product_id));
 
     let request_body = json!({
         "product": {
-            "id": product_id.parse::<u64>().map_err(|e| format!("Invalid product ID: {}", e))?,
+            "id": product_id.parse::<u64>().map_err(|e| AppError::Other(format!("Invalid product ID: {}", e)))?,
             "status": status
         }
     });
 
-    let response = client
-        .put(&url)
-        .headers(config.get_headers())
-        .json(&request_body)
-        .send()
-        .await
-        .map_err(|e| format!("Request failed: {}", e))?;
+    let response = send_with_retry(
+        client
+            .put(&url)
+            .headers(config.get_headers())
+            .json(&request_body),
+        &config,
+    )
+    .await?;
 
     if !response.status().is_success() {
-        return Err(format!(
-            "HTTP error! status: {} - {}",
-            response.status(),
-            response.status().canonical_reason().unwrap_or("Unknown")
-        ));
+        return Err(AppError::from_response(response).await);
     }
 
     Ok(())
@@ -406,7 +1524,7 @@ pub fn generate_summary(
     let eligible_count = products.len() - excluded_count;
     let successful_updates = update_results
         .iter()
-        .filter(|r| r.success && r.error.is_none())
+        .filter(|r| r.success && !r.skipped)
         .count();
     let failed_updates = update_results.iter().filter(|r| !r.success).count();
 
@@ -416,6 +1534,7 @@ pub fn generate_summary(
         eligible_count,
         successful_updates,
         failed_updates,
+        duplicates_removed: 0,
     }
 }
 
@@ -436,6 +1555,12 @@ pub fn print_results(
             "📦 Found {} active products with no stock",
             summary.total_found
         );
+        if summary.duplicates_removed > 0 {
+            println!(
+                "🧹 {} duplicate products were ignored",
+                summary.duplicates_removed
+            );
+        }
 
         if dry_run {
             println!("\n🧪 DRY RUN - Products that would be affected:");
@@ -1,4 +1,6 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tracing::warn;
 
 // ============================================================================
 // DATA STRUCTURES
@@ -12,6 +14,14 @@ pub struct ProductVariant {
     pub inventory_quantity: i32,
     pub price: String,
     pub sku: Option<String>,
+    #[serde(default)]
+    pub barcode: Option<String>,
+    #[serde(default)]
+    pub locations: HashMap<String, i32>, // Location name → available, when fetched
+    #[serde(default)]
+    pub compare_at_price: Option<String>,
+    #[serde(default)]
+    pub cost: Option<String>, // Unit cost of the inventory item; only fetched via GraphQL
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -25,6 +35,14 @@ pub struct Product {
     pub variants: Vec<ProductVariant>,
     pub total_inventory: i32,
     pub locations: std::collections::HashMap<String, i32>,
+    #[serde(default)]
+    pub warnings: Vec<String>, // Data problems staff should know about (e.g. unusable variants)
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub product_type: String,
+    #[serde(default)]
+    pub vendor: String,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -40,6 +58,362 @@ pub struct StatusResponse {
     pub message: String,
 }
 
+// ============================================================================
+// ERRORS
+// ============================================================================
+
+/// Error returned by commands. Serialized as `{ kind, message }` (plus `status` for
+/// Shopify API errors) so the frontend can branch on `kind` and keep showing `message`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AppError {
+    /// The request never got a response (connection, timeout, DNS)
+    Network(String),
+    /// Shopify answered with a non-success status
+    ShopifyApi {
+        status: u16,
+        body: String,
+    },
+    /// Firestore/Firebase failures
+    Firebase(String),
+    NotFound(String),
+    /// The data changed since the caller last read it (e.g. someone else edited a level)
+    Conflict(String),
+    /// A response or input could not be parsed
+    Parse(String),
+    Config(String),
+    /// Anything not classified above (validation messages, business rules)
+    Other(String),
+}
+
+impl AppError {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            AppError::Network(_) => "network",
+            AppError::ShopifyApi { .. } => "shopify_api",
+            AppError::Firebase(_) => "firebase",
+            AppError::NotFound(_) => "not_found",
+            AppError::Conflict(_) => "conflict",
+            AppError::Parse(_) => "parse",
+            AppError::Config(_) => "config",
+            AppError::Other(_) => "other",
+        }
+    }
+
+    /// Build a `Network` error from a failed send; timeouts report the `timeout_secs` the
+    /// client was built with
+    pub fn from_request_error(e: reqwest::Error, timeout_secs: u64) -> Self {
+        if e.is_timeout() {
+            AppError::Network(format!("Request timed out after {} seconds", timeout_secs))
+        } else {
+            AppError::from(e)
+        }
+    }
+
+    /// Build a `ShopifyApi` error from a non-success response
+    pub async fn from_response(response: reqwest::Response) -> Self {
+        let status = response.status().as_u16();
+        let body = response.text().await.unwrap_or_default();
+        AppError::ShopifyApi { status, body }
+    }
+}
+
+impl std::fmt::Display for AppError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AppError::ShopifyApi { status, body } => {
+                write!(f, "Shopify API error ({}): {}", status, body)
+            }
+            AppError::Network(message)
+            | AppError::Firebase(message)
+            | AppError::NotFound(message)
+            | AppError::Conflict(message)
+            | AppError::Parse(message)
+            | AppError::Config(message)
+            | AppError::Other(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for AppError {}
+
+impl Serialize for AppError {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        #[derive(Serialize)]
+        struct SerializedError<'a> {
+            kind: &'a str,
+            message: String,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            status: Option<u16>,
+        }
+
+        SerializedError {
+            kind: self.kind(),
+            message: self.to_string(),
+            status: match self {
+                AppError::ShopifyApi { status, .. } => Some(*status),
+                _ => None,
+            },
+        }
+        .serialize(serializer)
+    }
+}
+
+impl From<String> for AppError {
+    fn from(message: String) -> Self {
+        AppError::Other(message)
+    }
+}
+
+impl From<&str> for AppError {
+    fn from(message: &str) -> Self {
+        AppError::Other(message.to_string())
+    }
+}
+
+/// Prefer `AppConfig::request_error`, which can say how long a timed-out request waited
+impl From<reqwest::Error> for AppError {
+    fn from(e: reqwest::Error) -> Self {
+        if e.is_timeout() {
+            AppError::Network("Request timed out".to_string())
+        } else {
+            AppError::Network(format!("Request failed: {}", e))
+        }
+    }
+}
+
+impl From<serde_json::Error> for AppError {
+    fn from(e: serde_json::Error) -> Self {
+        AppError::Parse(format!("Failed to parse JSON: {}", e))
+    }
+}
+
+/// Lets String-returning helpers keep using `?` on functions that return `AppError`
+impl From<AppError> for String {
+    fn from(e: AppError) -> Self {
+        e.to_string()
+    }
+}
+
+// ============================================================================
+// FORMATTING HELPERS
+// ============================================================================
+
+/// Format a Shopify price string ("29.9") in the shop currency ("€ 29,90" for EUR)
+pub fn format_price(amount: &str, currency: &str) -> String {
+    let value = amount.trim().parse::<f64>().unwrap_or(0.0);
+    let formatted = format!("{:.2}", value).replace('.', ",");
+
+    let symbol = match currency.to_uppercase().as_str() {
+        "EUR" => "€".to_string(),
+        "USD" => "$".to_string(),
+        "GBP" => "£".to_string(),
+        "CHF" => "CHF".to_string(),
+        other => other.to_string(),
+    };
+
+    format!("{} {}", symbol, formatted)
+}
+
+/// Today's date (YYYY-MM-DD) in the given IANA timezone, falling back to the local clock
+pub fn today_in_timezone(timezone: Option<&str>) -> String {
+    match timezone.and_then(|tz| tz.parse::<chrono_tz::Tz>().ok()) {
+        Some(tz) => chrono::Utc::now()
+            .with_timezone(&tz)
+            .format("%Y-%m-%d")
+            .to_string(),
+        None => chrono::Local::now().format("%Y-%m-%d").to_string(),
+    }
+}
+
+// ============================================================================
+// HTTP CLIENT
+// ============================================================================
+
+/// Build the HTTP client shared by all commands so connections and TLS sessions are reused.
+/// Every request fails after `timeout_secs` instead of hanging on a stalled connection.
+pub fn build_http_client(timeout_secs: u64) -> reqwest::Client {
+    reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(timeout_secs))
+        .connect_timeout(std::time::Duration::from_secs(10))
+        .pool_idle_timeout(std::time::Duration::from_secs(90))
+        .pool_max_idle_per_host(8)
+        .build()
+        .expect("Failed to build HTTP client")
+}
+
+// ============================================================================
+// HTTP RETRY
+// ============================================================================
+
+/// Statuses worth retrying: rate limiting, which Shopify rejects before doing anything,
+/// and transient gateway errors, after which the request may or may not have been applied
+/// and so is only repeated when doing it twice is harmless
+pub fn is_retryable_status(status: reqwest::StatusCode, idempotent: bool) -> bool {
+    match status.as_u16() {
+        429 => true,
+        502 | 503 => idempotent,
+        _ => false,
+    }
+}
+
+/// Whether a failed send can be repeated: connection errors mean nothing reached the
+/// server, while a timeout may have come after the request was applied
+pub fn is_retryable_error(is_connect: bool, is_timeout: bool, idempotent: bool) -> bool {
+    is_connect || (is_timeout && idempotent)
+}
+
+/// Parse a `Retry-After` header given in seconds (Shopify sends e.g. "2.0")
+pub fn parse_retry_after(value: &str) -> Option<std::time::Duration> {
+    value
+        .trim()
+        .parse::<f64>()
+        .ok()
+        .filter(|secs| secs.is_finite() && *secs >= 0.0)
+        .map(std::time::Duration::from_secs_f64)
+}
+
+/// Exponential backoff: base * 2^attempt, plus up to half the delay as jitter
+pub fn backoff_delay(attempt: u32, base_delay_ms: u64, jitter_seed: u64) -> std::time::Duration {
+    let delay = base_delay_ms.saturating_mul(1u64 << attempt.min(16));
+    let jitter = jitter_seed % (delay / 2 + 1);
+    std::time::Duration::from_millis(delay + jitter)
+}
+
+/// Last `X-Shopify-Shop-Api-Call-Limit` seen by `send_with_retry` as (used, total, when)
+static LAST_CALL_LIMIT: std::sync::Mutex<Option<(u32, u32, std::time::Instant)>> =
+    std::sync::Mutex::new(None);
+
+/// Shopify's REST leaky bucket drains two calls per second
+const CALL_LIMIT_LEAK_MS: u64 = 500;
+
+/// Parse an `X-Shopify-Shop-Api-Call-Limit` header value like "32/40" into (used, total)
+pub fn parse_call_limit(header: &str) -> Option<(u32, u32)> {
+    let (used, total) = header.trim().split_once('/')?;
+    let used = used.trim().parse().ok()?;
+    let total: u32 = total.trim().parse().ok()?;
+    (total > 0).then_some((used, total))
+}
+
+/// How long to wait before the next call given the bucket state: nothing while below
+/// 80% of the cap, then long enough for the calls above that mark to drain
+pub fn call_limit_delay(used: u32, total: u32) -> std::time::Duration {
+    let threshold = total.saturating_mul(8) / 10;
+    let excess = used.saturating_sub(threshold) as u64;
+    std::time::Duration::from_millis(excess * CALL_LIMIT_LEAK_MS)
+}
+
+/// Like `call_limit_delay` for a reading taken `elapsed` ago, during which the bucket has
+/// kept draining
+pub fn call_limit_delay_since(
+    used: u32,
+    total: u32,
+    elapsed: std::time::Duration,
+) -> std::time::Duration {
+    let drained = (elapsed.as_millis() / CALL_LIMIT_LEAK_MS as u128).min(u32::MAX as u128) as u32;
+    call_limit_delay(used.saturating_sub(drained), total)
+}
+
+fn record_call_limit(response: &reqwest::Response) {
+    if let Some((used, total)) = response
+        .headers()
+        .get("X-Shopify-Shop-Api-Call-Limit")
+        .and_then(|v| v.to_str().ok())
+        .and_then(parse_call_limit)
+    {
+        if let Ok(mut last) = LAST_CALL_LIMIT.lock() {
+            *last = Some((used, total, std::time::Instant::now()));
+        }
+    }
+}
+
+/// Pause before the next Shopify REST call according to the last call-limit header seen;
+/// returns immediately when the bucket has room
+pub async fn throttle_for_call_limit() {
+    let Some((used, total, seen_at)) = LAST_CALL_LIMIT.lock().ok().and_then(|last| *last) else {
+        return;
+    };
+    let delay = call_limit_delay_since(used, total, seen_at.elapsed());
+    if !delay.is_zero() {
+        warn!(
+            "🐢 API call limit at {}/{}, waiting {}ms",
+            used,
+            total,
+            delay.as_millis()
+        );
+        tokio::time::sleep(delay).await;
+    }
+}
+
+/// Send a request, retrying on 429 and connection errors up to `max_retries` times, and
+/// also on 502/503 and timeouts for idempotent methods. POSTs such as inventory
+/// adjustments are never repeated once they may have reached Shopify. `Retry-After` is
+/// honoured when present, otherwise exponential backoff is used.
+pub async fn send_with_retry(
+    req_builder: reqwest::RequestBuilder,
+    config: &AppConfig,
+) -> Result<reqwest::Response, AppError> {
+    let (max_retries, base_delay_ms) = (config.max_retries, config.retry_base_delay_ms);
+    let mut attempt = 0;
+    let idempotent = req_builder
+        .try_clone()
+        .and_then(|builder| builder.build().ok())
+        .is_some_and(|request| request.method().is_idempotent());
+
+    loop {
+        // Bodies built with .json() can always be cloned; anything else is sent once
+        let Some(request) = req_builder.try_clone() else {
+            return req_builder
+                .send()
+                .await
+                .map_err(|e| config.request_error(e));
+        };
+
+        let jitter_seed = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.subsec_nanos() as u64 / 1_000_000)
+            .unwrap_or(0);
+
+        let delay = match request.send().await {
+            Ok(response)
+                if is_retryable_status(response.status(), idempotent) && attempt < max_retries =>
+            {
+                let retry_after = response
+                    .headers()
+                    .get("Retry-After")
+                    .and_then(|v| v.to_str().ok())
+                    .and_then(parse_retry_after);
+                warn!(
+                    "⏳ Shopify responded {}, retrying ({}/{})",
+                    response.status(),
+                    attempt + 1,
+                    max_retries
+                );
+                retry_after.unwrap_or_else(|| backoff_delay(attempt, base_delay_ms, jitter_seed))
+            }
+            Ok(response) => {
+                record_call_limit(&response);
+                return Ok(response);
+            }
+            Err(e)
+                if is_retryable_error(e.is_connect(), e.is_timeout(), idempotent)
+                    && attempt < max_retries =>
+            {
+                warn!(
+                    "⏳ Request error ({}), retrying ({}/{})",
+                    e,
+                    attempt + 1,
+                    max_retries
+                );
+                backoff_delay(attempt, base_delay_ms, jitter_seed)
+            }
+            Err(e) => return Err(config.request_error(e)),
+        };
+
+        tokio::time::sleep(delay).await;
+        attempt += 1;
+    }
+}
+
 // ============================================================================
 // CONFIGURATION
 // ============================================================================
@@ -54,6 +428,8 @@ pub struct AppConfig {
     pub api_version: String,
     pub primary_location: String,
     pub secondary_location: String,
+    /// Further stores as (name, location id) pairs, from EXTRA_LOCATIONS
+    pub extra_locations: Vec<(String, String)>,
 
     // Firebase Configuration
     pub firebase_api_key: String,
@@ -63,9 +439,104 @@ pub struct AppConfig {
     pub firebase_messaging_sender_id: String,
     pub firebase_app_id: String,
     pub firebase_measurement_id: String,
+    /// Path to a service-account JSON key; when set, Firestore calls use OAuth2 bearer
+    /// tokens instead of the API key
+    pub firebase_service_account: Option<String>,
 
     // App Configuration
     pub version: String,
+
+    // Bulk Operations
+    pub bulk_concurrency: usize,
+    pub bulk_chunk_size: usize,
+
+    // Retry Policy
+    pub max_retries: u32,
+    pub retry_base_delay_ms: u64,
+
+    // Caching
+    pub sku_cache_ttl_secs: u64,
+
+    // Whole-request timeout for Shopify and Firestore calls
+    pub request_timeout_secs: u64,
+
+    // Longest modification-history window, in days, to avoid unbounded Firestore scans
+    pub history_max_days: i32,
+
+    // Where the values above were loaded from
+    pub config_source: ConfigSource,
+}
+
+/// Which loading path produced an AppConfig
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ConfigSource {
+    /// Read at runtime from the process environment / `.env` (DEV_ENV=true)
+    RuntimeEnv,
+    /// Embedded in the binary at build time (production)
+    CompileTime,
+}
+
+/// Shopify Admin API version used when SHOPIFY_API_VERSION is not set
+pub const DEFAULT_API_VERSION: &str = "2025-01";
+
+/// Default number of concurrent requests in bulk operations
+pub const DEFAULT_BULK_CONCURRENCY: usize = 4;
+/// Default number of ids per request in bulk operations
+pub const DEFAULT_BULK_CHUNK_SIZE: usize = 50;
+/// Most inventory item ids Shopify accepts in one `inventory_levels.json` request
+pub const INVENTORY_LEVELS_MAX_IDS: usize = 50;
+
+/// Default number of retries for rate-limited or transient Shopify failures
+pub const DEFAULT_MAX_RETRIES: u32 = 3;
+/// Default base delay for exponential backoff between retries
+pub const DEFAULT_RETRY_BASE_DELAY_MS: u64 = 500;
+
+/// Default lifetime of a cached SKU → inventory item lookup
+pub const DEFAULT_SKU_CACHE_TTL_SECS: u64 = 600;
+
+/// Default time a request may take before it is abandoned
+pub const DEFAULT_REQUEST_TIMEOUT_SECS: u64 = 30;
+
+/// Default longest window accepted by the modification history
+pub const DEFAULT_HISTORY_MAX_DAYS: i32 = 365;
+
+/// Parse a positive size setting, falling back to `default` when missing or invalid
+fn parse_size_setting(value: Option<&str>, default: usize) -> usize {
+    value
+        .and_then(|v| v.trim().parse::<usize>().ok())
+        .filter(|v| *v > 0)
+        .unwrap_or(default)
+}
+
+/// Parse `EXTRA_LOCATIONS`, a comma-separated list of `Name:location_id` pairs such as
+/// `Castelfranco:123456,Padova:789012`. Malformed entries are kept for `validate` to report.
+pub fn parse_extra_locations(value: Option<&str>) -> Vec<(String, String)> {
+    value
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            let (name, id) = entry.split_once(':').unwrap_or((entry, ""));
+            (name.trim().to_string(), id.trim().to_string())
+        })
+        .collect()
+}
+
+/// Whether `version` has the `YYYY-MM` shape of a Shopify API version (or is "unstable")
+fn is_api_version_shape(version: &str) -> bool {
+    if version == "unstable" {
+        return true;
+    }
+    let Some((year, month)) = version.split_once('-') else {
+        return false;
+    };
+    year.len() == 4
+        && month.len() == 2
+        && year.chars().all(|c| c.is_ascii_digit())
+        && month
+            .parse::<u32>()
+            .is_ok_and(|month| (1..=12).contains(&month))
 }
 
 impl AppConfig {
@@ -75,7 +546,7 @@ impl AppConfig {
 
         // Check if we're in development mode
         let is_dev = std::env::var("DEV_ENV")
-            .unwrap_or_else(|_| "3.2.0".to_string())
+            .unwrap_or_else(|_| "false".to_string())
             .parse::<bool>()
             .unwrap_or(false);
 
@@ -89,41 +560,74 @@ impl AppConfig {
     }
 
     /// Load configuration from runtime environment variables (development)
-    fn from_runtime_env() -> Result<Self, String> {
+    pub fn from_runtime_env() -> Result<Self, String> {
+        Self::from_lookup("", |key| std::env::var(key).ok())
+    }
+
+    /// Build a configuration from a key lookup; `prefix` is only used in error messages
+    fn from_lookup<F>(prefix: &str, get: F) -> Result<Self, String>
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        let required = |key: &str| {
+            get(key).ok_or_else(|| format!("{}{} must be set in .env file", prefix, key))
+        };
+
         // Shopify Configuration
-        let shop_domain = std::env::var("SHOPIFY_SHOP_DOMAIN")
-            .map_err(|_| "SHOPIFY_SHOP_DOMAIN must be set in .env file")?;
-        let access_token = std::env::var("SHOPIFY_ACCESS_TOKEN")
-            .map_err(|_| "SHOPIFY_ACCESS_TOKEN must be set in .env file")?;
-        let api_key = std::env::var("SHOPIFY_API_KEY")
-            .map_err(|_| "SHOPIFY_API_KEY must be set in .env file")?;
-        let api_secret = std::env::var("SHOPIFY_API_SECRET_KEY")
-            .map_err(|_| "SHOPIFY_API_SECRET_KEY must be set in .env file")?;
+        let shop_domain = required("SHOPIFY_SHOP_DOMAIN")?;
+        let access_token = required("SHOPIFY_ACCESS_TOKEN")?;
+        let api_key = required("SHOPIFY_API_KEY")?;
+        let api_secret = required("SHOPIFY_API_SECRET_KEY")?;
         let api_version =
-            std::env::var("SHOPIFY_API_VERSION").unwrap_or_else(|_| "3.2.0".to_string());
-        let primary_location = std::env::var("LOCATION_TREVISO")
-            .map_err(|_| "LOCATION_TREVISO must be set in .env file")?;
-        let secondary_location = std::env::var("LOCATION_MOGLIANO")
-            .map_err(|_| "LOCATION_MOGLIANO must be set in .env file")?;
+            get("SHOPIFY_API_VERSION").unwrap_or_else(|| DEFAULT_API_VERSION.to_string());
+        let primary_location = required("LOCATION_TREVISO")?;
+        let secondary_location = required("LOCATION_MOGLIANO")?;
+        let extra_locations = parse_extra_locations(get("EXTRA_LOCATIONS").as_deref());
 
         // Firebase Configuration
-        let firebase_api_key = std::env::var("FIREBASE_API_KEY")
-            .map_err(|_| "FIREBASE_API_KEY must be set in .env file")?;
-        let firebase_auth_domain = std::env::var("FIREBASE_AUTH_DOMAIN")
-            .map_err(|_| "FIREBASE_AUTH_DOMAIN must be set in .env file")?;
-        let firebase_project_id = std::env::var("FIREBASE_PROJECT_ID")
-            .map_err(|_| "FIREBASE_PROJECT_ID must be set in .env file")?;
-        let firebase_storage_bucket = std::env::var("FIREBASE_STORAGE_BUCKET")
-            .map_err(|_| "FIREBASE_STORAGE_BUCKET must be set in .env file")?;
-        let firebase_messaging_sender_id = std::env::var("FIREBASE_MESSAGING_SENDER_ID")
-            .map_err(|_| "FIREBASE_MESSAGING_SENDER_ID must be set in .env file")?;
-        let firebase_app_id = std::env::var("FIREBASE_APP_ID")
-            .map_err(|_| "FIREBASE_APP_ID must be set in .env file")?;
-        let firebase_measurement_id = std::env::var("FIREBASE_MEASUREMENT_ID")
-            .map_err(|_| "FIREBASE_MEASUREMENT_ID must be set in .env file")?;
+        let firebase_api_key = required("FIREBASE_API_KEY")?;
+        let firebase_auth_domain = required("FIREBASE_AUTH_DOMAIN")?;
+        let firebase_project_id = required("FIREBASE_PROJECT_ID")?;
+        let firebase_storage_bucket = required("FIREBASE_STORAGE_BUCKET")?;
+        let firebase_messaging_sender_id = required("FIREBASE_MESSAGING_SENDER_ID")?;
+        let firebase_app_id = required("FIREBASE_APP_ID")?;
+        let firebase_measurement_id = required("FIREBASE_MEASUREMENT_ID")?;
+        let firebase_service_account =
+            get("FIREBASE_SERVICE_ACCOUNT").filter(|path| !path.trim().is_empty());
 
         // App Configuration
-        let version = std::env::var("VERSION").unwrap_or_else(|_| "3.2.0".to_string());
+        let version = get("VERSION").unwrap_or_else(|| "3.2.0".to_string());
+
+        // Bulk Operations
+        let bulk_concurrency =
+            parse_size_setting(get("BULK_CONCURRENCY").as_deref(), DEFAULT_BULK_CONCURRENCY);
+        let bulk_chunk_size =
+            parse_size_setting(get("BULK_CHUNK_SIZE").as_deref(), DEFAULT_BULK_CHUNK_SIZE);
+
+        // Retry Policy
+        let max_retries = get("SHOPIFY_MAX_RETRIES")
+            .and_then(|v| v.trim().parse().ok())
+            .unwrap_or(DEFAULT_MAX_RETRIES);
+        let retry_base_delay_ms = get("SHOPIFY_RETRY_BASE_DELAY_MS")
+            .and_then(|v| v.trim().parse().ok())
+            .unwrap_or(DEFAULT_RETRY_BASE_DELAY_MS);
+
+        // Caching
+        let sku_cache_ttl_secs = get("SKU_CACHE_TTL_SECS")
+            .and_then(|v| v.trim().parse().ok())
+            .unwrap_or(DEFAULT_SKU_CACHE_TTL_SECS);
+
+        // Timeouts
+        let request_timeout_secs = get("REQUEST_TIMEOUT_SECS")
+            .and_then(|v| v.trim().parse().ok())
+            .filter(|secs| *secs > 0)
+            .unwrap_or(DEFAULT_REQUEST_TIMEOUT_SECS);
+
+        // History
+        let history_max_days = get("HISTORY_MAX_DAYS")
+            .and_then(|v| v.trim().parse().ok())
+            .filter(|days| *days > 0)
+            .unwrap_or(DEFAULT_HISTORY_MAX_DAYS);
 
         Ok(AppConfig {
             shop_domain,
@@ -133,6 +637,7 @@ impl AppConfig {
             api_version,
             primary_location,
             secondary_location,
+            extra_locations,
             firebase_api_key,
             firebase_auth_domain,
             firebase_project_id,
@@ -140,10 +645,77 @@ impl AppConfig {
             firebase_messaging_sender_id,
             firebase_app_id,
             firebase_measurement_id,
+            firebase_service_account,
             version,
+            bulk_concurrency,
+            bulk_chunk_size,
+            max_retries,
+            retry_base_delay_ms,
+            sku_cache_ttl_secs,
+            request_timeout_secs,
+            history_max_days,
+            config_source: ConfigSource::RuntimeEnv,
         })
     }
 
+    /// Load named shop profiles from the process environment (see `profiles_from_vars`)
+    pub fn load_profiles() -> (HashMap<String, AppConfig>, Vec<String>) {
+        dotenvy::dotenv().ok();
+        let vars: HashMap<String, String> = std::env::vars().collect();
+        Self::profiles_from_vars(&vars)
+    }
+
+    /// Build one configuration per `SHOP<N>_` prefix found in `vars`.
+    ///
+    /// A profile exists when `SHOP<N>_SHOPIFY_SHOP_DOMAIN` is set. Shopify and location keys
+    /// must be prefixed; the remaining keys (Firebase, bulk, retry) fall back to the
+    /// unprefixed values so a shared Firebase project only needs to be configured once.
+    /// Profile names are the lowercased prefix, e.g. `shop1`. A profile with missing or
+    /// malformed keys is left out and its problem returned alongside the valid profiles.
+    pub fn profiles_from_vars(
+        vars: &HashMap<String, String>,
+    ) -> (HashMap<String, AppConfig>, Vec<String>) {
+        let mut profiles = HashMap::new();
+        let mut problems = Vec::new();
+
+        for key in vars.keys() {
+            let Some(prefix) = key.strip_suffix("_SHOPIFY_SHOP_DOMAIN") else {
+                continue;
+            };
+            let is_profile_prefix = prefix
+                .strip_prefix("SHOP")
+                .is_some_and(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()));
+            if !is_profile_prefix {
+                continue;
+            }
+
+            let full_prefix = format!("{}_", prefix);
+            let loaded = Self::from_lookup(&full_prefix, |name| {
+                vars.get(&format!("{}{}", full_prefix, name))
+                    .or_else(|| {
+                        let shop_specific = name.starts_with("SHOPIFY_")
+                            || name.starts_with("LOCATION_")
+                            || name == "EXTRA_LOCATIONS";
+                        if shop_specific {
+                            None
+                        } else {
+                            vars.get(name)
+                        }
+                    })
+                    .cloned()
+            });
+            match loaded {
+                Ok(config) => {
+                    profiles.insert(prefix.to_lowercase(), config);
+                }
+                Err(e) => problems.push(format!("Skipping profile {}: {}", prefix, e)),
+            }
+        }
+        problems.sort();
+
+        (profiles, problems)
+    }
+
     /// Load configuration from compile-time environment variables (production)
     fn from_compile_time_env() -> Result<Self, String> {
         // In production mode, these variables should be embedded at compile time
@@ -163,7 +735,7 @@ impl AppConfig {
             .ok_or("SHOPIFY_API_SECRET_KEY must be set at compile time for production builds")?
             .to_string();
         let api_version = option_env!("SHOPIFY_API_VERSION")
-            .unwrap_or("2025-01")
+            .unwrap_or(DEFAULT_API_VERSION)
             .to_string();
         let primary_location = option_env!("LOCATION_TREVISO")
             .ok_or("LOCATION_TREVISO must be set at compile time for production builds")?
@@ -171,6 +743,7 @@ impl AppConfig {
         let secondary_location = option_env!("LOCATION_MOGLIANO")
             .ok_or("LOCATION_MOGLIANO must be set at compile time for production builds")?
             .to_string();
+        let extra_locations = parse_extra_locations(option_env!("EXTRA_LOCATIONS"));
 
         // Firebase Configuration
         let firebase_api_key = option_env!("FIREBASE_API_KEY")
@@ -196,10 +769,44 @@ impl AppConfig {
         let firebase_measurement_id = option_env!("FIREBASE_MEASUREMENT_ID")
             .ok_or("FIREBASE_MEASUREMENT_ID must be set at compile time for production builds")?
             .to_string();
+        let firebase_service_account = option_env!("FIREBASE_SERVICE_ACCOUNT")
+            .filter(|path| !path.trim().is_empty())
+            .map(str::to_string);
 
         // App Configuration
         let version = option_env!("VERSION").unwrap_or("3.2.0").to_string();
 
+        // Bulk Operations
+        let bulk_concurrency =
+            parse_size_setting(option_env!("BULK_CONCURRENCY"), DEFAULT_BULK_CONCURRENCY);
+        let bulk_chunk_size =
+            parse_size_setting(option_env!("BULK_CHUNK_SIZE"), DEFAULT_BULK_CHUNK_SIZE);
+
+        // Retry Policy
+        let max_retries = option_env!("SHOPIFY_MAX_RETRIES")
+            .and_then(|v| v.trim().parse().ok())
+            .unwrap_or(DEFAULT_MAX_RETRIES);
+        let retry_base_delay_ms = option_env!("SHOPIFY_RETRY_BASE_DELAY_MS")
+            .and_then(|v| v.trim().parse().ok())
+            .unwrap_or(DEFAULT_RETRY_BASE_DELAY_MS);
+
+        // Caching
+        let sku_cache_ttl_secs = option_env!("SKU_CACHE_TTL_SECS")
+            .and_then(|v| v.trim().parse().ok())
+            .unwrap_or(DEFAULT_SKU_CACHE_TTL_SECS);
+
+        // Timeouts
+        let request_timeout_secs = option_env!("REQUEST_TIMEOUT_SECS")
+            .and_then(|v| v.trim().parse().ok())
+            .filter(|secs| *secs > 0)
+            .unwrap_or(DEFAULT_REQUEST_TIMEOUT_SECS);
+
+        // History
+        let history_max_days = option_env!("HISTORY_MAX_DAYS")
+            .and_then(|v| v.trim().parse().ok())
+            .filter(|days| *days > 0)
+            .unwrap_or(DEFAULT_HISTORY_MAX_DAYS);
+
         Ok(AppConfig {
             shop_domain,
             access_token,
@@ -208,6 +815,7 @@ impl AppConfig {
             api_version,
             primary_location,
             secondary_location,
+            extra_locations,
             firebase_api_key,
             firebase_auth_domain,
             firebase_project_id,
@@ -215,10 +823,136 @@ impl AppConfig {
             firebase_messaging_sender_id,
             firebase_app_id,
             firebase_measurement_id,
+            firebase_service_account,
             version,
+            bulk_concurrency,
+            bulk_chunk_size,
+            max_retries,
+            retry_base_delay_ms,
+            sku_cache_ttl_secs,
+            request_timeout_secs,
+            history_max_days,
+            config_source: ConfigSource::CompileTime,
         })
     }
 
+    /// Check that the loaded values are well-formed, collecting every problem so they
+    /// can all be reported at once
+    pub fn validate(&self) -> Result<(), Vec<String>> {
+        let mut problems = Vec::new();
+
+        if !self.shop_domain.trim().ends_with(".myshopify.com") {
+            problems.push(format!(
+                "SHOPIFY_SHOP_DOMAIN '{}' must end in .myshopify.com",
+                self.shop_domain
+            ));
+        }
+
+        for (key, value) in [
+            ("LOCATION_TREVISO", &self.primary_location),
+            ("LOCATION_MOGLIANO", &self.secondary_location),
+        ] {
+            if value.trim().parse::<u64>().is_err() {
+                problems.push(format!("{} '{}' must be a numeric location ID", key, value));
+            }
+        }
+
+        for (name, id) in &self.extra_locations {
+            if name.trim().is_empty() {
+                problems.push(format!("EXTRA_LOCATIONS entry ':{}' must have a name", id));
+            }
+            if id.trim().parse::<u64>().is_err() {
+                problems.push(format!(
+                    "EXTRA_LOCATIONS entry '{}:{}' must have a numeric location ID",
+                    name, id
+                ));
+            }
+        }
+        let mut seen_names = Vec::new();
+        for (name, _) in self.named_locations() {
+            let key = name.to_lowercase();
+            if seen_names.contains(&key) {
+                problems.push(format!("Location name '{}' is configured twice", name));
+            }
+            seen_names.push(key);
+        }
+
+        if !is_api_version_shape(&self.api_version) {
+            problems.push(format!(
+                "SHOPIFY_API_VERSION '{}' must look like YYYY-MM (e.g. 2025-01)",
+                self.api_version
+            ));
+        }
+
+        for (key, value) in [
+            ("FIREBASE_API_KEY", &self.firebase_api_key),
+            ("FIREBASE_AUTH_DOMAIN", &self.firebase_auth_domain),
+            ("FIREBASE_PROJECT_ID", &self.firebase_project_id),
+            ("FIREBASE_STORAGE_BUCKET", &self.firebase_storage_bucket),
+            (
+                "FIREBASE_MESSAGING_SENDER_ID",
+                &self.firebase_messaging_sender_id,
+            ),
+            ("FIREBASE_APP_ID", &self.firebase_app_id),
+            ("FIREBASE_MEASUREMENT_ID", &self.firebase_measurement_id),
+        ] {
+            if value.trim().is_empty() {
+                problems.push(format!("{} must not be empty", key));
+            }
+        }
+
+        if let Some(path) = &self.firebase_service_account {
+            if !std::path::Path::new(path).is_file() {
+                problems.push(format!(
+                    "FIREBASE_SERVICE_ACCOUNT '{}' is not a readable file",
+                    path
+                ));
+            }
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(problems)
+        }
+    }
+
+    /// Split `items` into chunks of the configured bulk chunk size
+    pub fn bulk_chunks<'a, T>(&self, items: &'a [T]) -> std::slice::Chunks<'a, T> {
+        items.chunks(self.bulk_chunk_size.max(1))
+    }
+
+    /// Like `bulk_chunks`, but never more than the ids `inventory_levels.json` accepts per call
+    pub fn inventory_level_chunks<'a, T>(&self, items: &'a [T]) -> std::slice::Chunks<'a, T> {
+        items.chunks(self.bulk_chunk_size.clamp(1, INVENTORY_LEVELS_MAX_IDS))
+    }
+
+    /// The store used until a location is chosen on this machine: the first configured one
+    pub fn default_location_name(&self) -> String {
+        self.named_locations()
+            .into_iter()
+            .map(|(name, _)| name)
+            .next()
+            .unwrap_or_default()
+    }
+
+    /// Every configured store as (name, location id): Treviso and Mogliano first, then
+    /// the EXTRA_LOCATIONS entries in the order they were listed
+    pub fn named_locations(&self) -> Vec<(String, String)> {
+        [
+            ("Treviso".to_string(), self.primary_location.clone()),
+            ("Mogliano".to_string(), self.secondary_location.clone()),
+        ]
+        .into_iter()
+        .chain(self.extra_locations.iter().cloned())
+        .collect()
+    }
+
+    /// Map a failed send to a `Network` error, naming this config's timeout on timeouts
+    pub fn request_error(&self, e: reqwest::Error) -> AppError {
+        AppError::from_request_error(e, self.request_timeout_secs)
+    }
+
     pub fn get_api_url(&self, endpoint: &str) -> String {
         format!(
             "https://{}/admin/api/{}/{}",
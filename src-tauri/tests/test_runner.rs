@@ -3,8 +3,8 @@
 
 use chrono::Utc;
 use inventario_cappellettoshop_lib::firebase::{FirebaseClient, LogData, LogEntry};
-use inventario_cappellettoshop_lib::utils::AppConfig;
 use inventario_cappellettoshop_lib::utils::InventoryUpdate;
+use inventario_cappellettoshop_lib::utils::{AppConfig, ConfigSource};
 use serde_json::{json, Value};
 use std::env;
 
@@ -559,7 +559,7 @@ async fn test_real_firebase_log_structure() {
     };
 
     // Test Firebase client creation (just to verify it can be created)
-    let _firebase_client = FirebaseClient::new(config);
+    let _firebase_client = FirebaseClient::new(&reqwest::Client::new(), config);
 
     // Test that log entry is properly structured
     assert_eq!(log_entry.request_type, "Rettifica");
@@ -718,7 +718,16 @@ fn test_dry_run_all_functions() {
         firebase_messaging_sender_id: "123456".to_string(),
         firebase_app_id: "1:123456:web:abc123".to_string(),
         firebase_measurement_id: "G-ABC123".to_string(),
+        firebase_service_account: None,
         version: "3.2.0".to_string(),
+        bulk_concurrency: 4,
+        bulk_chunk_size: 50,
+        max_retries: 3,
+        retry_base_delay_ms: 500,
+        sku_cache_ttl_secs: 600,
+        request_timeout_secs: 30,
+        history_max_days: 365,
+        config_source: ConfigSource::RuntimeEnv,
     };
 
     // Test URL generation for all endpoints
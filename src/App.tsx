@@ -6,6 +6,7 @@ import {
   ConfigProvider,
   message,
   Modal,
+  Result,
   App as AntdApp,
 } from "antd";
 
@@ -30,6 +31,7 @@ import {
   isDevelopmentVersion,
 } from "./utils/version";
 import { getPrimaryLocationName } from "./utils/location";
+import TauriAPI from "./services/tauri";
 import { listen } from "@tauri-apps/api/event";
 import "antd/dist/reset.css";
 import "./App.css";
@@ -107,6 +109,9 @@ function App() {
   const [transferModeEnabled, setTransferModeEnabled] =
     useState<boolean>(false);
 
+  // Set when the backend couldn't load its configuration
+  const [startupError, setStartupError] = useState<string | null>(null);
+
   const sidebarRef = useRef<HTMLDivElement>(null);
 
   // Initialize updater with configuration
@@ -124,6 +129,12 @@ function App() {
     showErrorMessages: false, // Don't show error messages automatically
   });
 
+  useEffect(() => {
+    TauriAPI.Status.getStartupError()
+      .then(setStartupError)
+      .catch((error) => console.error("❌ Startup check failed:", error));
+  }, []);
+
   useEffect(() => {
     document.title = `Inventario CappellettoShop ${getDisplayVersion()}`;
 
@@ -380,6 +391,22 @@ function App() {
     localStorage.setItem("transferModeEnabled", enabled.toString());
   };
 
+  if (startupError) {
+    return (
+      <ConfigProvider theme={customTheme}>
+        <Result
+          status="error"
+          title="Impossibile avviare l'app"
+          subTitle={
+            <pre style={{ textAlign: "left", whiteSpace: "pre-wrap" }}>
+              {startupError}
+            </pre>
+          }
+        />
+      </ConfigProvider>
+    );
+  }
+
   return (
     <LogProvider>
       <ConfigProvider theme={customTheme}>
@@ -4,12 +4,17 @@ import type {
   LocationConfig,
   InventoryUpdate,
   StatusResponse,
+  AppError,
   ProductModificationHistory,
   EnhancedStatusResponse,
   LocationInfo,
+  LocationLevels,
   FirebaseConfig,
   LogData,
   CheckRequestWithId,
+  ProductStatus,
+  SyncResult,
+  VariantInventoryAudit,
 } from "../types/index";
 import { message } from "antd";
 
@@ -22,6 +27,14 @@ export interface ProductVariant {
   sku?: string;
 }
 
+// Commands reject with an AppError object; older builds rejected with a plain string
+export function errorMessage(error: unknown): string {
+  if (typeof error === "object" && error !== null && "message" in error) {
+    return (error as AppError).message;
+  }
+  return String(error);
+}
+
 // Firebase-related type definitions
 export interface LogEntry {
   requestType: string;
@@ -29,19 +42,52 @@ export interface LogEntry {
   timestamp: string;
 }
 
+export interface LogsPage<T = LogEntry> {
+  logs: T[];
+  next_page_token: string | null;
+}
+
+export interface LogEntryWithId extends LogEntry {
+  id: string; // Firestore document ID
+}
+
 // Product API functions
 export class ProductAPI {
   /**
-   * Get all products (limit 250)
+   * Get all products, following pagination (optionally capped at maxPages pages of 250)
    */
-  static async getProducts(): Promise<Product[]> {
+  static async getProducts(maxPages?: number): Promise<Product[]> {
     try {
-      const result = await invoke<Product[]>("get_products");
+      const result = await invoke<Product[]>("get_products", { maxPages });
       console.log("🔍 Raw API Response - get_products:", result);
       return result;
     } catch (error) {
       console.error("Error fetching products:", error);
-      throw new Error(`Failed to fetch products: ${error}`);
+      throw new Error(`Failed to fetch products: ${errorMessage(error)}`);
+    }
+  }
+
+  /**
+   * Get the whole catalog, served from the backend cache when younger than maxAgeSecs
+   */
+  static async getProductsCached(maxAgeSecs?: number): Promise<Product[]> {
+    try {
+      return await invoke<Product[]>("get_products_cached", { maxAgeSecs });
+    } catch (error) {
+      console.error("Error fetching cached products:", error);
+      throw new Error(`Failed to fetch products: ${errorMessage(error)}`);
+    }
+  }
+
+  /**
+   * Invalidate the cached catalog so the next getProductsCached refetches
+   */
+  static async forceRefreshProducts(): Promise<StatusResponse> {
+    try {
+      return await invoke<StatusResponse>("force_refresh_products");
+    } catch (error) {
+      console.error("Error invalidating products cache:", error);
+      throw new Error(`Failed to refresh products: ${errorMessage(error)}`);
     }
   }
 
@@ -58,21 +104,65 @@ export class ProductAPI {
       return result;
     } catch (error) {
       console.error("Error fetching product by ID:", error);
-      throw new Error(`Failed to fetch product ${productId}: ${error}`);
+      throw new Error(`Failed to fetch product ${productId}: ${errorMessage(error)}`);
+    }
+  }
+
+  /**
+   * Fetch several products in one call, in the order of productIds.
+   * Ids that don't resolve are left out.
+   */
+  static async getProductsByIds(productIds: string[]): Promise<Product[]> {
+    try {
+      return await invoke<Product[]>("get_products_by_ids", { productIds });
+    } catch (error) {
+      console.error("Error fetching products by ids:", error);
+      throw new Error(`Failed to fetch products: ${errorMessage(error)}`);
+    }
+  }
+
+  /**
+   * Get a product with per-location quantities filled in for it and each variant
+   */
+  static async getProductWithLocations(
+    productId: string,
+    primaryLocationName: string
+  ): Promise<Product> {
+    try {
+      const result = await invoke<Product>("get_product_with_locations", {
+        productId,
+        primaryLocationName,
+      });
+      console.log(
+        `🔍 Raw API Response - get_product_with_locations (${productId}, ${primaryLocationName}):`,
+        result
+      );
+      return result;
+    } catch (error) {
+      console.error("Error fetching product with locations:", error);
+      throw new Error(`Failed to fetch product ${productId}: ${errorMessage(error)}`);
     }
   }
 
   /**
    * Search products by title
    */
-  static async searchProducts(query: string): Promise<Product[]> {
+  static async searchProducts(
+    query: string,
+    status?: ProductStatus,
+    vendor?: string
+  ): Promise<Product[]> {
     try {
-      const result = await invoke<Product[]>("search_products", { query });
+      const result = await invoke<Product[]>("search_products", {
+        query,
+        status,
+        vendor,
+      });
       console.log(`🔍 Raw API Response - search_products (${query}):`, result);
       return result;
     } catch (error) {
       console.error("Error searching products:", error);
-      throw new Error(`Failed to search products: ${error}`);
+      throw new Error(`Failed to search products: ${errorMessage(error)}`);
     }
   }
 
@@ -82,7 +172,9 @@ export class ProductAPI {
   static async searchProductsByNameGraphQL(
     name: string,
     sortKey?: string,
-    sortReverse?: boolean
+    sortReverse?: boolean,
+    status?: ProductStatus,
+    vendor?: string
   ): Promise<Product[]> {
     try {
       const result = await invoke<Product[]>(
@@ -91,6 +183,8 @@ export class ProductAPI {
           name,
           sortKey: sortKey || "RELEVANCE",
           sortReverse: sortReverse || false,
+          status,
+          vendor,
         }
       );
       console.log(
@@ -105,7 +199,25 @@ export class ProductAPI {
       return result;
     } catch (error) {
       console.error("Error in GraphQL product search:", error);
-      throw new Error(`Failed to search products by name: ${error}`);
+      throw new Error(`Failed to search products by name: ${errorMessage(error)}`);
+    }
+  }
+
+  /**
+   * All products carrying a tag (e.g. "SALDI"), any status
+   */
+  static async searchProductsByTag(
+    tag: string,
+    sortKey?: string
+  ): Promise<Product[]> {
+    try {
+      return await invoke<Product[]>("search_products_by_tag_graphql", {
+        tag,
+        sortKey,
+      });
+    } catch (error) {
+      console.error("Error searching products by tag:", error);
+      throw new Error(`Failed to search products by tag: ${errorMessage(error)}`);
     }
   }
 
@@ -122,7 +234,7 @@ export class ProductAPI {
       return result;
     } catch (error) {
       console.error("Error searching products by SKU:", error);
-      throw new Error(`Failed to search products by SKU: ${error}`);
+      throw new Error(`Failed to search products by SKU: ${errorMessage(error)}`);
     }
   }
 
@@ -142,7 +254,7 @@ export class ProductAPI {
       return result;
     } catch (error) {
       console.error("Error in enhanced product search:", error);
-      throw new Error(`Failed to search products: ${error}`);
+      throw new Error(`Failed to search products: ${errorMessage(error)}`);
     }
   }
 
@@ -161,7 +273,7 @@ export class ProductAPI {
       return result;
     } catch (error) {
       console.error("Error in enhanced search:", error);
-      throw new Error(`Failed to search products: ${error}`);
+      throw new Error(`Failed to search products: ${errorMessage(error)}`);
     }
   }
 
@@ -180,7 +292,19 @@ export class ProductAPI {
       return result;
     } catch (error) {
       console.error("Error searching products by SKU with GraphQL:", error);
-      throw new Error(`Failed to search products by SKU: ${error}`);
+      throw new Error(`Failed to search products by SKU: ${errorMessage(error)}`);
+    }
+  }
+
+  /**
+   * Forget cached SKU lookups so the next searches hit Shopify again
+   */
+  static async clearSkuCache(): Promise<StatusResponse> {
+    try {
+      return await invoke<StatusResponse>("clear_sku_cache");
+    } catch (error) {
+      console.error("Error clearing SKU cache:", error);
+      throw new Error(`Failed to clear SKU cache: ${errorMessage(error)}`);
     }
   }
 
@@ -211,13 +335,83 @@ export class ProductAPI {
       }
     } catch (error) {
       console.error("Error finding product by exact SKU:", error);
-      throw new Error(`Failed to find product by SKU: ${error}`);
+      throw new Error(`Failed to find product by SKU: ${errorMessage(error)}`);
+    }
+  }
+
+  /**
+   * Check whether a SKU is already used by any product (any status).
+   * Returns [productId, productTitle] of the owner, or null if the SKU is free.
+   */
+  static async checkSkuExists(sku: string): Promise<[string, string] | null> {
+    try {
+      return await invoke<[string, string] | null>("check_sku_exists", { sku });
+    } catch (error) {
+      console.error("Error checking SKU:", error);
+      throw new Error(`Failed to check SKU ${sku}: ${errorMessage(error)}`);
+    }
+  }
+
+  /**
+   * Find product by EAN/UPC barcode using GraphQL - returns product and matching variant ID
+   */
+  static async findProductByBarcodeGraphQL(
+    barcode: string
+  ): Promise<{ product: Product; variantInventoryItemId: string } | null> {
+    try {
+      const result = await invoke<[Product, string] | null>(
+        "find_product_by_barcode_graphql",
+        { barcode }
+      );
+      if (result) {
+        const [product, variantInventoryItemId] = result;
+        return { product, variantInventoryItemId };
+      }
+      return null;
+    } catch (error) {
+      console.error("Error finding product by barcode:", error);
+      throw new Error(`Failed to find product by barcode: ${errorMessage(error)}`);
     }
   }
 }
 
 // Inventory API functions
+export interface SetQuantityResult {
+  inventory_item_id: string;
+  location_id: string;
+  quantity: number;
+  on_hand: number | null;
+}
+
+export interface ReasonOption {
+  value: string;
+  label_it: string;
+}
+
+export interface LocationLowStock {
+  product_id: string;
+  product_title: string;
+  variant_id: string;
+  variant_title: string;
+  sku: string | null;
+  inventory_item_id: string;
+  location_quantity: number;
+  total_quantity: number;
+}
+
 export class InventoryAPI {
+  /**
+   * Adjustment reasons (Shopify value + Italian label) for the reason dropdown
+   */
+  static async getAdjustmentReasons(): Promise<ReasonOption[]> {
+    try {
+      return await invoke<ReasonOption[]>("get_adjustment_reasons");
+    } catch (error) {
+      console.error("Error getting adjustment reasons:", error);
+      throw new Error(`Failed to get adjustment reasons: ${errorMessage(error)}`);
+    }
+  }
+
   /**
    * Get location configuration from backend
    */
@@ -228,7 +422,7 @@ export class InventoryAPI {
       return result;
     } catch (error) {
       console.error("Error fetching location config:", error);
-      throw new Error(`Failed to fetch location config: ${error}`);
+      throw new Error(`Failed to fetch location config: ${errorMessage(error)}`);
     }
   }
 
@@ -238,10 +432,10 @@ export class InventoryAPI {
   static async getInventoryLevelsForLocations(
     inventoryItemIds: string[],
     primaryLocationName: string
-  ): Promise<{ [itemId: string]: { primary: number; secondary: number } }> {
+  ): Promise<{ [itemId: string]: LocationLevels }> {
     try {
       const result = await invoke<{
-        [itemId: string]: { primary?: number; secondary?: number };
+        [itemId: string]: Partial<LocationLevels>;
       }>("get_inventory_levels_for_locations", {
         inventoryItemIds,
         primaryLocationName,
@@ -256,13 +450,13 @@ export class InventoryAPI {
         inventoryItemIds
       );
 
-      // Ensure all items have both primary and secondary values
-      const processedResult: {
-        [itemId: string]: { primary: number; secondary: number };
-      } = {};
+      // Ensure all items have both primary and secondary values; per-store
+      // quantities keyed by location name are passed through as well
+      const processedResult: { [itemId: string]: LocationLevels } = {};
       for (const itemId of inventoryItemIds) {
         const itemData = result[itemId] || {};
         processedResult[itemId] = {
+          ...itemData,
           primary: itemData.primary || 0,
           secondary: itemData.secondary || 0,
         };
@@ -271,7 +465,23 @@ export class InventoryAPI {
       return processedResult;
     } catch (error) {
       console.error("Error fetching location-aware inventory levels:", error);
-      throw new Error(`Failed to fetch inventory levels: ${error}`);
+      throw new Error(`Failed to fetch inventory levels: ${errorMessage(error)}`);
+    }
+  }
+
+  /**
+   * Compare each variant's Shopify inventory_quantity with its summed location levels
+   */
+  static async auditProductInventory(
+    productId: string
+  ): Promise<VariantInventoryAudit[]> {
+    try {
+      return await invoke<VariantInventoryAudit[]>("audit_product_inventory", {
+        productId,
+      });
+    } catch (error) {
+      console.error("Error auditing product inventory:", error);
+      throw new Error(`Failed to audit inventory: ${errorMessage(error)}`);
     }
   }
 
@@ -293,7 +503,25 @@ export class InventoryAPI {
       return result;
     } catch (error) {
       console.error("Error fetching inventory levels:", error);
-      throw new Error(`Failed to fetch inventory levels: ${error}`);
+      throw new Error(`Failed to fetch inventory levels: ${errorMessage(error)}`);
+    }
+  }
+
+  /**
+   * Live quantity of one variant at one location (barcode-scan hot path)
+   */
+  static async getVariantQuantity(
+    inventoryItemId: string,
+    locationId: string
+  ): Promise<number> {
+    try {
+      return await invoke<number>("get_variant_quantity", {
+        inventoryItemId,
+        locationId,
+      });
+    } catch (error) {
+      console.error("Error fetching variant quantity:", error);
+      throw new Error(`Failed to fetch variant quantity: ${errorMessage(error)}`);
     }
   }
 
@@ -312,7 +540,7 @@ export class InventoryAPI {
       return result;
     } catch (error) {
       console.error("Error adjusting inventory:", error);
-      throw new Error(`Failed to adjust inventory: ${error}`);
+      throw new Error(`Failed to adjust inventory: ${errorMessage(error)}`);
     }
   }
 
@@ -337,7 +565,70 @@ export class InventoryAPI {
       return result;
     } catch (error) {
       console.error("Error setting inventory level:", error);
-      throw new Error(`Failed to set inventory level: ${error}`);
+      throw new Error(`Failed to set inventory level: ${errorMessage(error)}`);
+    }
+  }
+
+  /**
+   * Set exact inventory level via GraphQL; resolves with the on-hand quantity after the write
+   */
+  static async setInventoryLevelGraphQL(
+    inventoryItemId: string,
+    locationId: string,
+    quantity: number,
+    reason: string
+  ): Promise<SetQuantityResult> {
+    try {
+      return await invoke<SetQuantityResult>("set_inventory_level_graphql", {
+        inventoryItemId,
+        locationId,
+        quantity,
+        reason,
+      });
+    } catch (error) {
+      console.error("Error setting inventory level via GraphQL:", error);
+      throw new Error(`Failed to set inventory level: ${errorMessage(error)}`);
+    }
+  }
+
+  /**
+   * Set exact inventory level only if it still equals expectedQuantity.
+   * Rejects with the AppError itself so callers can check kind === "conflict".
+   */
+  static async setInventoryLevelSafe(
+    inventoryItemId: string,
+    locationId: string,
+    quantity: number,
+    reason: string,
+    expectedQuantity?: number
+  ): Promise<SetQuantityResult> {
+    try {
+      return await invoke<SetQuantityResult>("set_inventory_level_safe", {
+        inventoryItemId,
+        locationId,
+        quantity,
+        expectedQuantity,
+        reason,
+      });
+    } catch (error) {
+      console.error("Error setting inventory level safely:", error);
+      throw error;
+    }
+  }
+
+  /**
+   * Set a product's status directly (active, draft or archived).
+   * Returns the status reported by Shopify after the update.
+   */
+  static async setProductStatus(
+    productId: string,
+    status: ProductStatus
+  ): Promise<string> {
+    try {
+      return await invoke<string>("set_product_status", { productId, status });
+    } catch (error) {
+      console.error("Error setting product status:", error);
+      throw new Error(`Failed to set product status: ${errorMessage(error)}`);
     }
   }
 
@@ -357,7 +648,30 @@ export class InventoryAPI {
       return result;
     } catch (error) {
       console.error("Error fetching low stock products:", error);
-      throw new Error(`Failed to fetch low stock products: ${error}`);
+      throw new Error(`Failed to fetch low stock products: ${errorMessage(error)}`);
+    }
+  }
+
+  /**
+   * Get variants with low stock at one location, with their total across locations
+   */
+  static async getLowStockProductsByLocation(
+    threshold: number,
+    locationName: string
+  ): Promise<LocationLowStock[]> {
+    try {
+      const result = await invoke<LocationLowStock[]>(
+        "get_low_stock_products_by_location",
+        { threshold, locationName }
+      );
+      console.log(
+        `🔍 Raw API Response - get_low_stock_products_by_location (${locationName}, threshold: ${threshold}):`,
+        result
+      );
+      return result;
+    } catch (error) {
+      console.error("Error fetching low stock products by location:", error);
+      throw new Error(`Failed to fetch low stock products: ${errorMessage(error)}`);
     }
   }
 
@@ -372,7 +686,8 @@ export class InventoryAPI {
     productName: string,
     price: string,
     negozio: string,
-    images: string[]
+    images: string[],
+    quantity: number = 1
   ): Promise<EnhancedStatusResponse> {
     try {
       const result = await invoke<EnhancedStatusResponse>(
@@ -386,6 +701,7 @@ export class InventoryAPI {
           price,
           negozio,
           images,
+          quantity,
         }
       );
 
@@ -394,7 +710,7 @@ export class InventoryAPI {
         result
       );
       console.log(
-        `📝 Decreased inventory for ${productName} (${variantTitle}) at ${negozio}`
+        `📝 Decreased inventory by ${quantity} for ${productName} (${variantTitle}) at ${negozio}`
       );
 
       // Show enhanced toast notifications based on status changes
@@ -408,12 +724,12 @@ export class InventoryAPI {
       return result;
     } catch (error) {
       console.error("Error decreasing inventory with logging:", error);
-      throw new Error(`Failed to decrease inventory: ${error}`);
+      throw new Error(`Failed to decrease inventory: ${errorMessage(error)}`);
     }
   }
 
   /**
-   * Undo inventory decrease (increase by 1) and log to Firebase (enhanced function)
+   * Undo inventory decrease (increase by quantity, default 1) and log to Firebase (enhanced function)
    */
   static async undoDecreaseInventoryWithLogging(
     inventoryItemId: string,
@@ -423,7 +739,8 @@ export class InventoryAPI {
     productName: string,
     price: string,
     negozio: string,
-    images: string[]
+    images: string[],
+    quantity = 1
   ): Promise<EnhancedStatusResponse> {
     try {
       const result = await invoke<EnhancedStatusResponse>(
@@ -437,6 +754,7 @@ export class InventoryAPI {
           price,
           negozio,
           images,
+          quantity,
         }
       );
 
@@ -459,7 +777,7 @@ export class InventoryAPI {
       return result;
     } catch (error) {
       console.error("Error undoing inventory decrease with logging:", error);
-      throw new Error(`Failed to undo inventory decrease: ${error}`);
+      throw new Error(`Failed to undo inventory decrease: ${errorMessage(error)}`);
     }
   }
 
@@ -488,7 +806,7 @@ export class InventoryAPI {
       return result;
     } catch (error) {
       console.error("Error getting product modification history:", error);
-      throw new Error(`Failed to get modification history: ${error}`);
+      throw new Error(`Failed to get modification history: ${errorMessage(error)}`);
     }
   }
 
@@ -505,7 +823,8 @@ export class InventoryAPI {
     price: string,
     fromLocation: string,
     toLocation: string,
-    images: string[]
+    images: string[],
+    quantity: number = 1
   ): Promise<EnhancedStatusResponse> {
     try {
       const result = await invoke<EnhancedStatusResponse>(
@@ -521,6 +840,7 @@ export class InventoryAPI {
           fromLocation,
           toLocation,
           images,
+          quantity,
         }
       );
 
@@ -529,13 +849,38 @@ export class InventoryAPI {
         result
       );
       console.log(
-        `📝 Transferred inventory for ${productName} (${variantTitle}) from ${fromLocation} to ${toLocation}`
+        `📝 Transferred ${quantity} x ${productName} (${variantTitle}) from ${fromLocation} to ${toLocation}`
       );
 
       return result;
     } catch (error) {
       console.error("Error transferring inventory between locations:", error);
-      throw new Error(`Failed to transfer inventory: ${error}`);
+      throw new Error(`Failed to transfer inventory: ${errorMessage(error)}`);
+    }
+  }
+
+  /**
+   * Transfer between two stores by name; location ids and variant details
+   * are resolved by the backend
+   */
+  static async transferByLocationName(
+    inventoryItemId: string,
+    productId: string,
+    fromName: string,
+    toName: string,
+    quantity: number = 1
+  ): Promise<EnhancedStatusResponse> {
+    try {
+      return await invoke<EnhancedStatusResponse>("transfer_by_location_name", {
+        inventoryItemId,
+        productId,
+        fromName,
+        toName,
+        quantity,
+      });
+    } catch (error) {
+      console.error("Error transferring inventory by location name:", error);
+      throw new Error(`Failed to transfer inventory: ${errorMessage(error)}`);
     }
   }
 }
@@ -559,7 +904,7 @@ export class FirebaseAPI {
       return result;
     } catch (error) {
       console.error("Error creating log:", error);
-      throw new Error(`Failed to create log: ${error}`);
+      throw new Error(`Failed to create log: ${errorMessage(error)}`);
     }
   }
 
@@ -567,20 +912,94 @@ export class FirebaseAPI {
    * Get logs from Firebase with optional filtering
    */
   static async getLogs(query?: string, location?: string): Promise<LogEntry[]> {
+    const page = await this.getLogsPage(query, location);
+    return page.logs;
+  }
+
+  /**
+   * Get one page of a day's logs (today unless a date is given)
+   */
+  static async getLogsPage(
+    query?: string,
+    location?: string,
+    options: { date?: string; limit?: number; pageToken?: string } = {}
+  ): Promise<LogsPage> {
     try {
-      const result = await invoke<LogEntry[]>("get_logs", {
+      const result = await invoke<LogsPage>("get_logs", {
         query: query || null,
         location: location || "Treviso", // Default to Treviso
+        date: options.date ?? null,
+        limit: options.limit ?? null,
+        pageToken: options.pageToken ?? null,
       });
       console.log(
         `🔍 Raw API Response - get_logs (query: ${query}, location: ${location}):`,
         result
       );
-      console.log(`📊 Found ${result.length} log entries`);
+      console.log(`📊 Found ${result.logs.length} log entries`);
       return result;
     } catch (error) {
       console.error("Error fetching logs:", error);
-      throw new Error(`Failed to fetch logs: ${error}`);
+      throw new Error(`Failed to fetch logs: ${errorMessage(error)}`);
+    }
+  }
+
+  /**
+   * Get one page of a day's logs together with their Firestore document IDs
+   */
+  static async getLogsWithIds(
+    query?: string,
+    location?: string,
+    options: { date?: string; limit?: number; pageToken?: string } = {}
+  ): Promise<LogsPage<LogEntryWithId>> {
+    try {
+      return await invoke<LogsPage<LogEntryWithId>>("get_logs_with_ids", {
+        query: query || null,
+        location: location || "Treviso", // Default to Treviso
+        date: options.date ?? null,
+        limit: options.limit ?? null,
+        pageToken: options.pageToken ?? null,
+      });
+    } catch (error) {
+      console.error("Error fetching logs with ids:", error);
+      throw new Error(`Failed to fetch logs: ${errorMessage(error)}`);
+    }
+  }
+
+  /**
+   * Delete a mistaken log entry by its Firestore document ID
+   */
+  static async deleteLog(documentId: string): Promise<StatusResponse> {
+    try {
+      return await invoke<StatusResponse>("delete_log", { documentId });
+    } catch (error) {
+      console.error("Error deleting log:", error);
+      throw new Error(`Failed to delete log: ${errorMessage(error)}`);
+    }
+  }
+
+  /**
+   * Upload logs kept in the local audit file because Firebase was unreachable
+   */
+  static async syncPendingLogs(): Promise<SyncResult> {
+    try {
+      return await invoke<SyncResult>("sync_pending_logs");
+    } catch (error) {
+      console.error("Error syncing pending logs:", error);
+      throw new Error(`Failed to sync pending logs: ${errorMessage(error)}`);
+    }
+  }
+
+  /**
+   * Retry one pending log by its position in the pending list or its audit ID.
+   * The response message is the Firestore document ID.
+   */
+  static async retryQueuedLog(indexOrId: string): Promise<StatusResponse> {
+    try {
+      return await invoke<StatusResponse>("retry_queued_log", { indexOrId });
+    } catch (error) {
+      console.error("Error retrying pending log:", error);
+      throw new Error(`Failed to retry pending log: ${errorMessage(error)}`);
     }
   }
 
@@ -594,7 +1013,7 @@ export class FirebaseAPI {
       return result;
     } catch (error) {
       console.error("Error fetching Firebase config:", error);
-      throw new Error(`Failed to fetch Firebase config: ${error}`);
+      throw new Error(`Failed to fetch Firebase config: ${errorMessage(error)}`);
     }
   }
 
@@ -647,7 +1066,50 @@ export class FirebaseAPI {
       return result;
     } catch (error) {
       console.error("Error fetching logs with date range:", error);
-      throw new Error(`Failed to fetch logs with date range: ${error}`);
+      throw new Error(`Failed to fetch logs with date range: ${errorMessage(error)}`);
+    }
+  }
+
+  /**
+   * Stream logs in a date range as "logs-batch" events (then "logs-complete"),
+   * for exports larger than get_logs_date_range can return. Resolves to the total.
+   */
+  static async streamLogs(
+    location: string,
+    startDate: string,
+    endDate: string
+  ): Promise<number> {
+    try {
+      const total = await invoke<number>("stream_logs", {
+        location,
+        startDate,
+        endDate,
+      });
+      console.log(`📊 Streamed ${total} log entries (${startDate} to ${endDate})`);
+      return total;
+    } catch (error) {
+      console.error("Error streaming logs:", error);
+      throw new Error(`Failed to stream logs: ${errorMessage(error)}`);
+    }
+  }
+
+  /**
+   * Export logs in a date range as CSV text, ready to be written via the save dialog
+   */
+  static async exportLogsCsv(
+    location: string,
+    startDate: string,
+    endDate: string
+  ): Promise<string> {
+    try {
+      return await invoke<string>("export_logs_csv", {
+        location,
+        startDate,
+        endDate,
+      });
+    } catch (error) {
+      console.error("Error exporting logs as CSV:", error);
+      throw new Error(`Failed to export logs: ${errorMessage(error)}`);
     }
   }
 
@@ -675,7 +1137,7 @@ export class FirebaseAPI {
       return result;
     } catch (error) {
       console.error("Error fetching logs by product ID:", error);
-      throw new Error(`Failed to fetch product logs: ${error}`);
+      throw new Error(`Failed to fetch product logs: ${errorMessage(error)}`);
     }
   }
 
@@ -689,7 +1151,7 @@ export class FirebaseAPI {
       console.log("✅ Check request created successfully");
     } catch (error) {
       console.error("❌ Error creating check request:", error);
-      throw new Error(`Failed to create check request: ${error}`);
+      throw new Error(`Failed to create check request: ${errorMessage(error)}`);
     }
   }
 
@@ -697,11 +1159,15 @@ export class FirebaseAPI {
    * Get check requests from Firebase filtered by location
    */
   static async getCheckRequests(
-    location: string
+    location: string,
+    status?: string,
+    priority?: "low" | "medium" | "high"
   ): Promise<CheckRequestWithId[]> {
     try {
       const result = await invoke<CheckRequestWithId[]>("get_check_requests", {
         location,
+        status,
+        priority,
       });
       console.log(
         `🔍 Raw API Response - get_check_requests (location: ${location}):`,
@@ -711,7 +1177,7 @@ export class FirebaseAPI {
       return result;
     } catch (error) {
       console.error("Error fetching check requests:", error);
-      throw new Error(`Failed to fetch check requests: ${error}`);
+      throw new Error(`Failed to fetch check requests: ${errorMessage(error)}`);
     }
   }
 
@@ -735,7 +1201,32 @@ export class FirebaseAPI {
       console.log("✅ Check request updated successfully");
     } catch (error) {
       console.error("❌ Error updating check request:", error);
-      throw new Error(`Failed to update check request: ${error}`);
+      throw new Error(`Failed to update check request: ${errorMessage(error)}`);
+    }
+  }
+
+  /**
+   * Change a check request's priority, assignee and/or notes without closing it.
+   * Only the supplied fields are updated.
+   */
+  static async updateCheckRequestFields(
+    documentId: string,
+    fields: {
+      priority?: "low" | "medium" | "high";
+      checkedBy?: string;
+      notes?: string;
+    }
+  ): Promise<StatusResponse> {
+    try {
+      return await invoke<StatusResponse>("update_check_request_fields", {
+        documentId,
+        priority: fields.priority,
+        checkedBy: fields.checkedBy,
+        notes: fields.notes,
+      });
+    } catch (error) {
+      console.error("❌ Error updating check request fields:", error);
+      throw new Error(`Failed to update check request: ${errorMessage(error)}`);
     }
   }
 }
@@ -752,7 +1243,7 @@ export class LocationAPI {
       return result;
     } catch (error) {
       console.error("Error fetching app location:", error);
-      throw new Error(`Failed to fetch app location: ${error}`);
+      throw new Error(`Failed to fetch app location: ${errorMessage(error)}`);
     }
   }
 
@@ -771,21 +1262,49 @@ export class LocationAPI {
       return result;
     } catch (error) {
       console.error("Error setting app location:", error);
-      throw new Error(`Failed to set app location: ${error}`);
+      throw new Error(`Failed to set app location: ${errorMessage(error)}`);
+    }
+  }
+
+  /**
+   * Set the app location and get back the resolved primary/secondary config.
+   * Fails if expectedCurrent is given and the stored location no longer matches it.
+   */
+  static async setAppLocationResolved(
+    location: string,
+    expectedCurrent?: string
+  ): Promise<LocationConfig> {
+    try {
+      const result = await invoke<LocationConfig>("set_app_location_resolved", {
+        location,
+        expectedCurrent,
+      });
+      console.log(
+        `🔍 Raw API Response - set_app_location_resolved (${location}):`,
+        result
+      );
+      return result;
+    } catch (error) {
+      console.error("Error setting app location:", error);
+      throw new Error(`Failed to set app location: ${errorMessage(error)}`);
     }
   }
 
   /**
-   * Get all available locations
+   * Get all available locations (loaded from Shopify when fromShopify is set)
    */
-  static async getAvailableLocations(): Promise<LocationInfo[]> {
+  static async getAvailableLocations(
+    fromShopify?: boolean
+  ): Promise<LocationInfo[]> {
     try {
-      const result = await invoke<LocationInfo[]>("get_available_locations");
+      const result = await invoke<LocationInfo[]>("get_available_locations", {
+        fromShopify,
+      });
       console.log(`🔍 Raw API Response - get_available_locations:`, result);
       return result;
     } catch (error) {
       console.error("Error fetching available locations:", error);
-      throw new Error(`Failed to fetch available locations: ${error}`);
+      throw new Error(`Failed to fetch available locations: ${errorMessage(error)}`);
     }
   }
 
@@ -804,7 +1323,7 @@ export class LocationAPI {
       return result;
     } catch (error) {
       console.error("Error fetching location by name:", error);
-      throw new Error(`Failed to fetch location by name: ${error}`);
+      throw new Error(`Failed to fetch location by name: ${errorMessage(error)}`);
     }
   }
 
@@ -820,7 +1339,7 @@ export class LocationAPI {
       return result;
     } catch (error) {
       console.error("Error fetching current location config:", error);
-      throw new Error(`Failed to fetch current location config: ${error}`);
+      throw new Error(`Failed to fetch current location config: ${errorMessage(error)}`);
     }
   }
 }
@@ -837,7 +1356,31 @@ export class StatusAPI {
       return result;
     } catch (error) {
       console.error("Error testing Shopify connection:", error);
-      throw new Error(`Failed to test connection: ${error}`);
+      throw new Error(`Failed to test connection: ${errorMessage(error)}`);
+    }
+  }
+
+  /**
+   * Why the app couldn't start (e.g. an invalid .env), or null when it started fine
+   */
+  static async getStartupError(): Promise<string | null> {
+    try {
+      return await invoke<string | null>("get_startup_error");
+    } catch (error) {
+      console.error("Error getting startup error:", error);
+      throw new Error(`Failed to get startup error: ${errorMessage(error)}`);
+    }
+  }
+
+  /**
+   * Whether the running config was loaded from .env (dev) or embedded at build time
+   */
+  static async getConfigSource(): Promise<"RuntimeEnv" | "CompileTime"> {
+    try {
+      return await invoke<"RuntimeEnv" | "CompileTime">("get_config_source");
+    } catch (error) {
+      console.error("Error getting config source:", error);
+      throw new Error(`Failed to get config source: ${errorMessage(error)}`);
     }
   }
 
@@ -851,7 +1394,7 @@ export class StatusAPI {
       return result;
     } catch (error) {
       console.error("Error in greet function:", error);
-      throw new Error(`Failed to greet: ${error}`);
+      throw new Error(`Failed to greet: ${errorMessage(error)}`);
     }
   }
 }
@@ -39,6 +39,10 @@ const mockProductDetails = {
 const mockLocationConfig = {
   primary_location: { id: "loc_1", name: "Treviso" },
   secondary_location: { id: "loc_2", name: "Mogliano" },
+  locations: [
+    { id: "loc_1", name: "Treviso" },
+    { id: "loc_2", name: "Mogliano" },
+  ],
 };
 
 const mockLogContext = {
@@ -49,6 +49,10 @@ export interface Product {
   variants: ProductVariant[];
   total_inventory: number;
   locations: { [key: string]: number };
+  warnings?: string[];
+  tags?: string[];
+  product_type?: string;
+  vendor?: string;
 }
 
 export interface ProductVariant {
@@ -58,6 +62,10 @@ export interface ProductVariant {
   inventory_quantity: number;
   price: string;
   sku?: string;
+  barcode?: string;
+  locations?: Record<string, number>; // Location name → available, when fetched
+  compare_at_price?: string | null;
+  cost?: string | null; // Unit cost, only from GraphQL searches
 }
 
 export interface InventoryLevel {
@@ -65,7 +73,13 @@ export interface InventoryLevel {
   secondary: number;
 }
 
+// InventoryLevel plus the quantity at every configured store, keyed by name
+export interface LocationLevels extends InventoryLevel {
+  [locationName: string]: number;
+}
+
 export interface LocationConfig {
+  locations: LocationInfo[]; // Every configured store, current one first
   primary_location: {
     id: string;
     name: string;
@@ -87,6 +101,21 @@ export interface StatusResponse {
   message: string;
 }
 
+// Error returned by Rust commands (status is set for Shopify API errors)
+export interface AppError {
+  kind:
+    | "network"
+    | "shopify_api"
+    | "firebase"
+    | "not_found"
+    | "conflict"
+    | "parse"
+    | "config"
+    | "other";
+  message: string;
+  status?: number;
+}
+
 // Enhanced status response that includes product status change information
 export interface EnhancedStatusResponse {
   status: string;
@@ -134,6 +163,8 @@ export interface VariantModificationHistory {
   inventory_item_id: string;
   app_net_change: number; // Total net change from app in period
   current_quantity: number;
+  shopify_net_change: number; // Net change seen on Shopify without an app log
+  discrepancy: number; // current_quantity minus the start quantity plus app changes
   daily_modifications: DailyModificationGroup[]; // Daily breakdown
 }
 
@@ -141,6 +172,8 @@ export interface DailyModificationGroup {
   date: string; // YYYY-MM-DD format for grouping
   app_net_change: number; // Net change from app for this date
   app_details: ModificationDetail[]; // Individual app modifications
+  shopify_net_change: number; // Net change from Shopify for this date
+  shopify_details: ModificationDetail[]; // Shopify movements without an app log
 }
 
 export interface ModificationDetail {
@@ -164,6 +197,8 @@ export type TimeRangeOption = {
 
 export type ToastType = "success" | "error" | "warning" | "info";
 
+export type ProductStatus = "active" | "draft" | "archived";
+
 export interface Toast {
   message: string;
   type: ToastType;
@@ -198,9 +233,25 @@ export interface CheckRequest {
   variant_id?: number;
   variant_name?: string;
   image_url?: string;
+  closing_notes?: string;
+}
+
+// A variant's Shopify total next to the sum of its location levels
+export interface VariantInventoryAudit {
+  variant_title: string;
+  inventory_item_id: string;
+  inventory_quantity: number;
+  location_total: number;
+  difference: number;
+}
+
+// Result of uploading logs kept locally while Firebase was unreachable
+export interface SyncResult {
+  synced: number;
+  still_pending: number;
+  errors: string[];
 }
 
 export interface CheckRequestWithId extends CheckRequest {
   id: string; // Document ID from Firebase
-  closing_notes?: string;
 }